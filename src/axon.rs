@@ -0,0 +1,1080 @@
+#[cfg(feature = "filter")]
+use crate::{CmpOp, Filter, Path};
+use crate::{Position, Qname, TagName, Value};
+use thiserror::Error;
+
+/// Names which have a special meaning in Axon, and cannot be used
+/// as variables or function names.
+const KEYWORDS: [&str; 15] = [
+    "and", "catch", "defcomp", "do", "else", "end", "false", "if", "not",
+    "null", "or", "return", "throw", "true", "try",
+];
+
+/// Return true if the name is an Axon keyword.
+pub(crate) fn is_axon_keyword(name: &str) -> bool {
+    KEYWORDS.contains(&name)
+}
+
+/// An Axon expression, which can be rendered as Axon source code.
+///
+/// Literals are rendered with the `to_axon_code` functions of the crate's
+/// types, so strings, units and uris are escaped. Names are `TagName`s, and
+/// the functions which create variables, parameters, definitions,
+/// assignments and components reject Axon keywords, so names are always
+/// valid Axon identifiers. Parentheses are added where operator precedence
+/// requires them.
+///
+/// Expressions which were parsed from Axon source code also have the range
+/// of the source code they were parsed from. Two expressions are equal if
+/// they have the same structure, regardless of their source ranges.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::{BinaryOp, Expr, Param, Qname, TagName};
+/// let x = TagName::new("x".to_owned()).unwrap();
+/// let map = Qname::new("map".to_owned()).unwrap();
+/// let read_all = Qname::new("readAll".to_owned()).unwrap();
+///
+/// let site = Expr::var(TagName::new("site".to_owned()).unwrap()).unwrap();
+/// let dis = Expr::var(TagName::new("dis".to_owned()).unwrap()).unwrap();
+/// let filter = site.and(dis.binary(BinaryOp::Eq, Expr::literal("Main \"Plant\"")));
+/// let expr = Expr::call(read_all, vec![filter]).dot_call(
+///     map,
+///     vec![Expr::lambda(
+///         vec![Param::new(x.clone()).unwrap()],
+///         Expr::var(x).unwrap().get(TagName::new("dis".to_owned()).unwrap()),
+///     )],
+/// );
+/// assert_eq!(
+///     expr.to_axon_code(),
+///     "readAll(site and dis == \"Main \\\"Plant\\\"\").map((x) => x->dis)"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Expr {
+    kind: ExprKind,
+    range: Option<SourceRange>,
+}
+
+/// The different kinds of Axon expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    /// A literal value, like `1kW` or `"hello"`.
+    Literal(Value),
+    /// A variable, like `x`.
+    Var(TagName),
+    /// A list, like `[a, b]`.
+    List(Vec<Expr>),
+    /// A dict, like `{site, dis: "Site"}`.
+    Dict(Vec<(TagName, Expr)>),
+    /// A function call, like `parseNumber("1")`.
+    Call {
+        func: Qname,
+        args: Vec<Expr>,
+    },
+    /// A dotted function call, like `x.parseNumber()`, which passes the
+    /// target as the first argument. The parentheses are optional when
+    /// there are no other arguments, like `x.dis`.
+    DotCall {
+        target: Box<Expr>,
+        func: Qname,
+        args: Vec<Expr>,
+    },
+    /// A tag access which dereferences a ref if necessary, like `x->dis`.
+    Get {
+        target: Box<Expr>,
+        name: TagName,
+    },
+    /// An index into a list or dict, like `x[0]`.
+    Index {
+        target: Box<Expr>,
+        index: Box<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    /// A function, like `(a, b: 0) => a + b`.
+    Lambda {
+        params: Vec<Param>,
+        body: Box<Expr>,
+    },
+    /// A `do`/`end` block, whose value is the value of its last expression.
+    Block(Vec<Expr>),
+    /// A new variable definition, like `x: 1`.
+    Define {
+        name: TagName,
+        value: Box<Expr>,
+    },
+    /// An assignment to an existing variable, like `x = 1`.
+    Assign {
+        name: TagName,
+        value: Box<Expr>,
+    },
+    If {
+        cond: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Option<Box<Expr>>,
+    },
+    /// A `try`/`catch` expression. The variable, if present, holds the
+    /// caught exception.
+    Try {
+        body: Box<Expr>,
+        var: Option<TagName>,
+        handler: Box<Expr>,
+    },
+    Return(Box<Expr>),
+    Throw(Box<Expr>),
+    /// A SkySpark component, with cells which are declared with their
+    /// metadata, and a body which is evaluated when the cells change.
+    Defcomp {
+        cells: Vec<(TagName, Expr)>,
+        body: Vec<Expr>,
+    },
+}
+
+/// A parameter of an Axon function, with an optional default value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    name: TagName,
+    default: Option<Expr>,
+}
+
+impl Param {
+    /// Create a new `Param` with no default value. Returns an error if the
+    /// name is an Axon keyword.
+    pub fn new(name: TagName) -> Result<Self, KeywordError> {
+        Ok(Self {
+            name: check_keyword(name)?,
+            default: None,
+        })
+    }
+
+    /// Create a new `Param` with a default value. Returns an error if the
+    /// name is an Axon keyword.
+    pub fn with_default(
+        name: TagName,
+        default: Expr,
+    ) -> Result<Self, KeywordError> {
+        Ok(Self {
+            name: check_keyword(name)?,
+            default: Some(default),
+        })
+    }
+
+    /// Return the name of this parameter.
+    pub fn name(&self) -> &TagName {
+        &self.name
+    }
+
+    /// Return the default value of this parameter, if present.
+    pub fn default(&self) -> Option<&Expr> {
+        self.default.as_ref()
+    }
+}
+
+impl std::convert::TryFrom<TagName> for Param {
+    type Error = KeywordError;
+
+    fn try_from(name: TagName) -> Result<Self, Self::Error> {
+        Self::new(name)
+    }
+}
+
+/// An error indicating that an Axon keyword was used as the name of a
+/// variable, parameter or function.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("The name {name} is an Axon keyword")]
+pub struct KeywordError {
+    name: String,
+}
+
+impl KeywordError {
+    /// Return the name which is a keyword.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Return the name, or an error if it is an Axon keyword.
+fn check_keyword(name: TagName) -> Result<TagName, KeywordError> {
+    if is_axon_keyword(name.as_ref()) {
+        Err(KeywordError {
+            name: name.into_string(),
+        })
+    } else {
+        Ok(name)
+    }
+}
+
+/// A range of source code, from the start position (inclusive) to the end
+/// position (exclusive).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SourceRange {
+    start: Position,
+    end: Position,
+}
+
+impl SourceRange {
+    #[cfg(feature = "axon")]
+    pub(crate) fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// Return the position of the first character in this range.
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Return the position just after the last character in this range.
+    pub fn end(&self) -> Position {
+        self.end
+    }
+}
+
+impl std::fmt::Display for SourceRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// The precedence of each kind of expression, from lowest to highest. An
+/// expression is wrapped in parentheses when it is used somewhere that
+/// requires a higher precedence.
+pub(crate) const STATEMENT: u8 = 0;
+pub(crate) const CONTROL: u8 = 1;
+pub(crate) const OR: u8 = 2;
+pub(crate) const AND: u8 = 3;
+pub(crate) const COMPARE: u8 = 4;
+pub(crate) const RANGE: u8 = 5;
+pub(crate) const ADD: u8 = 6;
+pub(crate) const MULTIPLY: u8 = 7;
+pub(crate) const UNARY: u8 = 8;
+pub(crate) const POSTFIX: u8 = 9;
+pub(crate) const ATOM: u8 = 10;
+
+impl Expr {
+    /// Create a new `Expr`. This does not check names, so it is only used
+    /// by the checked constructors and the parser.
+    pub(crate) fn new(kind: ExprKind) -> Self {
+        Self { kind, range: None }
+    }
+
+    /// Create a new literal expression.
+    pub fn literal<T: Into<Value>>(value: T) -> Self {
+        Self::new(ExprKind::Literal(value.into()))
+    }
+
+    /// Create a new variable expression. Returns an error if the name is
+    /// an Axon keyword.
+    pub fn var(name: TagName) -> Result<Self, KeywordError> {
+        Ok(Self::new(ExprKind::Var(check_keyword(name)?)))
+    }
+
+    /// Create a new list expression.
+    pub fn list(items: Vec<Expr>) -> Self {
+        Self::new(ExprKind::List(items))
+    }
+
+    /// Create a new dict expression.
+    pub fn dict(entries: Vec<(TagName, Expr)>) -> Self {
+        Self::new(ExprKind::Dict(entries))
+    }
+
+    /// Create a new function call expression.
+    pub fn call(func: Qname, args: Vec<Expr>) -> Self {
+        Self::new(ExprKind::Call { func, args })
+    }
+
+    /// Return a dotted call of the function on this expression, like
+    /// `self.func(args)`.
+    pub fn dot_call(self, func: Qname, args: Vec<Expr>) -> Self {
+        Self::new(ExprKind::DotCall {
+            target: Box::new(self),
+            func,
+            args,
+        })
+    }
+
+    /// Return an expression getting the tag from this expression, like
+    /// `self->name`.
+    pub fn get(self, name: TagName) -> Self {
+        Self::new(ExprKind::Get {
+            target: Box::new(self),
+            name,
+        })
+    }
+
+    /// Return an expression indexing into this expression, like
+    /// `self[index]`.
+    pub fn index(self, index: Expr) -> Self {
+        Self::new(ExprKind::Index {
+            target: Box::new(self),
+            index: Box::new(index),
+        })
+    }
+
+    /// Create a new unary operation.
+    pub fn unary(op: UnaryOp, operand: Expr) -> Self {
+        Self::new(ExprKind::Unary {
+            op,
+            operand: Box::new(operand),
+        })
+    }
+
+    /// Return a binary operation with this expression on the left.
+    pub fn binary(self, op: BinaryOp, rhs: Expr) -> Self {
+        Self::new(ExprKind::Binary {
+            op,
+            lhs: Box::new(self),
+            rhs: Box::new(rhs),
+        })
+    }
+
+    /// Return the expression `self and rhs`.
+    pub fn and(self, rhs: Expr) -> Self {
+        self.binary(BinaryOp::And, rhs)
+    }
+
+    /// Return the expression `self or rhs`.
+    pub fn or(self, rhs: Expr) -> Self {
+        self.binary(BinaryOp::Or, rhs)
+    }
+
+    /// Create a new function expression.
+    pub fn lambda(params: Vec<Param>, body: Expr) -> Self {
+        Self::new(ExprKind::Lambda {
+            params,
+            body: Box::new(body),
+        })
+    }
+
+    /// Create a new `do`/`end` block expression.
+    pub fn block(exprs: Vec<Expr>) -> Self {
+        Self::new(ExprKind::Block(exprs))
+    }
+
+    /// Create a new variable definition, like `name: value`. Returns an
+    /// error if the name is an Axon keyword.
+    pub fn define(name: TagName, value: Expr) -> Result<Self, KeywordError> {
+        Ok(Self::new(ExprKind::Define {
+            name: check_keyword(name)?,
+            value: Box::new(value),
+        }))
+    }
+
+    /// Create a new assignment to an existing variable, like `name = value`.
+    /// Returns an error if the name is an Axon keyword.
+    pub fn assign(name: TagName, value: Expr) -> Result<Self, KeywordError> {
+        Ok(Self::new(ExprKind::Assign {
+            name: check_keyword(name)?,
+            value: Box::new(value),
+        }))
+    }
+
+    /// Create a new `if` expression, with an optional `else` branch.
+    pub fn if_else(cond: Expr, then: Expr, otherwise: Option<Expr>) -> Self {
+        Self::new(ExprKind::If {
+            cond: Box::new(cond),
+            then: Box::new(then),
+            otherwise: otherwise.map(Box::new),
+        })
+    }
+
+    /// Create a new `try`/`catch` expression. Returns an error if the name
+    /// of the caught exception is an Axon keyword.
+    pub fn try_catch(
+        body: Expr,
+        var: Option<TagName>,
+        handler: Expr,
+    ) -> Result<Self, KeywordError> {
+        Ok(Self::new(ExprKind::Try {
+            body: Box::new(body),
+            var: var.map(check_keyword).transpose()?,
+            handler: Box::new(handler),
+        }))
+    }
+
+    /// Create a new `return` expression.
+    pub fn ret(value: Expr) -> Self {
+        Self::new(ExprKind::Return(Box::new(value)))
+    }
+
+    /// Create a new `throw` expression.
+    pub fn throw(value: Expr) -> Self {
+        Self::new(ExprKind::Throw(Box::new(value)))
+    }
+
+    /// Create a new SkySpark component, with cells declared with their
+    /// metadata and a body which is evaluated when the cells change.
+    /// Returns an error if the name of a cell is an Axon keyword.
+    pub fn defcomp(
+        cells: Vec<(TagName, Expr)>,
+        body: Vec<Expr>,
+    ) -> Result<Self, KeywordError> {
+        let cells = cells
+            .into_iter()
+            .map(|(name, meta)| Ok((check_keyword(name)?, meta)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(ExprKind::Defcomp { cells, body }))
+    }
+
+    /// Return the kind of this expression, which contains its parts.
+    pub fn kind(&self) -> &ExprKind {
+        &self.kind
+    }
+
+    /// Consume this expression, returning its kind.
+    pub fn into_kind(self) -> ExprKind {
+        self.kind
+    }
+
+    /// Return the range of source code this expression was parsed from, if
+    /// it was parsed.
+    pub fn range(&self) -> Option<SourceRange> {
+        self.range
+    }
+
+    /// Return this expression with its source range replaced.
+    #[cfg(feature = "axon")]
+    pub(crate) fn with_range(self, range: SourceRange) -> Self {
+        Self {
+            kind: self.kind,
+            range: Some(range),
+        }
+    }
+
+    /// Return the expressions directly inside this expression, in the order
+    /// they appear in source code.
+    #[cfg(feature = "axon")]
+    pub(crate) fn children(&self) -> Vec<&Expr> {
+        match &self.kind {
+            ExprKind::Literal(_) | ExprKind::Var(_) => Vec::new(),
+            ExprKind::List(items) | ExprKind::Block(items) => {
+                items.iter().collect()
+            }
+            ExprKind::Dict(entries) => {
+                entries.iter().map(|(_, value)| value).collect()
+            }
+            ExprKind::Call { args, .. } => args.iter().collect(),
+            ExprKind::DotCall { target, args, .. } => {
+                std::iter::once(&**target).chain(args).collect()
+            }
+            ExprKind::Get { target, .. } => vec![target],
+            ExprKind::Index { target, index } => vec![target, index],
+            ExprKind::Unary { operand, .. } => vec![operand],
+            ExprKind::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+            ExprKind::Lambda { params, body } => params
+                .iter()
+                .filter_map(|param| param.default())
+                .chain(std::iter::once(&**body))
+                .collect(),
+            ExprKind::Define { value, .. }
+            | ExprKind::Assign { value, .. }
+            | ExprKind::Return(value)
+            | ExprKind::Throw(value) => vec![value],
+            ExprKind::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let mut children = vec![&**cond, &**then];
+                children.extend(otherwise.as_deref());
+                children
+            }
+            ExprKind::Try { body, handler, .. } => vec![body, handler],
+            ExprKind::Defcomp { cells, body } => {
+                cells.iter().map(|(_, meta)| meta).chain(body).collect()
+            }
+        }
+    }
+
+    /// Return this expression as Axon source code.
+    pub fn to_axon_code(&self) -> String {
+        let mut code = String::new();
+        self.write(&mut code);
+        code
+    }
+
+    pub(crate) fn precedence(&self) -> u8 {
+        match &self.kind {
+            ExprKind::Literal(value) => literal_precedence(value),
+            ExprKind::Var(_) | ExprKind::List(_) | ExprKind::Dict(_) => ATOM,
+            ExprKind::Call { .. }
+            | ExprKind::DotCall { .. }
+            | ExprKind::Get { .. }
+            | ExprKind::Index { .. } => POSTFIX,
+            ExprKind::Unary { .. } => UNARY,
+            ExprKind::Binary { op, .. } => op.precedence(),
+            ExprKind::Lambda { .. }
+            | ExprKind::Block(_)
+            | ExprKind::If { .. }
+            | ExprKind::Try { .. }
+            | ExprKind::Return(_)
+            | ExprKind::Throw(_)
+            | ExprKind::Defcomp { .. } => CONTROL,
+            ExprKind::Define { .. } | ExprKind::Assign { .. } => STATEMENT,
+        }
+    }
+
+    /// Write this expression, wrapping it in parentheses if its precedence
+    /// is lower than the given precedence.
+    fn write_operand(&self, code: &mut String, precedence: u8) {
+        if self.precedence() < precedence {
+            code.push('(');
+            self.write(code);
+            code.push(')');
+        } else {
+            self.write(code);
+        }
+    }
+
+    fn write(&self, code: &mut String) {
+        match &self.kind {
+            ExprKind::Literal(value) => code.push_str(&value.to_axon_code()),
+            ExprKind::Var(name) => code.push_str(name.as_ref()),
+            ExprKind::List(items) => {
+                code.push('[');
+                write_list(code, items);
+                code.push(']');
+            }
+            ExprKind::Dict(entries) => {
+                code.push('{');
+                for (index, (name, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        code.push_str(", ");
+                    }
+                    match value.kind() {
+                        ExprKind::Literal(Value::Marker) => {
+                            code.push_str(name.as_ref())
+                        }
+                        ExprKind::Literal(Value::RemoveMarker) => {
+                            code.push('-');
+                            code.push_str(name.as_ref());
+                        }
+                        _ => {
+                            code.push_str(name.as_ref());
+                            code.push_str(": ");
+                            value.write_operand(code, CONTROL);
+                        }
+                    }
+                }
+                code.push('}');
+            }
+            ExprKind::Call { func, args } => {
+                code.push_str(func.qname());
+                code.push('(');
+                write_list(code, args);
+                code.push(')');
+            }
+            ExprKind::DotCall { target, func, args } => {
+                target.write_operand(code, POSTFIX);
+                code.push('.');
+                code.push_str(func.qname());
+                code.push('(');
+                write_list(code, args);
+                code.push(')');
+            }
+            ExprKind::Get { target, name } => {
+                target.write_operand(code, POSTFIX);
+                code.push_str("->");
+                code.push_str(name.as_ref());
+            }
+            ExprKind::Index { target, index } => {
+                target.write_operand(code, POSTFIX);
+                code.push('[');
+                index.write_operand(code, CONTROL);
+                code.push(']');
+            }
+            ExprKind::Unary { op, operand } => {
+                code.push_str(op.symbol());
+                // Avoid writing two minus signs next to each other:
+                let mut operand_code = String::new();
+                operand.write_operand(&mut operand_code, UNARY);
+                if *op == UnaryOp::Neg && operand_code.starts_with('-') {
+                    operand_code = format!("({})", operand_code);
+                }
+                code.push_str(&operand_code);
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let precedence = op.precedence();
+                lhs.write_operand(code, op.lhs_precedence());
+                if *op == BinaryOp::Range {
+                    code.push_str(op.symbol());
+                } else {
+                    code.push(' ');
+                    code.push_str(op.symbol());
+                    code.push(' ');
+                }
+                rhs.write_operand(code, precedence + 1);
+            }
+            ExprKind::Lambda { params, body } => {
+                code.push('(');
+                for (index, param) in params.iter().enumerate() {
+                    if index > 0 {
+                        code.push_str(", ");
+                    }
+                    code.push_str(param.name().as_ref());
+                    if let Some(default) = param.default() {
+                        code.push_str(": ");
+                        default.write_operand(code, CONTROL);
+                    }
+                }
+                code.push_str(") => ");
+                body.write_operand(code, CONTROL);
+            }
+            ExprKind::Block(exprs) => {
+                code.push_str("do ");
+                for expr in exprs {
+                    expr.write_operand(code, STATEMENT);
+                    code.push_str("; ");
+                }
+                code.push_str("end");
+            }
+            ExprKind::Define { name, value } => {
+                code.push_str(name.as_ref());
+                code.push_str(": ");
+                value.write_operand(code, CONTROL);
+            }
+            ExprKind::Assign { name, value } => {
+                code.push_str(name.as_ref());
+                code.push_str(" = ");
+                value.write_operand(code, CONTROL);
+            }
+            ExprKind::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                code.push_str("if (");
+                cond.write_operand(code, CONTROL);
+                code.push_str(") ");
+                // A nested if in the first branch would take the else:
+                then.write_operand(code, OR);
+                if let Some(otherwise) = otherwise {
+                    code.push_str(" else ");
+                    otherwise.write_operand(code, CONTROL);
+                }
+            }
+            ExprKind::Try { body, var, handler } => {
+                code.push_str("try ");
+                body.write_operand(code, CONTROL);
+                code.push_str(" catch ");
+                if let Some(var) = var {
+                    code.push_str(&format!("({}) ", var));
+                }
+                handler.write_operand(code, CONTROL);
+            }
+            ExprKind::Return(value) => {
+                code.push_str("return ");
+                value.write_operand(code, CONTROL);
+            }
+            ExprKind::Throw(value) => {
+                code.push_str("throw ");
+                value.write_operand(code, CONTROL);
+            }
+            ExprKind::Defcomp { cells, body } => {
+                // Cells must be on separate lines:
+                code.push_str("defcomp\n");
+                for (name, meta) in cells {
+                    code.push_str(name.as_ref());
+                    code.push_str(": ");
+                    meta.write_operand(code, CONTROL);
+                    code.push('\n');
+                }
+                code.push_str("do ");
+                for expr in body {
+                    expr.write_operand(code, STATEMENT);
+                    code.push_str("; ");
+                }
+                code.push_str("end\nend");
+            }
+        }
+    }
+}
+
+/// Return the precedence of a literal. Negative numbers start with a unary
+/// minus, so they need parentheses before a dotted call. Date times are
+/// written as a call to `parseDateTime`.
+fn literal_precedence(value: &Value) -> u8 {
+    match value {
+        Value::Number(number) if number.to_axon_code().starts_with('-') => {
+            UNARY
+        }
+        Value::DateTime(_) => POSTFIX,
+        _ => ATOM,
+    }
+}
+
+fn write_list(code: &mut String, exprs: &[Expr]) {
+    for (index, expr) in exprs.iter().enumerate() {
+        if index > 0 {
+            code.push_str(", ");
+        }
+        expr.write_operand(code, CONTROL);
+    }
+}
+
+impl std::cmp::PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_axon_code())
+    }
+}
+
+impl std::convert::From<Value> for Expr {
+    fn from(value: Value) -> Self {
+        Self::new(ExprKind::Literal(value))
+    }
+}
+
+#[cfg(feature = "filter")]
+impl std::convert::TryFrom<Filter> for Expr {
+    type Error = KeywordError;
+
+    /// Convert a filter into the equivalent Axon expression, which can be
+    /// passed to functions like `readAll`. Returns an error if a path in
+    /// the filter starts with an Axon keyword.
+    fn try_from(filter: Filter) -> Result<Self, Self::Error> {
+        let expr = match filter {
+            Filter::Has(path) => path_expr(path)?,
+            Filter::Missing(path) => {
+                Self::unary(UnaryOp::Not, path_expr(path)?)
+            }
+            Filter::Cmp { path, op, value } => {
+                path_expr(path)?.binary(op.into(), Self::literal(value))
+            }
+            Filter::IsA(symbol) => Self::literal(symbol),
+            Filter::And(a, b) => Self::try_from(*a)?.and(Self::try_from(*b)?),
+            Filter::Or(a, b) => Self::try_from(*a)?.or(Self::try_from(*b)?),
+        };
+        Ok(expr)
+    }
+}
+
+/// Convert a filter path like `equipRef->siteRef->dis` into the
+/// equivalent expression.
+#[cfg(feature = "filter")]
+fn path_expr(path: Path) -> Result<Expr, KeywordError> {
+    let mut names = path.names().iter().cloned();
+    let first = names.next().expect("paths should have at least one name");
+    Ok(names.fold(Expr::var(first)?, |expr, name| expr.get(name)))
+}
+
+/// A unary operator in an Axon expression.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+impl UnaryOp {
+    pub(crate) fn symbol(&self) -> &'static str {
+        match self {
+            Self::Not => "not ",
+            Self::Neg => "-",
+        }
+    }
+}
+
+/// A binary operator in an Axon expression.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    /// The comparison operator `<=>`, which returns -1, 0 or 1.
+    Cmp,
+    /// The range operator `..`, like `2024-01-01..2024-01-31`.
+    Range,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    /// Return the Axon symbol for this operator, like `+` or `and`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Or => "or",
+            Self::And => "and",
+            Self::Eq => "==",
+            Self::NotEq => "!=",
+            Self::Lt => "<",
+            Self::LtEq => "<=",
+            Self::Gt => ">",
+            Self::GtEq => ">=",
+            Self::Cmp => "<=>",
+            Self::Range => "..",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+        }
+    }
+
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Self::Or => OR,
+            Self::And => AND,
+            Self::Eq
+            | Self::NotEq
+            | Self::Lt
+            | Self::LtEq
+            | Self::Gt
+            | Self::GtEq
+            | Self::Cmp => COMPARE,
+            Self::Range => RANGE,
+            Self::Add | Self::Sub => ADD,
+            Self::Mul | Self::Div => MULTIPLY,
+        }
+    }
+
+    /// Return the precedence required for the left operand. Comparisons
+    /// and ranges cannot be chained, and other operators are
+    /// left associative.
+    pub(crate) fn lhs_precedence(&self) -> u8 {
+        match self.precedence() {
+            COMPARE | RANGE => self.precedence() + 1,
+            precedence => precedence,
+        }
+    }
+}
+
+impl std::fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+#[cfg(feature = "filter")]
+impl std::convert::From<CmpOp> for BinaryOp {
+    fn from(op: CmpOp) -> Self {
+        match op {
+            CmpOp::Eq => Self::Eq,
+            CmpOp::NotEq => Self::NotEq,
+            CmpOp::Lt => Self::Lt,
+            CmpOp::LtEq => Self::LtEq,
+            CmpOp::Gt => Self::Gt,
+            CmpOp::GtEq => Self::GtEq,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BinaryOp, Expr, Param, UnaryOp};
+    use crate::{
+        Coord, DateTime, Dict, Grid, Number, Qname, TagName, Uri, Value, Xstr,
+    };
+
+    fn name(s: &str) -> TagName {
+        TagName::new(s.to_owned()).unwrap()
+    }
+
+    fn var(s: &str) -> Expr {
+        Expr::var(name(s)).unwrap()
+    }
+
+    fn num(n: f64) -> Expr {
+        Expr::literal(Number::new_unitless(n))
+    }
+
+    fn func(s: &str) -> Qname {
+        Qname::new(s.to_owned()).unwrap()
+    }
+
+    #[test]
+    fn literals_are_escaped() {
+        let s = Expr::literal("a \"quote\" $x \\ \n");
+        assert_eq!(s.to_axon_code(), r#""a \"quote\" \$x \\ \n""#);
+
+        let uri = Expr::literal(Uri::new("http://a/`b`\\".to_owned()));
+        assert_eq!(uri.to_axon_code(), r"`http://a/\`b\`\\`");
+
+        let xstr = Xstr::new("Color".to_owned(), "r\"ed".to_owned()).unwrap();
+        assert_eq!(
+            Expr::literal(xstr).to_axon_code(),
+            r#"xstr("Color", "r\"ed")"#
+        );
+
+        let unit = Number::new(1.5, Some("\")+evil(\"".to_owned()));
+        assert_eq!(
+            Expr::literal(unit).to_axon_code(),
+            r#"1.5.as("\")+evil(\"")"#
+        );
+        let nan = Number::new(f64::NAN, Some("\"".to_owned()));
+        assert_eq!(Expr::literal(nan).to_axon_code(), r#"nan().as("\"")"#);
+        let kw = Number::new(1.5, Some("kW".to_owned()));
+        assert_eq!(Expr::literal(kw).to_axon_code(), "1.5kW");
+    }
+
+    #[test]
+    fn collection_literals_work() {
+        let mut dict = Dict::new();
+        dict.insert(name("site"), Value::Marker);
+        dict.insert(name("dis"), "Site".into());
+        dict.insert(name("geo"), Coord::new(1.5, -2.0).into());
+        dict.insert(name("na"), Value::Na);
+        let list = Value::List(vec![Value::Null, true.into(), dict.into()]);
+        assert_eq!(
+            Expr::literal(list).to_axon_code(),
+            "[null, true, {site, dis: \"Site\", geo: coord(1.5, -2), na: na()}]"
+        );
+
+        let mut grid = Grid::from_rows(vec![Dict::new()]);
+        grid.meta_mut().insert(name("view"), "table".into());
+        grid.add_column("b", Dict::new()).unwrap();
+        let mut meta = Dict::new();
+        meta.insert(name("dis"), "A".into());
+        grid.add_column("a", meta).unwrap();
+        assert_eq!(
+            Expr::literal(grid).to_axon_code(),
+            "[{}].toGrid.addMeta({view: \"table\"})\
+             .addCol(\"b\", row => null)\
+             .addCol(\"a\", row => null).addColMeta(\"a\", {dis: \"A\"})"
+        );
+    }
+
+    #[test]
+    fn precedence_works() {
+        let a = var("a");
+        let b = var("b");
+        let c = var("c");
+
+        let sum = a.clone().binary(BinaryOp::Add, b.clone());
+        let product = sum.clone().binary(BinaryOp::Mul, c.clone());
+        assert_eq!(product.to_axon_code(), "(a + b) * c");
+
+        let right = a.clone().binary(BinaryOp::Sub, sum.clone());
+        assert_eq!(right.to_axon_code(), "a - (a + b)");
+        let left = sum.clone().binary(BinaryOp::Sub, c.clone());
+        assert_eq!(left.to_axon_code(), "a + b - c");
+
+        let or = a.clone().or(b.clone());
+        assert_eq!(or.clone().and(c.clone()).to_axon_code(), "(a or b) and c");
+        assert_eq!(
+            c.clone().or(a.clone().and(b.clone())).to_axon_code(),
+            "c or a and b"
+        );
+
+        let lt = a.clone().binary(BinaryOp::Lt, b.clone());
+        let eq = lt.binary(BinaryOp::Eq, c.clone());
+        assert_eq!(eq.to_axon_code(), "(a < b) == c");
+
+        let not = Expr::unary(UnaryOp::Not, or);
+        assert_eq!(not.to_axon_code(), "not (a or b)");
+
+        let neg = Expr::unary(UnaryOp::Neg, num(-1.0));
+        assert_eq!(neg.to_axon_code(), "-(-1)");
+
+        let target = num(-1.0).dot_call(func("abs"), vec![]);
+        assert_eq!(target.to_axon_code(), "(-1).abs()");
+        let target = sum.dot_call(func("abs"), vec![]);
+        assert_eq!(target.to_axon_code(), "(a + b).abs()");
+
+        let dt: DateTime = "2024-03-01T08:00:00Z UTC".parse().unwrap();
+        let target = Expr::literal(dt).dot_call(func("date"), vec![]);
+        assert_eq!(
+            target.to_axon_code(),
+            "parseDateTime(\"2024-03-01T08:00:00Z UTC\", \
+             \"YYYY-MM-DD'T'hh:mm:SS.FFFFFFFFFz zzzz\").date()"
+        );
+    }
+
+    #[test]
+    fn control_flow_works() {
+        let body = Expr::block(vec![
+            Expr::define(name("y"), var("x").index(num(0.0))).unwrap(),
+            Expr::if_else(
+                var("y").binary(BinaryOp::Gt, num(1.0)),
+                Expr::ret(var("y")),
+                Some(Expr::throw(Expr::literal("too small"))),
+            ),
+        ]);
+        let lambda = Expr::lambda(vec![Param::new(name("x")).unwrap()], body);
+        assert_eq!(
+            lambda.to_axon_code(),
+            "(x) => do y: x[0]; if (y > 1) (return y) else throw \"too small\"; end"
+        );
+
+        let nested = Expr::if_else(
+            var("a"),
+            Expr::if_else(var("b"), num(1.0), None),
+            Some(num(2.0)),
+        );
+        assert_eq!(nested.to_axon_code(), "if (a) (if (b) 1) else 2");
+
+        let caught = Expr::try_catch(
+            Expr::call(func("foo"), vec![]),
+            Some(name("ex")),
+            var("ex").get(name("dis")),
+        )
+        .unwrap();
+        let sum = caught.binary(BinaryOp::Add, num(1.0));
+        assert_eq!(sum.to_axon_code(), "(try foo() catch (ex) ex->dis) + 1");
+
+        let cells = vec![(name("a"), Expr::dict(vec![]))];
+        let assign = Expr::assign(name("a"), num(1.0)).unwrap();
+        let comp = Expr::defcomp(cells, vec![assign]).unwrap();
+        assert_eq!(comp.to_axon_code(), "defcomp\na: {}\ndo a = 1; end\nend");
+    }
+
+    #[cfg(feature = "filter")]
+    #[test]
+    fn filters_work() {
+        use std::convert::TryFrom;
+
+        let filter: crate::Filter =
+            "equipRef->siteRef == @abc and not point or ^ahu"
+                .parse()
+                .unwrap();
+        let expr = Expr::try_from(filter).unwrap();
+        let expr = Expr::call(func("readAll"), vec![expr]);
+        assert_eq!(
+            expr.to_axon_code(),
+            "readAll(equipRef->siteRef == @abc and not point or ^ahu)"
+        );
+
+        let filter: crate::Filter = "return".parse().unwrap();
+        assert!(Expr::try_from(filter).is_err());
+    }
+
+    #[test]
+    fn keywords_are_rejected() {
+        for keyword in &["return", "do", "end", "if", "not", "null", "try"] {
+            assert!(Expr::var(name(keyword)).is_err());
+            assert!(Param::new(name(keyword)).is_err());
+            assert!(Expr::define(name(keyword), num(1.0)).is_err());
+            assert!(Expr::assign(name(keyword), num(1.0)).is_err());
+            let cells = vec![(name(keyword), Expr::dict(vec![]))];
+            assert!(Expr::defcomp(cells, vec![]).is_err());
+            let handler = num(1.0);
+            let caught =
+                Expr::try_catch(num(1.0), Some(name(keyword)), handler);
+            assert!(caught.is_err());
+            assert!(Qname::new(keyword.to_string()).is_err());
+        }
+
+        let err = Expr::var(name("return")).unwrap_err();
+        assert_eq!(err.name(), "return");
+        let dict = Expr::dict(vec![(name("return"), num(1.0))]);
+        assert_eq!(dict.to_axon_code(), "{return: 1}");
+    }
+}
@@ -0,0 +1,423 @@
+//! Static analysis of Axon source code, finding the functions, tags and refs
+//! which it references.
+use crate::axon::ExprKind;
+#[cfg(feature = "filter")]
+use crate::Filter;
+use crate::{
+    AxonSource, BinaryOp, Expr, Position, Qname, Ref, SourceRange, TagName,
+    Value,
+};
+use std::collections::HashSet;
+
+/// Functions which take a filter, and the index of the filter argument. The
+/// target of a dotted call is the first argument.
+const FILTER_FUNCS: [(&str, usize); 8] = [
+    ("filter", 1),
+    ("filterToFunc", 0),
+    ("read", 0),
+    ("readAll", 0),
+    ("readAllStream", 0),
+    ("readAllTagNames", 0),
+    ("readAllTagVals", 0),
+    ("readCount", 0),
+];
+
+/// Functions which take a dict followed by the name of a tag in the dict.
+const TAG_FUNCS: [&str; 6] = ["get", "has", "missing", "remove", "set", "trap"];
+
+/// Something referenced by Axon source code.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FindingKind {
+    /// A function call, like `readAll(site)` or `x.core::toStr()`. Calls of
+    /// local variables are not included.
+    Call(Qname),
+    /// A tag name used in a filter, like `site` in `readAll(site)`, or used
+    /// to access a dict, like `dis` in `x->dis`, `x["dis"]` or
+    /// `x.get("dis")`. Names in strings passed to `parseFilter` are
+    /// also included.
+    Tag(TagName),
+    /// A ref literal, like `@p:demo:r:123`, or a string passed
+    /// to `parseRef`.
+    Ref(Ref),
+}
+
+/// A function, tag or ref referenced by Axon source code, and the range of
+/// source code which references it.
+///
+/// For tags and refs, the range covers the name, literal or string
+/// containing the reference. For function calls, the range covers the whole
+/// call, including the target and arguments of a dotted call.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Finding {
+    kind: FindingKind,
+    range: SourceRange,
+}
+
+impl Finding {
+    /// Return what was found.
+    pub fn kind(&self) -> &FindingKind {
+        &self.kind
+    }
+
+    /// Return the range of source code where it was found.
+    pub fn range(&self) -> SourceRange {
+        self.range
+    }
+}
+
+impl AxonSource {
+    /// Return every function call, every tag name used in a filter or to
+    /// access a dict, and every ref literal in the source code, in the order
+    /// they appear. Statements which could not be parsed are not included.
+    ///
+    /// Names in a filter, like the first argument of `readAll`, are tag
+    /// names, apart from the values which tags are compared to.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{AxonSource, FindingKind};
+    /// let source = AxonSource::parse(
+    ///     "(floor) => readAll(equip and floor == floor).map(e => e->dis)",
+    /// );
+    /// let found: Vec<String> = source
+    ///     .findings()
+    ///     .iter()
+    ///     .map(|finding| match finding.kind() {
+    ///         FindingKind::Call(func) => format!("call {}", func),
+    ///         FindingKind::Tag(name) => format!("tag {}", name),
+    ///         FindingKind::Ref(id) => format!("ref {}", id),
+    ///     })
+    ///     .collect();
+    /// assert_eq!(
+    ///     found,
+    ///     vec!["call map", "call readAll", "tag equip", "tag floor", "tag dis"]
+    /// );
+    /// ```
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut scanner = Scanner {
+            findings: Vec::new(),
+            scopes: vec![HashSet::new()],
+        };
+        for expr in self.exprs() {
+            scanner.scan(expr, false);
+        }
+        let mut findings = scanner.findings;
+        findings.sort_by_key(|finding| finding.range.start());
+        findings
+    }
+}
+
+struct Scanner {
+    findings: Vec<Finding>,
+    /// The variables defined in each enclosing scope.
+    scopes: Vec<HashSet<TagName>>,
+}
+
+impl Scanner {
+    fn is_var(&self, name: &TagName) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+    }
+
+    fn define(&mut self, name: &TagName) {
+        self.scopes
+            .last_mut()
+            .expect("there is always a scope")
+            .insert(name.clone());
+    }
+
+    fn push(&mut self, kind: FindingKind, range: SourceRange) {
+        self.findings.push(Finding { kind, range });
+    }
+
+    /// Scan the expression. Names in a filter are tag names.
+    fn scan(&mut self, expr: &Expr, in_filter: bool) {
+        let range = range(expr);
+        match expr.kind() {
+            ExprKind::Literal(Value::Ref(id)) => {
+                self.push(FindingKind::Ref(id.clone()), range)
+            }
+            ExprKind::Literal(_) => (),
+            ExprKind::Var(name) => {
+                if in_filter {
+                    self.push(FindingKind::Tag(name.clone()), range);
+                }
+            }
+            ExprKind::Call { func, args } => {
+                let is_local = TagName::new(func.qname().to_owned())
+                    .is_some_and(|name| self.is_var(&name));
+                if !is_local {
+                    self.push(FindingKind::Call(func.clone()), range);
+                }
+                let args: Vec<&Expr> = args.iter().collect();
+                self.scan_args(func, &args);
+            }
+            ExprKind::DotCall { target, func, args } => {
+                self.push(FindingKind::Call(func.clone()), range);
+                let args: Vec<&Expr> =
+                    std::iter::once(&**target).chain(args).collect();
+                self.scan_args(func, &args);
+            }
+            ExprKind::Get { target, name } => {
+                self.scan(target, in_filter);
+                // The name is the last token of the expression:
+                let end = range.end();
+                let start = Position {
+                    line: end.line,
+                    column: end.column - name.to_string().chars().count(),
+                };
+                let name_range = SourceRange::new(start, end);
+                self.push(FindingKind::Tag(name.clone()), name_range);
+            }
+            ExprKind::Index { target, index } => {
+                self.scan(target, false);
+                self.scan_tag_name(index);
+                self.scan(index, false);
+            }
+            ExprKind::Unary { operand, .. } => self.scan(operand, in_filter),
+            ExprKind::Binary { op, lhs, rhs } => {
+                // Values compared to tags are not filters:
+                let is_logical = *op == BinaryOp::And || *op == BinaryOp::Or;
+                self.scan(lhs, in_filter);
+                self.scan(rhs, in_filter && is_logical);
+            }
+            ExprKind::Lambda { params, body } => {
+                for default in params.iter().filter_map(|param| param.default())
+                {
+                    self.scan(default, false);
+                }
+                let scope = params.iter().map(|param| param.name().clone());
+                self.scopes.push(scope.collect());
+                self.scan(body, false);
+                self.scopes.pop();
+            }
+            ExprKind::Block(exprs) => {
+                self.scopes.push(HashSet::new());
+                for expr in exprs {
+                    self.scan(expr, false);
+                }
+                self.scopes.pop();
+            }
+            ExprKind::Define { name, value } => {
+                // Defined first, so functions can call themselves:
+                self.define(name);
+                self.scan(value, false);
+            }
+            ExprKind::Try { body, var, handler } => {
+                self.scan(body, false);
+                self.scopes.push(var.iter().cloned().collect());
+                self.scan(handler, false);
+                self.scopes.pop();
+            }
+            ExprKind::Defcomp { cells, body } => {
+                let scope = cells.iter().map(|(name, _)| name.clone());
+                self.scopes.push(scope.collect());
+                for expr in cells.iter().map(|(_, meta)| meta).chain(body) {
+                    self.scan(expr, false);
+                }
+                self.scopes.pop();
+            }
+            ExprKind::List(_)
+            | ExprKind::Dict(_)
+            | ExprKind::Assign { .. }
+            | ExprKind::If { .. }
+            | ExprKind::Return(_)
+            | ExprKind::Throw(_) => {
+                for child in expr.children() {
+                    self.scan(child, false);
+                }
+            }
+        }
+    }
+
+    /// Scan the arguments of a function call, including the target of
+    /// a dotted call.
+    fn scan_args(&mut self, func: &Qname, args: &[&Expr]) {
+        let name = func.name();
+        let filter_index = FILTER_FUNCS
+            .iter()
+            .find(|(filter_func, _)| *filter_func == name)
+            .map(|(_, index)| *index);
+        for (index, arg) in args.iter().enumerate() {
+            self.scan(arg, filter_index == Some(index));
+        }
+
+        match (name, args) {
+            #[cfg(feature = "filter")]
+            ("parseFilter", [arg, ..]) => self.scan_filter_string(arg),
+            ("parseRef", [arg, ..]) => self.scan_ref_string(arg),
+            (name, [_, arg, ..]) if TAG_FUNCS.contains(&name) => {
+                self.scan_tag_name(arg)
+            }
+            _ => (),
+        }
+    }
+
+    /// Add the tag name in a string literal, if it is a valid tag name.
+    fn scan_tag_name(&mut self, expr: &Expr) {
+        if let Some(name) = string_literal(expr).and_then(TagName::new) {
+            self.push(FindingKind::Tag(name), range(expr));
+        }
+    }
+
+    /// Add the tag names in a string literal, if it is a valid filter.
+    #[cfg(feature = "filter")]
+    fn scan_filter_string(&mut self, expr: &Expr) {
+        let filter = string_literal(expr).and_then(|s| s.parse().ok());
+        if let Some(filter) = filter {
+            let mut names = Vec::new();
+            filter_tag_names(&filter, &mut names);
+            for name in names {
+                self.push(FindingKind::Tag(name), range(expr));
+            }
+        }
+    }
+
+    /// Add the ref in a string literal, if it is a valid ref. The leading
+    /// `@` is optional.
+    fn scan_ref_string(&mut self, expr: &Expr) {
+        let id = string_literal(expr).map(|s| match s.strip_prefix('@') {
+            Some(_) => s,
+            None => format!("@{}", s),
+        });
+        if let Some(id) = id.and_then(|id| Ref::new(id).ok()) {
+            self.push(FindingKind::Ref(id), range(expr));
+        }
+    }
+}
+
+/// Return the range of a parsed expression.
+fn range(expr: &Expr) -> SourceRange {
+    expr.range().expect("parsed exprs have a range")
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr.kind() {
+        ExprKind::Literal(Value::Str(s)) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "filter")]
+fn filter_tag_names(filter: &Filter, names: &mut Vec<TagName>) {
+    match filter {
+        Filter::Has(path)
+        | Filter::Missing(path)
+        | Filter::Cmp { path, .. } => {
+            names.extend(path.names().iter().cloned())
+        }
+        Filter::IsA(_) => (),
+        Filter::And(lhs, rhs) | Filter::Or(lhs, rhs) => {
+            filter_tag_names(lhs, names);
+            filter_tag_names(rhs, names);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FindingKind;
+    use crate::AxonSource;
+
+    /// Return each finding as a string, with its start position.
+    fn findings(source: &str) -> Vec<String> {
+        let source = AxonSource::parse(source);
+        assert!(source.errors().is_empty());
+        source
+            .findings()
+            .iter()
+            .map(|finding| {
+                let found = match finding.kind() {
+                    FindingKind::Call(func) => format!("call {}", func),
+                    FindingKind::Tag(name) => format!("tag {}", name),
+                    FindingKind::Ref(id) => format!("ref {}", id),
+                };
+                format!("{} {}", finding.range().start(), found)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn calls_work() {
+        assert_eq!(
+            findings("f: x => x.core::toStr\nf(now()).lower()"),
+            vec!["1:9 call core::toStr", "2:1 call lower", "2:3 call now",]
+        );
+    }
+
+    #[test]
+    fn filter_tags_work() {
+        let source = "(site) => do
+  area: 100ft²
+  readAll(equip and siteRef == site->id and area > area).size
+  read(not point, false)
+end";
+        assert_eq!(
+            findings(source),
+            vec![
+                "3:3 call size",
+                "3:3 call readAll",
+                "3:11 tag equip",
+                "3:21 tag siteRef",
+                "3:38 tag id",
+                "3:45 tag area",
+                "4:3 call read",
+                "4:12 tag point",
+            ]
+        );
+        assert_eq!(
+            findings("readAllStream(ahu).filter(equipRef->siteRef)"),
+            vec![
+                "1:1 call filter",
+                "1:1 call readAllStream",
+                "1:15 tag ahu",
+                "1:27 tag equipRef",
+                "1:37 tag siteRef",
+            ]
+        );
+    }
+
+    #[cfg(feature = "filter")]
+    #[test]
+    fn filter_string_tags_work() {
+        assert_eq!(
+            findings("parseFilter(\"site and geoCity == \\\"x\\\"\")"),
+            vec!["1:1 call parseFilter", "1:13 tag site", "1:13 tag geoCity"]
+        );
+    }
+
+    #[test]
+    fn dict_tags_work() {
+        assert_eq!(
+            findings(
+                "x: {dis: \"A\"}\nx->dis + x[\"navName\"] + x.get(\"area\")"
+            ),
+            vec![
+                "2:4 tag dis",
+                "2:12 tag navName",
+                "2:25 call get",
+                "2:31 tag area",
+            ]
+        );
+        // Only valid tag names are included:
+        assert_eq!(findings("x[\"Not A Tag\"]"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn refs_work() {
+        assert_eq!(
+            findings(
+                "[@p:demo:r:1, parseRef(\"p:demo:r:2\"), parseRef(\"bad id\")]"
+            ),
+            vec![
+                "1:2 ref @p:demo:r:1",
+                "1:15 call parseRef",
+                "1:24 ref @p:demo:r:2",
+                "1:39 call parseRef",
+            ]
+        );
+        assert_eq!(
+            findings("@abc->siteRef"),
+            vec!["1:1 ref @abc", "1:7 tag siteRef"]
+        );
+    }
+}
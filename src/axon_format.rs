@@ -0,0 +1,1378 @@
+//! A canonical formatter for Axon source code.
+use crate::axon::{ExprKind, ATOM, CONTROL, POSTFIX, STATEMENT, UNARY};
+use crate::axon_parser::{lex, Tokens};
+use crate::tokenizer::Token;
+use crate::{
+    AxonSource, BinaryOp, Comment, Expr, ParseAxonError, Position, SourceRange,
+    TagName, UnaryOp, Value,
+};
+use std::collections::{HashMap, VecDeque};
+
+/// The maximum width of a line, which is exceeded only when a line
+/// cannot be broken.
+const MAX_WIDTH: usize = 80;
+/// The number of spaces in each level of indentation.
+const INDENT: usize = 2;
+
+/// Format Axon source code in a canonical style, with consistent
+/// indentation, spacing and line breaks. Comments are preserved, and
+/// literals are written with the `to_axon_code` functions of the crate's
+/// types, except for date times, which are kept as date time literals.
+/// Formatting the result again returns the same code.
+///
+/// Comments inside an expression are kept next to the code they follow.
+/// A `//` comment ends its line, so the enclosing list, call, operator or
+/// `if` is split over several lines to make room for it.
+///
+/// Expressions are written on a single line when they fit within 80
+/// characters. Otherwise, lists, dicts and function arguments are split over
+/// several lines, and `do`/`end` blocks are always split over several
+/// lines, indented by two spaces.
+///
+/// Returns the first syntax error if the source code could not be parsed.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::format_axon;
+/// let source = "(x)=>do // Double it\ny:x*2;y.toStr() end";
+/// let formatted = format_axon(source).unwrap();
+/// assert_eq!(
+///     formatted,
+///     "(x) => do\n  // Double it\n  y: x * 2\n  y.toStr()\nend"
+/// );
+/// assert_eq!(format_axon(&formatted).unwrap(), formatted);
+/// ```
+pub fn format_axon(source: &str) -> Result<String, ParseAxonError> {
+    let (tokens, _, _) = lex(source);
+    let source = AxonSource::parse(source);
+    if let Some(err) = source.errors().first() {
+        return Err(err.clone());
+    }
+    let mut formatter =
+        AxonFormatter::new(source.exprs(), source.comments(), &tokens);
+    let items: Vec<Item> = source
+        .exprs()
+        .iter()
+        .map(|expr| Item::Expr(expr, STATEMENT))
+        .collect();
+    let end = Position {
+        line: usize::MAX,
+        column: usize::MAX,
+    };
+    let mut code = String::new();
+    formatter.write_lines(&mut code, None, &items, 0, "", end);
+    // Every line starts with a newline:
+    Ok(code.trim_start_matches('\n').to_owned())
+}
+
+/// Something which is written on its own line when its container is split
+/// over several lines.
+enum Item<'a> {
+    /// An expression, with the precedence it requires.
+    Expr(&'a Expr, u8),
+    /// A dict entry, written like `name: value`, or using the shorthand for
+    /// markers and removed markers.
+    Entry(&'a TagName, &'a Expr),
+    /// A defcomp cell, written like `name: meta`.
+    Cell(&'a TagName, &'a Expr),
+}
+
+impl Item<'_> {
+    fn range(&self) -> SourceRange {
+        match self {
+            Self::Expr(expr, _)
+            | Self::Entry(_, expr)
+            | Self::Cell(_, expr) => range(expr),
+        }
+    }
+}
+
+/// The key for the comments belonging to a container, which is `None` for
+/// the top level of the source code.
+type ContainerKey = Option<SourceRange>;
+
+/// Where a comment inside an expression is written, next to one of the
+/// expression's operands.
+enum Slot {
+    Before(SourceRange),
+    After(SourceRange),
+}
+
+struct AxonFormatter {
+    /// The comments belonging to each container expression, in source
+    /// order. Comments are removed once they have been written.
+    comments: HashMap<ContainerKey, VecDeque<Comment>>,
+    /// The comments written just before an expression, keyed by its range.
+    leading: HashMap<SourceRange, Vec<Comment>>,
+    /// The comments written just after an expression, keyed by its range.
+    trailing: HashMap<SourceRange, Vec<Comment>>,
+}
+
+impl AxonFormatter {
+    fn new(exprs: &[Expr], comments: &[Comment], tokens: &Tokens) -> Self {
+        let mut formatter = Self {
+            comments: HashMap::new(),
+            leading: HashMap::new(),
+            trailing: HashMap::new(),
+        };
+        for comment in comments {
+            let start = comment.range().start();
+            let slot = exprs
+                .iter()
+                .find_map(|expr| innermost_expr(expr, start))
+                .filter(|expr| !is_container(expr))
+                .and_then(|expr| comment_slot(expr, comment, tokens));
+            let comments = match slot {
+                Some(Slot::Before(range)) => {
+                    formatter.leading.entry(range).or_default()
+                }
+                Some(Slot::After(range)) => {
+                    formatter.trailing.entry(range).or_default()
+                }
+                None => {
+                    // Written after the item containing the comment:
+                    let container = exprs
+                        .iter()
+                        .find_map(|expr| innermost_container(expr, start));
+                    formatter
+                        .comments
+                        .entry(container)
+                        .or_default()
+                        .push_back(comment.clone());
+                    continue;
+                }
+            };
+            comments.push(comment.clone());
+        }
+        formatter
+    }
+
+    /// Return the `//` comments written just before the expression.
+    fn leading_lines(&self, expr: &Expr) -> Vec<Comment> {
+        line_comments(self.leading.get(&range(expr)))
+    }
+
+    /// Return the `//` comments written just after the expression.
+    fn trailing_lines(&self, expr: &Expr) -> Vec<Comment> {
+        line_comments(self.trailing.get(&range(expr)))
+    }
+
+    /// Return true if `//` comments are written next to any operand of
+    /// the expression, which means it must be split over several lines.
+    fn has_line_comments(&self, expr: &Expr) -> bool {
+        expr.children().into_iter().any(|child| {
+            !self.leading_lines(child).is_empty()
+                || !self.trailing_lines(child).is_empty()
+        })
+    }
+
+    /// Return the code for an expression with its `/* */` comments, which
+    /// are written on the same line.
+    fn with_block_comments(&self, expr: &Expr, code: String) -> String {
+        let mut result = String::new();
+        for comment in block_comments(self.leading.get(&range(expr))) {
+            result.push_str(comment.text());
+            result.push(' ');
+        }
+        result.push_str(&code);
+        for comment in block_comments(self.trailing.get(&range(expr))) {
+            result.push(' ');
+            result.push_str(comment.text());
+        }
+        result
+    }
+
+    /// Return the width of the `/* */` comments written before
+    /// the expression.
+    fn leading_width(&self, expr: &Expr) -> usize {
+        block_comments(self.leading.get(&range(expr)))
+            .iter()
+            .map(|comment| width(comment.text()) + 1)
+            .sum()
+    }
+
+    /// Return true if any comments belong to the container expression,
+    /// which means it must be split over several lines.
+    fn has_comments(&self, container: &Expr) -> bool {
+        self.comments
+            .get(&container.range())
+            .is_some_and(|comments| !comments.is_empty())
+    }
+
+    /// Remove and return the next comment of the container, if it starts
+    /// before the given position.
+    fn next_comment(
+        &mut self,
+        container: ContainerKey,
+        before: Position,
+    ) -> Option<Comment> {
+        let comments = self.comments.get_mut(&container)?;
+        if comments.front()?.range().start() < before {
+            comments.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Remove and return the next comment of the container, if it is on the
+    /// same line as the end of an item, and before the next item.
+    fn trailing_comment(
+        &mut self,
+        container: ContainerKey,
+        item_end: Position,
+        next_item_start: Option<Position>,
+    ) -> Option<Comment> {
+        let comments = self.comments.get_mut(&container)?;
+        let start = comments.front()?.range().start();
+        let is_trailing = start.line == item_end.line
+            && next_item_start.is_none_or(|next| start < next);
+        if is_trailing {
+            comments.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Write each item on its own line, preceded by the container's comments
+    /// which come before it. Comments inside an item which could not be
+    /// written next to an operand are written after the item, the first one
+    /// on the same line. The remaining comments before the end position are
+    /// written after the items. Each line starts with a newline, and single
+    /// blank lines between items are preserved.
+    fn write_lines(
+        &mut self,
+        code: &mut String,
+        container: ContainerKey,
+        items: &[Item],
+        indent: usize,
+        separator: &str,
+        end: Position,
+    ) {
+        let mut prev_line = None;
+        for (index, item) in items.iter().enumerate() {
+            let item_range = item.range();
+            while let Some(comment) =
+                self.next_comment(container, item_range.start())
+            {
+                write_comment_line(code, &comment, indent, &mut prev_line);
+            }
+            start_line(code, indent, prev_line, item_range.start().line);
+            let item_code = self.item(item, indent);
+            code.push_str(&item_code);
+            if index + 1 < items.len() {
+                code.push_str(separator);
+            }
+            let end_line = item_range.end().line;
+            if let Some(comment) =
+                self.next_comment(container, item_range.end())
+            {
+                code.push(' ');
+                code.push_str(comment.text());
+                prev_line = Some(end_line);
+                while let Some(comment) =
+                    self.next_comment(container, item_range.end())
+                {
+                    write_comment_line(code, &comment, indent, &mut prev_line);
+                    prev_line = Some(end_line);
+                }
+                continue;
+            }
+            prev_line = Some(end_line);
+
+            let next_start = items.get(index + 1).map(|item| {
+                let range = item.range();
+                range.start()
+            });
+            let item_end = item_range.end();
+            if let Some(comment) =
+                self.trailing_comment(container, item_end, next_start)
+            {
+                code.push(' ');
+                code.push_str(comment.text());
+                prev_line = Some(comment.range().end().line);
+            }
+        }
+        while let Some(comment) = self.next_comment(container, end) {
+            write_comment_line(code, &comment, indent, &mut prev_line);
+        }
+    }
+
+    fn item(&mut self, item: &Item, indent: usize) -> String {
+        match item {
+            Item::Expr(expr, precedence) => {
+                self.operand(expr, *precedence, indent, indent)
+            }
+            Item::Entry(name, value) => {
+                if let Some(code) = shorthand_entry(name, value) {
+                    return code;
+                }
+                let column = indent + name_width(name) + 2;
+                let value = self.operand(value, CONTROL, indent, column);
+                format!("{}: {}", name, value)
+            }
+            Item::Cell(name, meta) => {
+                let column = indent + name_width(name) + 2;
+                let meta = self.operand(meta, CONTROL, indent, column);
+                format!("{}: {}", name, meta)
+            }
+        }
+    }
+
+    /// Return the code for an expression starting at the column, with any
+    /// continuation lines indented by the given number of spaces. The
+    /// expression is wrapped in parentheses if its precedence is lower than
+    /// the given precedence.
+    fn operand(
+        &mut self,
+        expr: &Expr,
+        precedence: u8,
+        indent: usize,
+        column: usize,
+    ) -> String {
+        let column = column + self.leading_width(expr);
+        let code = if expr.precedence() < precedence {
+            format!("({})", self.expr(expr, indent, column + 1))
+        } else {
+            self.expr(expr, indent, column)
+        };
+        self.with_block_comments(expr, code)
+    }
+
+    fn flat_operand(&self, expr: &Expr, precedence: u8) -> Option<String> {
+        let code = self.flat(expr)?;
+        let code = if expr.precedence() < precedence {
+            format!("({})", code)
+        } else {
+            code
+        };
+        Some(self.with_block_comments(expr, code))
+    }
+
+    fn expr(&mut self, expr: &Expr, indent: usize, column: usize) -> String {
+        match self.flat(expr) {
+            Some(code) if column + width(&code) <= MAX_WIDTH => code,
+            _ => self.split(expr, indent, column),
+        }
+    }
+
+    /// Return the code for an expression on a single line, or `None` if
+    /// the expression contains a block or `//` comments.
+    fn flat(&self, expr: &Expr) -> Option<String> {
+        if self.has_line_comments(expr) {
+            return None;
+        }
+        let code = match expr.kind() {
+            ExprKind::Literal(value) => literal(value),
+            ExprKind::Var(name) => name.to_string(),
+            ExprKind::List(items) => {
+                format!("[{}]", self.flat_list(expr, items)?)
+            }
+            ExprKind::Dict(entries) => {
+                let items: Vec<Item> = entries
+                    .iter()
+                    .map(|(name, value)| Item::Entry(name, value))
+                    .collect();
+                format!("{{{}}}", self.flat_items(expr, &items)?)
+            }
+            ExprKind::Call { func, args } => {
+                format!("{}({})", func, self.flat_list(expr, args)?)
+            }
+            ExprKind::DotCall { target, func, args } => format!(
+                "{}.{}({})",
+                self.flat_operand(target, POSTFIX)?,
+                func,
+                self.flat_list(expr, args)?
+            ),
+            ExprKind::Get { target, name } => {
+                format!("{}->{}", self.flat_operand(target, POSTFIX)?, name)
+            }
+            ExprKind::Index { target, index } => format!(
+                "{}[{}]",
+                self.flat_operand(target, POSTFIX)?,
+                self.flat_operand(index, CONTROL)?
+            ),
+            ExprKind::Unary { op, operand } => {
+                let operand = self.flat_operand(operand, UNARY)?;
+                unary(*op, operand)
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let lhs = self.flat_operand(lhs, op.lhs_precedence())?;
+                let rhs = self.flat_operand(rhs, op.precedence() + 1)?;
+                match op {
+                    BinaryOp::Range => format!("{}{}{}", lhs, op, rhs),
+                    _ => format!("{} {} {}", lhs, op, rhs),
+                }
+            }
+            ExprKind::Lambda { body, .. } => {
+                let params = self.flat_lambda_params(expr)?;
+                format!("{} => {}", params, self.flat_operand(body, STATEMENT)?)
+            }
+            ExprKind::Block(_) | ExprKind::Defcomp { .. } => return None,
+            ExprKind::Define { name, value } => {
+                format!("{}: {}", name, self.flat_operand(value, CONTROL)?)
+            }
+            ExprKind::Assign { name, value } => {
+                format!("{} = {}", name, self.flat_operand(value, CONTROL)?)
+            }
+            ExprKind::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let cond = self.flat_operand(cond, CONTROL)?;
+                let then_precedence = then_precedence(then, otherwise);
+                let then = self.flat_operand(then, then_precedence)?;
+                match otherwise {
+                    Some(otherwise) => format!(
+                        "if ({}) {} else {}",
+                        cond,
+                        then,
+                        self.flat_operand(otherwise, CONTROL)?
+                    ),
+                    None => format!("if ({}) {}", cond, then),
+                }
+            }
+            ExprKind::Try { body, var, handler } => format!(
+                "try {} catch {}{}",
+                self.flat_operand(body, CONTROL)?,
+                catch_var(var),
+                self.flat_operand(handler, CONTROL)?
+            ),
+            ExprKind::Return(value) => {
+                format!("return {}", self.flat_operand(value, CONTROL)?)
+            }
+            ExprKind::Throw(value) => {
+                format!("throw {}", self.flat_operand(value, CONTROL)?)
+            }
+        };
+        Some(code)
+    }
+
+    fn flat_list(&self, container: &Expr, exprs: &[Expr]) -> Option<String> {
+        let items: Vec<Item> =
+            exprs.iter().map(|expr| Item::Expr(expr, CONTROL)).collect();
+        self.flat_items(container, &items)
+    }
+
+    /// Return the code for an expression which does not fit on a single
+    /// line.
+    fn split(&mut self, expr: &Expr, indent: usize, column: usize) -> String {
+        match expr.kind() {
+            ExprKind::Literal(value) => literal(value),
+            ExprKind::Var(name) => name.to_string(),
+            ExprKind::List(items) => {
+                let items: Vec<Item> = items
+                    .iter()
+                    .map(|item| Item::Expr(item, CONTROL))
+                    .collect();
+                self.bracketed(expr, "[", &items, "]", indent, column)
+            }
+            ExprKind::Dict(entries) => {
+                let items: Vec<Item> = entries
+                    .iter()
+                    .map(|(name, value)| Item::Entry(name, value))
+                    .collect();
+                self.bracketed(expr, "{", &items, "}", indent, column)
+            }
+            ExprKind::Call { func, args } => {
+                let args: Vec<Item> =
+                    args.iter().map(|arg| Item::Expr(arg, CONTROL)).collect();
+                let open = format!("{}(", func);
+                self.bracketed(expr, &open, &args, ")", indent, column)
+            }
+            ExprKind::DotCall { target, func, args } => {
+                let target = self.operand(target, POSTFIX, indent, column);
+                let column = end_column(&target, column);
+                let args: Vec<Item> =
+                    args.iter().map(|arg| Item::Expr(arg, CONTROL)).collect();
+                let open = format!(".{}(", func);
+                let call =
+                    self.bracketed(expr, &open, &args, ")", indent, column);
+                target + &call
+            }
+            ExprKind::Get { target, name } => {
+                let target = self.operand(target, POSTFIX, indent, column);
+                format!("{}->{}", target, name)
+            }
+            ExprKind::Index { target, index } => {
+                let target = self.operand(target, POSTFIX, indent, column);
+                let column = end_column(&target, column) + 1;
+                let index = self.operand(index, CONTROL, indent, column);
+                format!("{}[{}]", target, index)
+            }
+            ExprKind::Unary { op, operand } => {
+                let column = column + op.symbol().len();
+                let operand = self.operand(operand, UNARY, indent, column);
+                unary(*op, operand)
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let mut comments = self.trailing_lines(lhs);
+                comments.extend(self.leading_lines(rhs));
+                let lhs =
+                    self.operand(lhs, op.lhs_precedence(), indent, column);
+                let column = end_column(&lhs, column);
+                if !comments.is_empty() {
+                    // The comments end the line after the operator:
+                    let space = if *op == BinaryOp::Range { "" } else { " " };
+                    let mut code = format!("{}{}{}", lhs, space, op);
+                    let indent = indent + INDENT;
+                    write_line_comments(&mut code, &comments, indent);
+                    let rhs =
+                        self.operand(rhs, op.precedence() + 1, indent, indent);
+                    code.push('\n');
+                    code.push_str(&spaces(indent));
+                    code.push_str(&rhs);
+                    return code;
+                }
+                if *op == BinaryOp::Range {
+                    let rhs_column = column + op.symbol().len();
+                    let rhs = self.operand(
+                        rhs,
+                        op.precedence() + 1,
+                        indent,
+                        rhs_column,
+                    );
+                    return format!("{}{}{}", lhs, op, rhs);
+                }
+                let rhs_column = column + op.symbol().len() + 2;
+                let flat_rhs = self.flat_operand(rhs, op.precedence() + 1);
+                match flat_rhs {
+                    Some(rhs) if rhs_column + width(&rhs) <= MAX_WIDTH => {
+                        format!("{} {} {}", lhs, op, rhs)
+                    }
+                    _ => {
+                        // Continue on the next line after the operator:
+                        let indent = indent + INDENT;
+                        let rhs = self.operand(
+                            rhs,
+                            op.precedence() + 1,
+                            indent,
+                            indent,
+                        );
+                        format!("{} {}\n{}{}", lhs, op, spaces(indent), rhs)
+                    }
+                }
+            }
+            ExprKind::Lambda { params, body } => {
+                let mut code = "(".to_owned();
+                for (index, param) in params.iter().enumerate() {
+                    if index > 0 {
+                        code.push_str(", ");
+                    }
+                    code.push_str(param.name().as_ref());
+                    if let Some(default) = param.default() {
+                        code.push_str(": ");
+                        let column = end_column(&code, column);
+                        code.push_str(
+                            &self.operand(default, CONTROL, indent, column),
+                        );
+                    }
+                }
+                code.push_str(") =>");
+                let column = end_column(&code, column) + 1;
+                let comments = self.leading_lines(body);
+                let is_flat = comments.is_empty()
+                    && self
+                        .flat_operand(body, STATEMENT)
+                        .is_some_and(|body| column + width(&body) <= MAX_WIDTH);
+                let is_multiline_body = comments.is_empty()
+                    && matches!(
+                        body.kind(),
+                        ExprKind::Block(_)
+                            | ExprKind::Defcomp { .. }
+                            | ExprKind::If { .. }
+                            | ExprKind::Try { .. }
+                    );
+                if is_flat || is_multiline_body {
+                    code.push(' ');
+                    code.push_str(
+                        &self.operand(body, STATEMENT, indent, column),
+                    );
+                } else {
+                    let indent = indent + INDENT;
+                    write_line_comments(&mut code, &comments, indent);
+                    code.push('\n');
+                    code.push_str(&spaces(indent));
+                    code.push_str(
+                        &self.operand(body, STATEMENT, indent, indent),
+                    );
+                }
+                code
+            }
+            ExprKind::Block(exprs) => {
+                let items: Vec<Item> = exprs
+                    .iter()
+                    .map(|expr| Item::Expr(expr, STATEMENT))
+                    .collect();
+                let mut code = "do".to_owned();
+                let end = range(expr).end();
+                let container = expr.range();
+                let inner = indent + INDENT;
+                self.write_lines(&mut code, container, &items, inner, "", end);
+                code.push('\n');
+                code.push_str(&spaces(indent));
+                code.push_str("end");
+                code
+            }
+            ExprKind::Define { name, value } => {
+                let column = column + name_width(name) + 2;
+                let value = self.operand(value, CONTROL, indent, column);
+                format!("{}: {}", name, value)
+            }
+            ExprKind::Assign { name, value } => {
+                let column = column + name_width(name) + 3;
+                let value = self.operand(value, CONTROL, indent, column);
+                format!("{} = {}", name, value)
+            }
+            ExprKind::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let cond = self.operand(cond, CONTROL, indent, column + 4);
+                let mut code = format!("if ({})", cond);
+                let precedence = then_precedence(then, otherwise);
+                let is_block = self
+                    .write_branch(&mut code, then, precedence, indent, column);
+                if let Some(otherwise) = otherwise {
+                    let is_block = self
+                        .write_after_branch(&mut code, then, is_block, indent);
+                    write_keyword(&mut code, "else", is_block, indent);
+                    let is_else_if =
+                        matches!(otherwise.kind(), ExprKind::If { .. })
+                            && self.leading_lines(otherwise).is_empty();
+                    if is_else_if {
+                        let column = end_column(&code, column) + 1;
+                        code.push(' ');
+                        code.push_str(
+                            &self.operand(otherwise, CONTROL, indent, column),
+                        );
+                    } else {
+                        self.write_branch(
+                            &mut code, otherwise, CONTROL, indent, column,
+                        );
+                    }
+                }
+                code
+            }
+            ExprKind::Try { body, var, handler } => {
+                let mut code = "try".to_owned();
+                let is_block =
+                    self.write_branch(&mut code, body, CONTROL, indent, column);
+                let is_block =
+                    self.write_after_branch(&mut code, body, is_block, indent);
+                write_keyword(&mut code, "catch", is_block, indent);
+                if let Some(var) = var {
+                    code.push_str(&format!(" ({})", var));
+                }
+                self.write_branch(&mut code, handler, CONTROL, indent, column);
+                code
+            }
+            ExprKind::Return(value) => {
+                let value = self.operand(value, CONTROL, indent, column + 7);
+                format!("return {}", value)
+            }
+            ExprKind::Throw(value) => {
+                let value = self.operand(value, CONTROL, indent, column + 6);
+                format!("throw {}", value)
+            }
+            ExprKind::Defcomp { cells, body } => {
+                let cells: Vec<Item> = cells
+                    .iter()
+                    .map(|(name, meta)| Item::Cell(name, meta))
+                    .collect();
+                let body: Vec<Item> = body
+                    .iter()
+                    .map(|expr| Item::Expr(expr, STATEMENT))
+                    .collect();
+                let container = expr.range();
+                let end = range(expr).end();
+                // Comments after the last cell are written in the body:
+                let cells_end =
+                    cells.last().map_or(range(expr).start(), |item| {
+                        let range = item.range();
+                        range.end()
+                    });
+                let inner = indent + INDENT;
+                let mut code = "defcomp".to_owned();
+                self.write_lines(
+                    &mut code, container, &cells, inner, "", cells_end,
+                );
+                code.push('\n');
+                code.push_str(&spaces(inner));
+                code.push_str("do");
+                self.write_lines(
+                    &mut code,
+                    container,
+                    &body,
+                    inner + INDENT,
+                    "",
+                    end,
+                );
+                code.push('\n');
+                code.push_str(&spaces(inner));
+                code.push_str("end\n");
+                code.push_str(&spaces(indent));
+                code.push_str("end");
+                code
+            }
+        }
+    }
+
+    /// Write the items of a list, dict or function call. The items are
+    /// written on separate lines, unless the last item is a function with
+    /// a `do`/`end` block body and the other items fit on the first line.
+    fn bracketed(
+        &mut self,
+        container: &Expr,
+        open: &str,
+        items: &[Item],
+        close: &str,
+        indent: usize,
+        column: usize,
+    ) -> String {
+        if let Some(items) = self.flat_items(container, items) {
+            let code = format!("{}{}{}", open, items, close);
+            if column + width(&code) <= MAX_WIDTH {
+                return code;
+            }
+        }
+        if let Some(code) =
+            self.hug_last_item(container, open, items, close, indent, column)
+        {
+            return code;
+        }
+        let mut code = open.to_owned();
+        let end = range(container).end();
+        let inner = indent + INDENT;
+        self.write_lines(&mut code, container.range(), items, inner, ",", end);
+        if code.len() == open.len() {
+            // No items or comments:
+            code.push_str(close);
+        } else {
+            code.push('\n');
+            code.push_str(&spaces(indent));
+            code.push_str(close);
+        }
+        code
+    }
+
+    /// Return the items of a list, dict or function call on a single line,
+    /// if possible.
+    fn flat_items(&self, container: &Expr, items: &[Item]) -> Option<String> {
+        if self.has_comments(container) {
+            return None;
+        }
+        let items = items
+            .iter()
+            .map(|item| match item {
+                Item::Expr(expr, precedence) => {
+                    self.flat_operand(expr, *precedence)
+                }
+                Item::Entry(name, value) => {
+                    match shorthand_entry(name, value) {
+                        Some(code) => Some(code),
+                        None => Some(format!(
+                            "{}: {}",
+                            name,
+                            self.flat_operand(value, CONTROL)?
+                        )),
+                    }
+                }
+                Item::Cell(..) => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(items.join(", "))
+    }
+
+    fn hug_last_item(
+        &mut self,
+        container: &Expr,
+        open: &str,
+        items: &[Item],
+        close: &str,
+        indent: usize,
+        column: usize,
+    ) -> Option<String> {
+        let (last, others) = items.split_last()?;
+        let last = match last {
+            Item::Expr(expr, _) => expr,
+            _ => return None,
+        };
+        let is_block_lambda = matches!(
+            last.kind(),
+            ExprKind::Lambda { body, .. }
+                if matches!(body.kind(), ExprKind::Block(_))
+        );
+        if !is_block_lambda || self.has_comments(container) {
+            return None;
+        }
+        let mut code = open.to_owned();
+        for item in others {
+            match item {
+                Item::Expr(expr, precedence) => {
+                    code.push_str(&self.flat_operand(expr, *precedence)?);
+                    code.push_str(", ");
+                }
+                _ => return None,
+            }
+        }
+        let column = column + width(&code);
+        let first_line = self.flat_lambda_params(last)?;
+        if column + width(&first_line) + " => do".len() > MAX_WIDTH {
+            return None;
+        }
+        code.push_str(&self.operand(last, CONTROL, indent, column));
+        code.push_str(close);
+        Some(code)
+    }
+
+    /// Return the parameters of a function on a single line, if possible.
+    fn flat_lambda_params(&self, lambda: &Expr) -> Option<String> {
+        match lambda.kind() {
+            ExprKind::Lambda { params, .. } => {
+                let params = params
+                    .iter()
+                    .map(|param| match param.default() {
+                        Some(default) => Some(format!(
+                            "{}: {}",
+                            param.name(),
+                            self.flat_operand(default, CONTROL)?
+                        )),
+                        None => Some(param.name().to_string()),
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("({})", params.join(", ")))
+            }
+            _ => None,
+        }
+    }
+
+    /// Write a branch of an `if` or `try` expression. Blocks start on the
+    /// same line, and other expressions on the next line, after any `//`
+    /// comments before the branch. Returns true if the branch was a block.
+    fn write_branch(
+        &mut self,
+        code: &mut String,
+        branch: &Expr,
+        precedence: u8,
+        indent: usize,
+        column: usize,
+    ) -> bool {
+        let comments = self.leading_lines(branch);
+        let is_block = matches!(branch.kind(), ExprKind::Block(_))
+            && branch.precedence() >= precedence
+            && comments.is_empty();
+        if is_block {
+            let column = end_column(code, column) + 1;
+            code.push(' ');
+            code.push_str(&self.operand(branch, precedence, indent, column));
+        } else {
+            let indent = indent + INDENT;
+            write_line_comments(code, &comments, indent);
+            code.push('\n');
+            code.push_str(&spaces(indent));
+            code.push_str(&self.operand(branch, precedence, indent, indent));
+        }
+        is_block
+    }
+
+    /// Write the `//` comments after the first branch of an `if` or `try`
+    /// expression. Returns true if the branch was a block and the keyword
+    /// after it can still be written on the same line.
+    fn write_after_branch(
+        &self,
+        code: &mut String,
+        branch: &Expr,
+        is_block: bool,
+        indent: usize,
+    ) -> bool {
+        let comments = self.trailing_lines(branch);
+        let indent = if is_block { indent } else { indent + INDENT };
+        write_line_comments(code, &comments, indent);
+        is_block && comments.is_empty()
+    }
+}
+
+/// Write the keyword after a branch, on the same line as the end of
+/// a block or otherwise on the next line.
+fn write_keyword(
+    code: &mut String,
+    keyword: &str,
+    after_block: bool,
+    indent: usize,
+) {
+    if after_block {
+        code.push(' ');
+    } else {
+        code.push('\n');
+        code.push_str(&spaces(indent));
+    }
+    code.push_str(keyword);
+}
+
+/// Return the range of a parsed expression.
+fn range(expr: &Expr) -> SourceRange {
+    expr.range().expect("parsed exprs have a range")
+}
+
+/// Return the innermost expression containing the position.
+fn innermost_expr(expr: &Expr, position: Position) -> Option<&Expr> {
+    let range = range(expr);
+    if position < range.start() || position >= range.end() {
+        return None;
+    }
+    let inner = expr
+        .children()
+        .into_iter()
+        .find_map(|child| innermost_expr(child, position));
+    inner.or(Some(expr))
+}
+
+/// Return the innermost expression containing the position which can have
+/// comments written between its items.
+fn innermost_container(expr: &Expr, position: Position) -> ContainerKey {
+    let range = range(expr);
+    if position < range.start() || position >= range.end() {
+        return None;
+    }
+    let inner = expr
+        .children()
+        .into_iter()
+        .find_map(|child| innermost_container(child, position));
+    if inner.is_some() {
+        return inner;
+    }
+    if is_container(expr) {
+        Some(range)
+    } else {
+        None
+    }
+}
+
+/// Return true if the expression has items which can be written on
+/// separate lines, with comments between them.
+fn is_container(expr: &Expr) -> bool {
+    matches!(
+        expr.kind(),
+        ExprKind::List(_)
+            | ExprKind::Dict(_)
+            | ExprKind::Call { .. }
+            | ExprKind::DotCall { .. }
+            | ExprKind::Block(_)
+            | ExprKind::Defcomp { .. }
+    )
+}
+
+/// Return where to write a comment which is inside the expression, but not
+/// inside any of its operands. A comment follows the operand before it,
+/// unless there are other tokens between them, in which case it comes
+/// before the next operand. `//` comments can only be written where the
+/// expression can be split over several lines, so `None` is returned for
+/// other expressions.
+fn comment_slot(
+    expr: &Expr,
+    comment: &Comment,
+    tokens: &Tokens,
+) -> Option<Slot> {
+    let start = comment.range().start();
+    let children = expr.children();
+    let prev = children
+        .iter()
+        .filter(|child| range(child).end() <= start)
+        .max_by_key(|child| range(child).end())
+        .map(|child| range(child));
+    let next = children
+        .iter()
+        .find(|child| range(child).start() >= start)
+        .map(|child| range(child));
+    let between: Vec<&Token> = prev.map_or_else(Vec::new, |prev| {
+        tokens_between(tokens, prev.end(), start)
+    });
+    if comment.text().starts_with("//") {
+        match expr.kind() {
+            ExprKind::Binary { rhs, .. } => Some(Slot::Before(range(rhs))),
+            ExprKind::If { cond, .. } if prev == Some(range(cond)) => {
+                next.map(Slot::Before)
+            }
+            ExprKind::If { .. } if prev.is_some() => {
+                branch_slot(prev, next, &between)
+            }
+            ExprKind::Try { .. } => branch_slot(prev, next, &between),
+            ExprKind::Lambda { body, .. } if next == Some(range(body)) => {
+                next.map(Slot::Before)
+            }
+            _ => None,
+        }
+    } else {
+        // Parentheses around the previous operand are skipped, except for
+        // the ones which are part of the syntax of `if` and functions:
+        let is_paren = |token: &&Token| **token == Token::RParen;
+        let has_syntax_parens = matches!(
+            expr.kind(),
+            ExprKind::If { .. } | ExprKind::Lambda { .. }
+        );
+        let follows_prev = between.is_empty()
+            || (!has_syntax_parens && between.iter().all(is_paren));
+        match (prev, next) {
+            (Some(prev), _) if follows_prev => Some(Slot::After(prev)),
+            (_, Some(next)) => Some(Slot::Before(next)),
+            (prev, None) => prev.map(Slot::After),
+        }
+    }
+}
+
+/// Return where to write a `//` comment between the branches of an `if` or
+/// `try` expression: after the branch before it, unless there is a keyword
+/// between them.
+fn branch_slot(
+    prev: Option<SourceRange>,
+    next: Option<SourceRange>,
+    between: &[&Token],
+) -> Option<Slot> {
+    match prev {
+        Some(prev) if between.is_empty() => Some(Slot::After(prev)),
+        _ => next.map(Slot::Before),
+    }
+}
+
+/// Return the tokens between two positions, ignoring newlines.
+fn tokens_between(
+    tokens: &Tokens,
+    start: Position,
+    end: Position,
+) -> Vec<&Token> {
+    tokens
+        .iter()
+        .filter(|(token, range)| {
+            *token != Token::Nl && range.start() >= start && range.end() <= end
+        })
+        .map(|(token, _)| token)
+        .collect()
+}
+
+/// Return the `//` comments in a list of comments.
+fn line_comments(comments: Option<&Vec<Comment>>) -> Vec<Comment> {
+    comments
+        .into_iter()
+        .flatten()
+        .filter(|comment| comment.text().starts_with("//"))
+        .cloned()
+        .collect()
+}
+
+/// Return the `/* */` comments in a list of comments.
+fn block_comments(comments: Option<&Vec<Comment>>) -> Vec<&Comment> {
+    comments
+        .into_iter()
+        .flatten()
+        .filter(|comment| !comment.text().starts_with("//"))
+        .collect()
+}
+
+/// Write `//` comments at the end of the current line, and any further
+/// comments on their own lines.
+fn write_line_comments(code: &mut String, comments: &[Comment], indent: usize) {
+    for (index, comment) in comments.iter().enumerate() {
+        if index == 0 {
+            code.push(' ');
+        } else {
+            code.push('\n');
+            code.push_str(&spaces(indent));
+        }
+        code.push_str(comment.text());
+    }
+}
+
+/// Return the dict entry shorthand for markers and removed markers.
+fn shorthand_entry(name: &TagName, value: &Expr) -> Option<String> {
+    match value.kind() {
+        ExprKind::Literal(Value::Marker) => Some(name.to_string()),
+        ExprKind::Literal(Value::RemoveMarker) => Some(format!("-{}", name)),
+        _ => None,
+    }
+}
+
+/// Return the code for a literal. Date times are written as they appear in
+/// source code, rather than as a call to `parseDateTime`, so they are
+/// parsed as literals again.
+fn literal(value: &Value) -> String {
+    match value {
+        Value::DateTime(date_time) => date_time.to_string(),
+        value => value.to_axon_code(),
+    }
+}
+
+fn unary(op: UnaryOp, operand: String) -> String {
+    // Avoid writing two minus signs next to each other:
+    if op == UnaryOp::Neg && operand.starts_with('-') {
+        format!("{}({})", op.symbol(), operand)
+    } else {
+        format!("{}{}", op.symbol(), operand)
+    }
+}
+
+/// Return the precedence required for the first branch of an `if`. A
+/// nested `if` without an `else` would take the `else` of the outer `if`.
+fn then_precedence(then: &Expr, otherwise: &Option<Box<Expr>>) -> u8 {
+    if otherwise.is_some() && ends_with_if(then) {
+        ATOM
+    } else {
+        CONTROL
+    }
+}
+
+/// Return true if the expression ends with an `if` which has no `else`.
+fn ends_with_if(expr: &Expr) -> bool {
+    match expr.kind() {
+        ExprKind::If { otherwise, .. } => {
+            otherwise.as_deref().is_none_or(ends_with_if)
+        }
+        ExprKind::Lambda { body: expr, .. }
+        | ExprKind::Define { value: expr, .. }
+        | ExprKind::Assign { value: expr, .. }
+        | ExprKind::Try { handler: expr, .. }
+        | ExprKind::Return(expr)
+        | ExprKind::Throw(expr) => ends_with_if(expr),
+        ExprKind::Unary { operand, .. } => ends_with_if(operand),
+        ExprKind::Binary { rhs, .. } => ends_with_if(rhs),
+        _ => false,
+    }
+}
+
+fn catch_var(var: &Option<TagName>) -> String {
+    match var {
+        Some(var) => format!("({}) ", var),
+        None => String::new(),
+    }
+}
+
+/// Start a new line at the indentation, after a blank line if there was
+/// one in the source code.
+fn start_line(
+    code: &mut String,
+    indent: usize,
+    prev_line: Option<usize>,
+    line: usize,
+) {
+    if prev_line.is_some_and(|prev_line| line > prev_line + 1) {
+        code.push('\n');
+    }
+    code.push('\n');
+    code.push_str(&spaces(indent));
+}
+
+fn write_comment_line(
+    code: &mut String,
+    comment: &Comment,
+    indent: usize,
+    prev_line: &mut Option<usize>,
+) {
+    let range = comment.range();
+    start_line(code, indent, *prev_line, range.start().line);
+    code.push_str(comment.text());
+    *prev_line = Some(range.end().line);
+}
+
+fn name_width(name: &TagName) -> usize {
+    width(name.as_ref())
+}
+
+fn spaces(count: usize) -> String {
+    " ".repeat(count)
+}
+
+fn width(code: &str) -> usize {
+    code.chars().count()
+}
+
+/// Return the column after the code, which starts at the given column.
+fn end_column(code: &str, column: usize) -> usize {
+    match code.rfind('\n') {
+        Some(index) => width(&code[index + 1..]),
+        None => column + width(code),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::format_axon;
+    use crate::AxonSource;
+
+    fn format(s: &str) -> String {
+        format_axon(s).unwrap()
+    }
+
+    #[test]
+    fn spacing_works() {
+        assert_eq!(format("x:1+2*-y"), "x: 1 + 2 * -y");
+        assert_eq!(format("a  and(b or c)"), "a and (b or c)");
+        assert_eq!(format("x=>x->dis.toStr"), "(x) => x->dis.toStr()");
+        assert_eq!(
+            format("{site,dis:\"A\",-equip}"),
+            "{site, dis: \"A\", -equip}"
+        );
+        assert_eq!(format("f( a , b )\n\n\n\ng()"), "f(a, b)\n\ng()");
+        assert_eq!(format("`a b`.parseUri"), "`a b`.parseUri()");
+        assert_eq!(
+            format("2024-01-01 .. 2024-01-31"),
+            "2024-01-01..2024-01-31"
+        );
+        assert_eq!(
+            format("2024-01-01T00:00:00Z UTC .date"),
+            "2024-01-01T00:00:00Z UTC.date()"
+        );
+    }
+
+    #[test]
+    fn blocks_work() {
+        assert_eq!(
+            format("(a,b:2)=>do x:a+b;if(x>0)do x end else null end"),
+            "(a, b: 2) => do
+  x: a + b
+  if (x > 0) do
+    x
+  end else
+    null
+end"
+        );
+        assert_eq!(
+            format("try do a end catch (ex) do b end"),
+            "try do\n  a\nend catch (ex) do\n  b\nend"
+        );
+        assert_eq!(
+            format("defcomp a:{defVal:0}\ndo a=a+1 end end"),
+            "defcomp\n  a: {defVal: 0}\n  do\n    a = a + 1\n  end\nend"
+        );
+    }
+
+    #[test]
+    fn long_lines_are_split() {
+        assert_eq!(
+            format(
+                "readAll(site).map(s => do s->dis end).findAll(s => s.startsWith(\"Main\"))"
+            ),
+            "readAll(site).map((s) => do
+  s->dis
+end).findAll((s) => s.startsWith(\"Main\"))"
+        );
+        assert_eq!(
+            format(
+                "f(aaaaaaaaaaaaaaaaaaaaaaaaa, bbbbbbbbbbbbbbbbbbbbbbbbb, [ccccccccccccccccccccccccc, 1])"
+            ),
+            "f(
+  aaaaaaaaaaaaaaaaaaaaaaaaa,
+  bbbbbbbbbbbbbbbbbbbbbbbbb,
+  [ccccccccccccccccccccccccc, 1]
+)"
+        );
+        assert_eq!(
+            format(
+                "x: if (aaaaaaaaaaaaaaaaaaaaaaaaa and bbbbbbbbbbbbbbbbbbbbbbbbb) ccccccccccccccccc else d"
+            ),
+            "x: if (aaaaaaaaaaaaaaaaaaaaaaaaa and bbbbbbbbbbbbbbbbbbbbbbbbb)
+  ccccccccccccccccc
+else
+  d"
+        );
+        assert_eq!(
+            format(
+                "aaaaaaaaaaaaaaaaaaaaaaaaa + bbbbbbbbbbbbbbbbbbbbbbbbb + ccccccccccccccccccccccccc"
+            ),
+            "aaaaaaaaaaaaaaaaaaaaaaaaa + bbbbbbbbbbbbbbbbbbbbbbbbb +
+  ccccccccccccccccccccccccc"
+        );
+    }
+
+    #[test]
+    fn comments_are_preserved() {
+        let source = "// Sum the list
+(list) => do // start
+  total: 0
+  list.each(x => total = total + x) // add
+
+  /* return
+     the total */
+  total
+end";
+        assert_eq!(
+            format(source),
+            "// Sum the list
+(list) => do
+  // start
+  total: 0
+  list.each((x) => total = total + x) // add
+
+  /* return
+     the total */
+  total
+end"
+        );
+        assert_eq!(format("[1, // one\n2]"), "[\n  1, // one\n  2\n]");
+        assert_eq!(format("x: a + /* b */ b"), "x: a + /* b */ b");
+    }
+
+    #[test]
+    fn inline_comments_stay_in_place() {
+        assert_eq!(format("a /* inline */ + b"), "a /* inline */ + b");
+        assert_eq!(format("(a or b) /* c */ and d"), "(a or b) /* c */ and d");
+        assert_eq!(
+            format("if (a) /* c */ b else c"),
+            "if (a) /* c */ b else c"
+        );
+        assert_eq!(
+            format("if (a) // why\n  b\nelse c"),
+            "if (a) // why\n  b\nelse\n  c"
+        );
+        assert_eq!(
+            format("if (a) b // then\nelse c"),
+            "if (a)\n  b // then\nelse\n  c"
+        );
+        assert_eq!(
+            format("try a catch (e) // ignore\n null"),
+            "try\n  a\ncatch (e) // ignore\n  null"
+        );
+        assert_eq!(format("x: a + // add\n b"), "x: a + // add\n  b");
+        assert_eq!(
+            format("f(a + // add\n b, c)"),
+            "f(\n  a + // add\n    b,\n  c\n)"
+        );
+        assert_eq!(format("(x) => // body\n x + 1"), "(x) => // body\n  x + 1");
+        assert_eq!(format("x: // value\n 1\ny"), "x: 1 // value\ny");
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let sources = [
+            "(x)=>do // Double it\ny:x*2;y.toStr() end",
+            "if (a) if (b) c else d",
+            "if (a) (if (b) c) else d",
+            "-(-1) - -1",
+            "x.map(v => if (v) do a end else b).sort((a, b) => a <=> b)",
+            "defcomp\n  a: {defVal: 0} // a\n  do\n    // body\n    a = 1\n  end\nend",
+            "[[1111111111, 2222222222, 3333333333], [4444444444, 5555555555, 6666666666]]",
+            "do\n  a // after a\n  /* before b */ b\n  // last\nend",
+            "try\n  aaaaaaaaaaaaaaaaaaaaaaaaa(bbbbbbbbbbbbbbbbbbbbbbbbb, ccccccccccccccccccccccccc)\ncatch null",
+            "x: a /* a */ + // b\n  b",
+            "if (a) // why\n  b\nelse // other\n  if (c) d",
+            "[1, a + // a\n  b, 2]",
+            "2024-01-01T00:00:00Z UTC",
+            "2024-01-01T00:00:00-05:00 New_York->dis.date()",
+        ];
+        for source in sources.iter() {
+            let formatted = format(source);
+            assert_eq!(format(&formatted), formatted);
+            assert_eq!(
+                AxonSource::parse(&formatted).exprs(),
+                AxonSource::parse(source).exprs()
+            );
+        }
+    }
+
+    #[test]
+    fn syntax_errors_are_returned() {
+        let err = format_axon("x: (1 +").unwrap_err();
+        assert_eq!(err.line(), 1);
+        assert_eq!(err.column(), 8);
+    }
+}
@@ -0,0 +1,1144 @@
+use crate::axon::{
+    is_axon_keyword, BinaryOp, ExprKind, Param, SourceRange, UnaryOp,
+};
+use crate::tokenizer::{Token, TokenError, Tokenizer};
+use crate::{Expr, Number, Position, Qname, TagName, Value};
+use thiserror::Error;
+
+/// Parsed Axon source code.
+///
+/// Parsing continues after a syntax error, so all the errors in the source
+/// can be reported at once. Statements containing errors are left out of
+/// the parsed expressions.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::{AxonSource, ExprKind};
+/// let source = AxonSource::parse("(x) => do\n  // Double it\n  x * 2\nend");
+/// assert!(source.errors().is_empty());
+/// assert_eq!(source.exprs().len(), 1);
+/// assert!(matches!(source.exprs()[0].kind(), ExprKind::Lambda { .. }));
+/// assert_eq!(source.comments()[0].text(), "// Double it");
+///
+/// let source = AxonSource::parse("a: 1\nb: (2 +)\nc: 3");
+/// assert_eq!(source.errors().len(), 1);
+/// assert_eq!(source.errors()[0].line(), 2);
+/// assert_eq!(source.exprs().len(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxonSource {
+    exprs: Vec<Expr>,
+    comments: Vec<Comment>,
+    errors: Vec<ParseAxonError>,
+}
+
+impl AxonSource {
+    /// Parse Axon source code. The source may contain several expressions,
+    /// separated by newlines or semicolons.
+    pub fn parse(source: &str) -> Self {
+        let (tokens, comments, errors) = lex(source);
+        let mut parser = AxonParser {
+            tokens,
+            index: 0,
+            prev_end: Position { line: 1, column: 1 },
+            newlines: Vec::new(),
+            errors,
+        };
+        let exprs = parser.parse_statements(false);
+        let mut errors = parser.errors;
+        errors.sort_by_key(|err| (err.line, err.column));
+        Self {
+            exprs,
+            comments,
+            errors,
+        }
+    }
+
+    /// Return the top-level expressions in the source code.
+    pub fn exprs(&self) -> &[Expr] {
+        &self.exprs
+    }
+
+    /// Consume this source, returning its top-level expressions.
+    pub fn into_exprs(self) -> Vec<Expr> {
+        self.exprs
+    }
+
+    /// Return the comments in the source code, in the order they appear.
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
+    /// Return the syntax errors in the source code, in the order
+    /// they appear.
+    pub fn errors(&self) -> &[ParseAxonError] {
+        &self.errors
+    }
+}
+
+/// A comment in Axon source code.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Comment {
+    text: String,
+    range: SourceRange,
+}
+
+impl Comment {
+    /// Return the text of this comment, including the `//` or `/* */`
+    /// delimiters.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Return the range of source code containing this comment.
+    pub fn range(&self) -> SourceRange {
+        self.range
+    }
+}
+
+impl std::str::FromStr for Expr {
+    type Err = ParseAxonError;
+
+    /// Parse a single Axon expression. Returns the first syntax error if
+    /// the source contains any errors.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let source = AxonSource::parse(s);
+        if let Some(err) = source.errors.into_iter().next() {
+            return Err(err);
+        }
+        let mut exprs = source.exprs.into_iter();
+        match (exprs.next(), exprs.next()) {
+            (Some(expr), None) => Ok(expr),
+            (None, _) => Err(ParseAxonError::new(
+                Position { line: 1, column: 1 },
+                "Expected an expression".to_owned(),
+            )),
+            (Some(_), Some(extra)) => Err(ParseAxonError::new(
+                extra.range().expect("parsed exprs have a range").start(),
+                "Expected a single expression".to_owned(),
+            )),
+        }
+    }
+}
+
+/// An error indicating that Axon source code could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse Axon at line {line}, column {column}: {message}")]
+pub struct ParseAxonError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseAxonError {
+    fn new(position: Position, message: String) -> Self {
+        Self {
+            line: position.line,
+            column: position.column,
+            message,
+        }
+    }
+
+    /// Return the line at which the error occurred, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Return the column at which the error occurred, starting from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Return a description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::convert::From<TokenError> for ParseAxonError {
+    fn from(err: TokenError) -> Self {
+        Self::new(err.position, err.message)
+    }
+}
+
+pub(crate) type Tokens = Vec<(Token, SourceRange)>;
+
+/// Return 1 if the token opens a bracket or block, -1 if it closes one,
+/// or 0 otherwise.
+fn nesting(token: &Token) -> i32 {
+    match token {
+        Token::LParen | Token::LBracket | Token::LBrace => 1,
+        Token::RParen | Token::RBracket | Token::RBrace => -1,
+        Token::Id(id) if id == "do" || id == "defcomp" => 1,
+        Token::Id(id) if id == "end" => -1,
+        _ => 0,
+    }
+}
+
+/// Split the source into tokens and comments. The last token is always
+/// `Token::Eof`.
+pub(crate) fn lex(source: &str) -> (Tokens, Vec<Comment>, Vec<ParseAxonError>) {
+    let mut tokenizer = Tokenizer::new(source.as_bytes()).read_comments();
+    let mut tokens = Vec::new();
+    let mut comments = Vec::new();
+    let mut errors = Vec::new();
+    loop {
+        let before = tokenizer.position();
+        match tokenizer.next_token() {
+            Ok((Token::Comment(text), start)) => {
+                let range = SourceRange::new(start, tokenizer.position());
+                comments.push(Comment { text, range });
+            }
+            Ok((Token::Eof, start)) => {
+                tokens.push((Token::Eof, SourceRange::new(start, start)));
+                break;
+            }
+            Ok((token, start)) => {
+                let range = SourceRange::new(start, tokenizer.position());
+                tokens.push((token, range));
+            }
+            Err(err) => {
+                errors.push(err.into());
+                // Stop if the tokenizer cannot get past the error:
+                if tokenizer.position() == before {
+                    let end = tokenizer.position();
+                    tokens.push((Token::Eof, SourceRange::new(end, end)));
+                    break;
+                }
+            }
+        }
+    }
+    (tokens, comments, errors)
+}
+
+/// Indicates that a syntax error was recorded, and the parser should skip
+/// ahead to somewhere it can continue.
+struct Abort;
+
+type ParseResult<T> = Result<T, Abort>;
+
+struct AxonParser {
+    tokens: Tokens,
+    index: usize,
+    /// The end of the last token which was consumed.
+    prev_end: Position,
+    /// Whether newlines are ignored, for each enclosing bracket or block.
+    /// Newlines separate statements, except inside brackets.
+    newlines: Vec<bool>,
+    errors: Vec<ParseAxonError>,
+}
+
+impl AxonParser {
+    fn ignores_newlines(&self) -> bool {
+        self.newlines.last() == Some(&true)
+    }
+
+    /// Return the index of the current token, skipping any newlines
+    /// which are ignored.
+    fn cur_index(&self) -> usize {
+        let mut index = self.index;
+        if self.ignores_newlines() {
+            while self.tokens[index].0 == Token::Nl {
+                index += 1;
+            }
+        }
+        index
+    }
+
+    fn cur(&self) -> &Token {
+        &self.tokens[self.cur_index()].0
+    }
+
+    fn cur_range(&self) -> SourceRange {
+        self.tokens[self.cur_index()].1
+    }
+
+    /// Return the token directly after the current token, without skipping
+    /// any newlines.
+    fn next(&self) -> &Token {
+        let index = (self.cur_index() + 1).min(self.tokens.len() - 1);
+        &self.tokens[index].0
+    }
+
+    /// Return the first token from the current token which is not
+    /// a newline.
+    fn cur_past_newlines(&self) -> &Token {
+        self.tokens[self.index..]
+            .iter()
+            .map(|(token, _)| token)
+            .find(|token| **token != Token::Nl)
+            .unwrap_or(&Token::Eof)
+    }
+
+    fn advance(&mut self) {
+        let index = self.cur_index();
+        self.prev_end = self.tokens[index].1.end();
+        if self.tokens[index].0 != Token::Eof {
+            self.index = index + 1;
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while *self.cur() == Token::Nl {
+            self.advance();
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.cur(), Token::Id(id) if id == keyword)
+    }
+
+    fn error<T>(&mut self, message: String) -> ParseResult<T> {
+        let start = self.cur_range().start();
+        self.error_at(start, message)
+    }
+
+    fn error_at<T>(
+        &mut self,
+        start: Position,
+        message: String,
+    ) -> ParseResult<T> {
+        // Only report the first error at each position:
+        let is_reported = self.errors.last().is_some_and(|err| {
+            (err.line, err.column) == (start.line, start.column)
+        });
+        if !is_reported {
+            self.errors.push(ParseAxonError::new(start, message));
+        }
+        Err(Abort)
+    }
+
+    fn expect(&mut self, expected: Token) -> ParseResult<()> {
+        if *self.cur() == expected {
+            self.advance();
+            Ok(())
+        } else {
+            let message =
+                format!("Expected {} but found {}", expected, self.cur());
+            self.error(message)
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> ParseResult<()> {
+        if self.is_keyword(keyword) {
+            self.advance();
+            Ok(())
+        } else {
+            let message =
+                format!("Expected '{}' but found {}", keyword, self.cur());
+            self.error(message)
+        }
+    }
+
+    /// Return an expression covering the source from the start position to
+    /// the end of the last token which was consumed.
+    fn finish(&self, start: Position, kind: ExprKind) -> Expr {
+        Expr::new(kind).with_range(SourceRange::new(start, self.prev_end))
+    }
+
+    /// Parse statements separated by newlines or semicolons, until the end
+    /// of the input or, in a block, an `end` keyword.
+    fn parse_statements(&mut self, in_block: bool) -> Vec<Expr> {
+        let mut exprs = Vec::new();
+        loop {
+            while matches!(self.cur(), Token::Nl | Token::Semicolon) {
+                self.advance();
+            }
+            if *self.cur() == Token::Eof || (in_block && self.is_keyword("end"))
+            {
+                return exprs;
+            }
+
+            let before = self.index;
+            let depth = self.newlines.len();
+            match self.parse_statement() {
+                Ok(expr) => {
+                    exprs.push(expr);
+                    let is_separated =
+                        matches!(self.cur(), Token::Nl | Token::Semicolon)
+                            || *self.cur() == Token::Eof
+                            || (in_block && self.is_keyword("end"));
+                    if !is_separated {
+                        let message = format!(
+                            "Expected a newline or ';' but found {}",
+                            self.cur()
+                        );
+                        let _ = self.error::<()>(message);
+                        self.synchronize(before);
+                    }
+                }
+                Err(Abort) => {
+                    self.newlines.truncate(depth);
+                    self.synchronize(before);
+                }
+            }
+            if self.index == before {
+                self.advance();
+            }
+        }
+    }
+
+    /// Skip to the end of the statement starting at the given token index,
+    /// after a syntax error.
+    fn synchronize(&mut self, statement_start: usize) {
+        // Brackets and blocks opened by the statement so far:
+        let mut depth = self.tokens[statement_start..self.index]
+            .iter()
+            .map(|(token, _)| nesting(token))
+            .sum::<i32>();
+        loop {
+            let token = self.cur();
+            let nesting = nesting(token);
+            match token {
+                Token::Eof => return,
+                Token::Nl | Token::Semicolon if depth <= 0 => return,
+                _ if nesting < 0 && depth <= 0 => return,
+                _ => depth += nesting,
+            }
+            self.advance();
+        }
+    }
+
+    /// Skip to the next item in a list of items separated by commas, after
+    /// a syntax error.
+    fn synchronize_item(&mut self, close: &Token) {
+        let mut depth = 0;
+        loop {
+            let token = self.cur();
+            if *token == Token::Eof
+                || (depth == 0 && (token == close || *token == Token::Comma))
+            {
+                return;
+            }
+            match token {
+                Token::LParen | Token::LBracket | Token::LBrace => depth += 1,
+                Token::RParen | Token::RBracket | Token::RBrace => depth -= 1,
+                _ => (),
+            }
+            self.advance();
+        }
+    }
+
+    /// Parse items separated by commas, up to the closing token. The opening
+    /// token should already have been consumed. Newlines are ignored.
+    fn parse_items<T>(
+        &mut self,
+        close: Token,
+        parse_item: fn(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        self.newlines.push(true);
+        let depth = self.newlines.len();
+        let mut items = Vec::new();
+        while *self.cur() != close && *self.cur() != Token::Eof {
+            match parse_item(self) {
+                Ok(item) => items.push(item),
+                Err(Abort) => {
+                    self.newlines.truncate(depth);
+                    self.synchronize_item(&close);
+                }
+            }
+            if *self.cur() == Token::Comma {
+                self.advance();
+            } else if *self.cur() != close && *self.cur() != Token::Eof {
+                let message = format!(
+                    "Expected ',' or {} but found {}",
+                    close,
+                    self.cur()
+                );
+                let _ = self.error::<()>(message);
+                self.synchronize_item(&close);
+                if *self.cur() == Token::Comma {
+                    self.advance();
+                }
+            }
+        }
+        let result = self.expect(close);
+        self.newlines.pop();
+        result.map(|_| items)
+    }
+
+    fn parse_statement(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        if let Token::Id(id) = self.cur() {
+            let is_define = *self.next() == Token::Colon;
+            let is_assign = *self.next() == Token::Assign;
+            if is_define || is_assign {
+                let name = self.parse_var_name()?;
+                self.advance();
+                self.skip_newlines();
+                let value = Box::new(self.parse_expr()?);
+                let kind = if is_define {
+                    ExprKind::Define { name, value }
+                } else {
+                    ExprKind::Assign { name, value }
+                };
+                return Ok(self.finish(start, kind));
+            }
+            // Avoid a confusing error for a misplaced keyword:
+            if id == "else" || id == "catch" {
+                let message = format!("Unexpected '{}'", id);
+                return self.error(message);
+            }
+        }
+        self.parse_expr()
+    }
+
+    fn parse_expr(&mut self) -> ParseResult<Expr> {
+        self.parse_or()
+    }
+
+    /// Parse a binary operator and its right operand, with the left
+    /// operand already parsed.
+    fn parse_binary(
+        &mut self,
+        lhs: Expr,
+        op: BinaryOp,
+        parse_rhs: fn(&mut Self) -> ParseResult<Expr>,
+    ) -> ParseResult<Expr> {
+        let start = lhs.range().expect("parsed exprs have a range").start();
+        self.advance();
+        self.skip_newlines();
+        let rhs = parse_rhs(self)?;
+        Ok(self.finish(start, lhs.binary(op, rhs).into_kind()))
+    }
+
+    fn parse_or(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.is_keyword("or") {
+            expr = self.parse_binary(expr, BinaryOp::Or, Self::parse_and)?;
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_compare()?;
+        while self.is_keyword("and") {
+            expr =
+                self.parse_binary(expr, BinaryOp::And, Self::parse_compare)?;
+        }
+        Ok(expr)
+    }
+
+    fn parse_compare(&mut self) -> ParseResult<Expr> {
+        let expr = self.parse_range()?;
+        let op = match self.cur() {
+            Token::Eq => BinaryOp::Eq,
+            Token::NotEq => BinaryOp::NotEq,
+            Token::Lt => BinaryOp::Lt,
+            Token::LtEq => BinaryOp::LtEq,
+            Token::Gt => BinaryOp::Gt,
+            Token::GtEq => BinaryOp::GtEq,
+            Token::Cmp => BinaryOp::Cmp,
+            _ => return Ok(expr),
+        };
+        self.parse_binary(expr, op, Self::parse_range)
+    }
+
+    fn parse_range(&mut self) -> ParseResult<Expr> {
+        let expr = self.parse_add()?;
+        if *self.cur() == Token::DotDot {
+            self.parse_binary(expr, BinaryOp::Range, Self::parse_add)
+        } else {
+            Ok(expr)
+        }
+    }
+
+    fn parse_add(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_mul()?;
+        loop {
+            expr = match self.cur() {
+                Token::Plus => {
+                    self.parse_binary(expr, BinaryOp::Add, Self::parse_mul)?
+                }
+                Token::Minus => {
+                    self.parse_binary(expr, BinaryOp::Sub, Self::parse_mul)?
+                }
+                Token::Val(Value::Number(number))
+                    if number.to_f64().is_sign_negative() =>
+                {
+                    // The tokenizer reads `x-1` as `x` and `-1`:
+                    self.parse_negative_rhs(expr, -number.clone())?
+                }
+                _ => return Ok(expr),
+            };
+        }
+    }
+
+    /// Parse a subtraction where the tokenizer has read the minus sign as
+    /// part of a negative number. The number is the first operand of the
+    /// right hand side, so `x-1*2` is parsed as `x - (1 * 2)`.
+    fn parse_negative_rhs(
+        &mut self,
+        lhs: Expr,
+        number: Number,
+    ) -> ParseResult<Expr> {
+        let start = lhs.range().expect("parsed exprs have a range").start();
+        let range = self.cur_range();
+        self.advance();
+        let number_start = Position {
+            line: range.start().line,
+            column: range.start().column + 1,
+        };
+        let rhs = Expr::literal(number)
+            .with_range(SourceRange::new(number_start, range.end()));
+        let rhs = self.parse_postfix_ops(number_start, rhs)?;
+        let rhs = self.parse_mul_ops(rhs)?;
+        Ok(self.finish(start, lhs.binary(BinaryOp::Sub, rhs).into_kind()))
+    }
+
+    fn parse_mul(&mut self) -> ParseResult<Expr> {
+        let expr = self.parse_unary()?;
+        self.parse_mul_ops(expr)
+    }
+
+    /// Parse any multiplications and divisions following the expression.
+    fn parse_mul_ops(&mut self, mut expr: Expr) -> ParseResult<Expr> {
+        loop {
+            expr = match self.cur() {
+                Token::Star => {
+                    self.parse_binary(expr, BinaryOp::Mul, Self::parse_unary)?
+                }
+                Token::Slash => {
+                    self.parse_binary(expr, BinaryOp::Div, Self::parse_unary)?
+                }
+                _ => return Ok(expr),
+            };
+        }
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        let op = if self.is_keyword("not") {
+            UnaryOp::Not
+        } else if *self.cur() == Token::Minus {
+            UnaryOp::Neg
+        } else {
+            return self.parse_postfix();
+        };
+        self.advance();
+        let operand = self.parse_unary()?;
+        Ok(self.finish(start, Expr::unary(op, operand).into_kind()))
+    }
+
+    fn parse_postfix(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        let expr = self.parse_primary()?;
+        self.parse_postfix_ops(start, expr)
+    }
+
+    /// Parse any dotted calls, gets and indexes following the expression,
+    /// which starts at `start`.
+    fn parse_postfix_ops(
+        &mut self,
+        start: Position,
+        mut expr: Expr,
+    ) -> ParseResult<Expr> {
+        loop {
+            let kind = match self.cur() {
+                Token::Dot => {
+                    self.advance();
+                    let func = self.parse_func_name()?;
+                    let args = if *self.cur() == Token::LParen {
+                        self.advance();
+                        self.parse_items(Token::RParen, Self::parse_expr)?
+                    } else {
+                        Vec::new()
+                    };
+                    expr.dot_call(func, args).into_kind()
+                }
+                Token::Arrow => {
+                    self.advance();
+                    let name = self.parse_tag_name()?;
+                    expr.get(name).into_kind()
+                }
+                Token::LBracket => {
+                    self.advance();
+                    self.newlines.push(true);
+                    let index = self.parse_expr()?;
+                    self.expect(Token::RBracket)?;
+                    self.newlines.pop();
+                    expr.index(index).into_kind()
+                }
+                _ => return Ok(expr),
+            };
+            expr = self.finish(start, kind);
+        }
+    }
+
+    fn parse_primary(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        let id = match self.cur().clone() {
+            Token::Val(value) => {
+                self.advance();
+                return Ok(self.finish(start, ExprKind::Literal(value)));
+            }
+            Token::LParen if self.is_lambda() => return self.parse_lambda(),
+            Token::LParen => {
+                self.advance();
+                self.newlines.push(true);
+                let expr = self.parse_statement()?;
+                self.expect(Token::RParen)?;
+                self.newlines.pop();
+                return Ok(expr);
+            }
+            Token::LBracket => {
+                self.advance();
+                let items =
+                    self.parse_items(Token::RBracket, Self::parse_expr)?;
+                return Ok(self.finish(start, ExprKind::List(items)));
+            }
+            Token::LBrace => {
+                self.advance();
+                let entries =
+                    self.parse_items(Token::RBrace, Self::parse_dict_entry)?;
+                return Ok(self.finish(start, ExprKind::Dict(entries)));
+            }
+            Token::Id(id) => id,
+            token => {
+                let message =
+                    format!("Expected an expression but found {}", token);
+                return self.error(message);
+            }
+        };
+
+        let literal = match id.as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            "null" => Some(Value::Null),
+            _ => None,
+        };
+        if let Some(value) = literal {
+            self.advance();
+            return Ok(self.finish(start, ExprKind::Literal(value)));
+        }
+
+        match id.as_str() {
+            "if" => self.parse_if(),
+            "try" => self.parse_try(),
+            "do" => {
+                let body = self.parse_block()?;
+                Ok(self.finish(start, ExprKind::Block(body)))
+            }
+            "defcomp" => self.parse_defcomp(),
+            "return" | "throw" => {
+                self.advance();
+                let value = Box::new(self.parse_expr()?);
+                let kind = if id == "return" {
+                    ExprKind::Return(value)
+                } else {
+                    ExprKind::Throw(value)
+                };
+                Ok(self.finish(start, kind))
+            }
+            _ if *self.next() == Token::FatArrow => self.parse_lambda(),
+            _ if *self.next() == Token::LParen
+                || *self.next() == Token::DoubleColon =>
+            {
+                let func = self.parse_func_name()?;
+                if *self.cur() != Token::LParen {
+                    let message = format!(
+                        "Expected '(' after {} but found {}",
+                        func,
+                        self.cur()
+                    );
+                    return self.error(message);
+                }
+                self.advance();
+                let args = self.parse_items(Token::RParen, Self::parse_expr)?;
+                Ok(self.finish(start, ExprKind::Call { func, args }))
+            }
+            _ => {
+                let name = self.parse_var_name()?;
+                Ok(self.finish(start, ExprKind::Var(name)))
+            }
+        }
+    }
+
+    /// Return true if the current token starts the parameters of
+    /// a function, like `(a, b) =>`.
+    fn is_lambda(&self) -> bool {
+        let mut depth = 0;
+        for (index, (token, _)) in
+            self.tokens.iter().enumerate().skip(self.cur_index())
+        {
+            match token {
+                Token::LParen => depth += 1,
+                Token::RParen if depth == 1 => {
+                    return self.tokens[index + 1..]
+                        .iter()
+                        .find(|(token, _)| *token != Token::Nl)
+                        .is_some_and(|(token, _)| *token == Token::FatArrow)
+                }
+                Token::RParen => depth -= 1,
+                Token::Eof => return false,
+                _ => (),
+            }
+        }
+        false
+    }
+
+    fn parse_lambda(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        let params = if *self.cur() == Token::LParen {
+            self.advance();
+            self.parse_items(Token::RParen, Self::parse_param)?
+        } else {
+            let start = self.cur_range().start();
+            let param = Param::new(self.parse_var_name()?);
+            vec![param.or_else(|err| self.error_at(start, err.to_string()))?]
+        };
+        self.skip_newlines();
+        self.expect(Token::FatArrow)?;
+        self.skip_newlines();
+        // The body may be an assignment, like `x => total = total + x`:
+        let body = Box::new(self.parse_statement()?);
+        Ok(self.finish(start, ExprKind::Lambda { params, body }))
+    }
+
+    fn parse_param(&mut self) -> ParseResult<Param> {
+        let start = self.cur_range().start();
+        let name = self.parse_var_name()?;
+        let param = if *self.cur() == Token::Colon {
+            self.advance();
+            Param::with_default(name, self.parse_expr()?)
+        } else {
+            Param::new(name)
+        };
+        param.or_else(|err| self.error_at(start, err.to_string()))
+    }
+
+    fn parse_dict_entry(&mut self) -> ParseResult<(TagName, Expr)> {
+        let start = self.cur_range().start();
+        if *self.cur() == Token::Minus {
+            self.advance();
+            let name = self.parse_tag_name()?;
+            let value = Value::RemoveMarker;
+            return Ok((name, self.finish(start, ExprKind::Literal(value))));
+        }
+        let name = self.parse_tag_name()?;
+        if *self.cur() == Token::Colon {
+            self.advance();
+            Ok((name, self.parse_expr()?))
+        } else {
+            Ok((name, self.finish(start, ExprKind::Literal(Value::Marker))))
+        }
+    }
+
+    fn parse_if(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        self.advance();
+        self.expect(Token::LParen)?;
+        self.newlines.push(true);
+        let cond = Box::new(self.parse_expr()?);
+        self.expect(Token::RParen)?;
+        self.newlines.pop();
+        self.skip_newlines();
+        let then = Box::new(self.parse_expr()?);
+        let otherwise = if self.is_next_keyword("else") {
+            self.skip_newlines();
+            self.advance();
+            self.skip_newlines();
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
+        let kind = ExprKind::If {
+            cond,
+            then,
+            otherwise,
+        };
+        Ok(self.finish(start, kind))
+    }
+
+    fn parse_try(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        self.advance();
+        self.skip_newlines();
+        let body = Box::new(self.parse_expr()?);
+        self.skip_newlines();
+        self.expect_keyword("catch")?;
+        let var = if *self.cur() == Token::LParen {
+            self.advance();
+            let var = self.parse_var_name()?;
+            self.expect(Token::RParen)?;
+            Some(var)
+        } else {
+            None
+        };
+        self.skip_newlines();
+        let handler = Box::new(self.parse_expr()?);
+        Ok(self.finish(start, ExprKind::Try { body, var, handler }))
+    }
+
+    /// Return true if the keyword is the next token, ignoring newlines.
+    fn is_next_keyword(&self, keyword: &str) -> bool {
+        matches!(self.cur_past_newlines(), Token::Id(id) if id == keyword)
+    }
+
+    /// Parse a `do`/`end` block, returning its statements.
+    fn parse_block(&mut self) -> ParseResult<Vec<Expr>> {
+        self.expect_keyword("do")?;
+        self.newlines.push(false);
+        let exprs = self.parse_statements(true);
+        let result = self.expect_keyword("end");
+        self.newlines.pop();
+        result.map(|_| exprs)
+    }
+
+    fn parse_defcomp(&mut self) -> ParseResult<Expr> {
+        let start = self.cur_range().start();
+        self.advance();
+        self.newlines.push(false);
+        let mut cells = Vec::new();
+        loop {
+            while matches!(self.cur(), Token::Nl | Token::Semicolon) {
+                self.advance();
+            }
+            if self.is_keyword("do") {
+                break;
+            }
+            if *self.cur() == Token::Eof || self.is_keyword("end") {
+                let message = format!("Expected 'do' but found {}", self.cur());
+                return self.error(message);
+            }
+            let name = self.parse_var_name()?;
+            self.expect(Token::Colon)?;
+            cells.push((name, self.parse_expr()?));
+        }
+        let body = self.parse_block()?;
+        self.skip_newlines();
+        self.expect_keyword("end")?;
+        self.newlines.pop();
+        Ok(self.finish(start, ExprKind::Defcomp { cells, body }))
+    }
+
+    /// Parse a function name, which may be qualified, like `core::now`.
+    fn parse_func_name(&mut self) -> ParseResult<Qname> {
+        let start = self.cur_range().start();
+        let mut name = self.parse_id()?;
+        if *self.cur() == Token::DoubleColon {
+            self.advance();
+            name = format!("{}::{}", name, self.parse_id()?);
+        }
+        match Qname::new(name) {
+            Ok(qname) => Ok(qname),
+            Err(err) => self.error_at(start, err.to_string()),
+        }
+    }
+
+    /// Parse a variable name, which cannot be a keyword.
+    fn parse_var_name(&mut self) -> ParseResult<TagName> {
+        if let Token::Id(id) = self.cur() {
+            if is_axon_keyword(id) {
+                let message = format!("Unexpected '{}'", id);
+                return self.error(message);
+            }
+        }
+        self.parse_tag_name()
+    }
+
+    fn parse_tag_name(&mut self) -> ParseResult<TagName> {
+        let name = self.parse_id()?;
+        match TagName::new(name) {
+            Some(name) => Ok(name),
+            None => {
+                self.index -= 1;
+                let message = format!("Invalid name {}", self.cur());
+                self.error(message)
+            }
+        }
+    }
+
+    fn parse_id(&mut self) -> ParseResult<String> {
+        match self.cur().clone() {
+            Token::Id(id) => {
+                self.advance();
+                Ok(id)
+            }
+            token => {
+                let message = format!("Expected a name but found {}", token);
+                self.error(message)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AxonSource;
+    use crate::{Expr, ExprKind, Number, Value};
+
+    fn round_trip(s: &str) -> String {
+        s.parse::<Expr>().unwrap().to_axon_code()
+    }
+
+    #[test]
+    fn literals_work() {
+        assert_eq!(round_trip("12.5kW"), "12.5kW");
+        assert_eq!(round_trip("\"a\\\"b\\n\""), "\"a\\\"b\\n\"");
+        assert_eq!(round_trip("`http://a/\\``"), "`http://a/\\``");
+        assert_eq!(round_trip("@abc-123"), "@abc-123");
+        assert_eq!(round_trip("^hot-water"), "^hot-water");
+        assert_eq!(round_trip("2024-01-31"), "2024-01-31");
+        assert_eq!(round_trip("08:30"), "08:30:00");
+        assert_eq!(
+            round_trip("2024-01-31T08:30:00-05:00 New_York"),
+            "parseDateTime(\"2024-01-31T08:30:00-05:00 New_York\", \
+             \"YYYY-MM-DD'T'hh:mm:SS.FFFFFFFFFz zzzz\")"
+        );
+        assert_eq!(round_trip("[true, false, null,]"), "[true, false, null]");
+        assert_eq!(
+            round_trip("{site, dis: \"A\", -equip}"),
+            "{site, dis: \"A\", -equip}"
+        );
+        assert_eq!(
+            "-3".parse::<Expr>().unwrap(),
+            Expr::literal(Number::new_unitless(-3.0))
+        );
+    }
+
+    #[test]
+    fn operators_work() {
+        assert_eq!(round_trip("a + b * c"), "a + b * c");
+        assert_eq!(round_trip("(a + b) * c"), "(a + b) * c");
+        assert_eq!(round_trip("a-1"), "a - 1");
+        assert_eq!(round_trip("a - -1"), "a - -1");
+        assert_eq!(round_trip("a-1*2"), "a - 1 * 2");
+        assert_eq!(round_trip("y: x-1*2"), "y: x - 1 * 2");
+        assert_eq!(round_trip("a-1.abs()"), "a - 1.abs()");
+        assert_eq!(round_trip("a-1[0]/2-3"), "a - 1[0] / 2 - 3");
+        assert_eq!(round_trip("not a and b or c"), "not a and b or c");
+        assert_eq!(round_trip("a <=> b"), "a <=> b");
+        assert_eq!(
+            round_trip("2024-01-01..2024-01-31"),
+            "2024-01-01..2024-01-31"
+        );
+        assert_eq!(round_trip("a and\n  b"), "a and b");
+        assert_eq!(round_trip("-x->y[0]"), "-x->y[0]");
+        assert_eq!(round_trip("@abc->dis"), "@abc->dis");
+    }
+
+    #[test]
+    fn calls_work() {
+        assert_eq!(
+            round_trip("readAll(site).map(s => s->dis).sort"),
+            "readAll(site).map((s) => s->dis).sort()"
+        );
+        assert_eq!(round_trip("core::now()"), "core::now()");
+        assert_eq!(round_trip("x.core::toStr"), "x.core::toStr()");
+        assert_eq!(round_trip("f(\n  1,\n  2,\n)"), "f(1, 2)");
+    }
+
+    #[test]
+    fn control_flow_works() {
+        let source = "(a, b: 2) => do
+  x: if (a > b) a else b
+  if (x == 0)
+    throw \"zero\"
+  else if (x < 0) do
+    return -x
+  end
+  try x / 2 catch (ex) null
+end";
+        assert_eq!(
+            round_trip(source),
+            "(a, b: 2) => do x: if (a > b) a else b; \
+             if (x == 0) (throw \"zero\") else if (x < 0) (do return -x; end); \
+             try x / 2 catch (ex) null; end"
+        );
+        assert_eq!(round_trip("x = x + 1"), "x = x + 1");
+        assert_eq!(round_trip("x => y = x"), "(x) => (y = x)");
+    }
+
+    #[test]
+    fn defcomp_works() {
+        let source = "defcomp
+  a: {defVal: 0}
+  sum: {ro}
+  do
+    sum = a + 1
+  end
+end";
+        let expr: Expr = source.parse().unwrap();
+        match expr.kind() {
+            ExprKind::Defcomp { cells, body } => {
+                assert_eq!(cells.len(), 2);
+                assert_eq!(body.len(), 1);
+            }
+            kind => panic!("Unexpected {:?}", kind),
+        }
+        assert_eq!(expr.to_axon_code().parse::<Expr>().unwrap(), expr);
+    }
+
+    #[test]
+    fn ranges_work() {
+        let source = AxonSource::parse("x: 1\nfoo(x,\n  y->z)");
+        let exprs = source.exprs();
+        assert_eq!(exprs.len(), 2);
+        let range = exprs[1].range().unwrap();
+        assert_eq!((range.start().line(), range.start().column()), (2, 1));
+        assert_eq!((range.end().line(), range.end().column()), (3, 8));
+        match exprs[1].kind() {
+            ExprKind::Call { args, .. } => {
+                let range = args[1].range().unwrap();
+                assert_eq!(range.to_string(), "3:3-3:7");
+            }
+            kind => panic!("Unexpected {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn comments_work() {
+        let source = AxonSource::parse(
+            "// first\nx: 1 /* inline */ + 2\n/* block\n comment */",
+        );
+        assert!(source.errors().is_empty());
+        let comments: Vec<&str> =
+            source.comments().iter().map(|c| c.text()).collect();
+        assert_eq!(
+            comments,
+            vec!["// first", "/* inline */", "/* block\n comment */"]
+        );
+        assert_eq!(source.comments()[1].range().to_string(), "2:6-2:18");
+    }
+
+    #[test]
+    fn errors_are_recoverable() {
+        let source = AxonSource::parse(
+            "do\n  a: 1 + * 2\n  b: [1, , 3]\n  c: 3 %\n  d: if x\nend",
+        );
+        let errors: Vec<(usize, usize)> = source
+            .errors()
+            .iter()
+            .map(|err| (err.line(), err.column()))
+            .collect();
+        assert_eq!(errors, vec![(2, 10), (3, 10), (4, 8), (5, 9)]);
+        match source.exprs()[0].kind() {
+            ExprKind::Block(exprs) => {
+                // Only `b` and `c` could be parsed:
+                let code: Vec<String> =
+                    exprs.iter().map(|expr| expr.to_axon_code()).collect();
+                assert_eq!(code, vec!["b: [1, 3]", "c: 3"]);
+            }
+            kind => panic!("Unexpected {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn parse_errors_work() {
+        let err = "foo(".parse::<Expr>().unwrap_err();
+        assert_eq!(err.line(), 1);
+        assert_eq!(err.column(), 5);
+        assert!("a b".parse::<Expr>().is_err());
+        assert!("1; 2".parse::<Expr>().is_err());
+        assert!("".parse::<Expr>().is_err());
+        assert!("Foo".parse::<Expr>().is_err());
+        assert!("if: 1".parse::<Expr>().is_err());
+        assert_eq!("Core::now()".parse::<Expr>().unwrap_err().column(), 1);
+        assert_eq!(
+            "x".parse::<Expr>().unwrap().kind(),
+            &ExprKind::Var("x".parse().unwrap())
+        );
+        assert_eq!("null".parse::<Expr>().unwrap(), Expr::literal(Value::Null));
+    }
+}
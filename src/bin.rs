@@ -0,0 +1,193 @@
+use crate::zinc::zinc_str;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use thiserror::Error;
+
+/// A Haystack Bin, representing binary data with a MIME type. The binary
+/// data itself is optional, because Haystack servers usually only send
+/// the MIME type.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Bin {
+    mime: String,
+    data: Option<Vec<u8>>,
+}
+
+impl Bin {
+    /// Create a new `Bin`. Returns an error if the MIME type is not valid.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Bin;
+    /// let my_bin = Bin::new("image/png".to_string()).unwrap();
+    /// assert!(Bin::new("png".to_string()).is_err());
+    /// ```
+    pub fn new(mime: String) -> Result<Self, BinError> {
+        if is_mime_type(&mime) {
+            Ok(Self { mime, data: None })
+        } else {
+            Err(BinError::InvalidMime(mime))
+        }
+    }
+
+    /// Create a new `Bin` containing binary data. Returns an error if the
+    /// MIME type is not valid.
+    pub fn new_with_data(
+        mime: String,
+        data: Vec<u8>,
+    ) -> Result<Self, BinError> {
+        Ok(Self::new(mime)?.with_data(Some(data)))
+    }
+
+    /// Create a new `Bin` containing binary data which is encoded as
+    /// a base64 string. Returns an error if the MIME type is not valid or
+    /// the string is not valid base64.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Bin;
+    /// let bin = Bin::from_base64("text/plain".to_string(), "aGk=").unwrap();
+    /// assert_eq!(bin.data(), Some("hi".as_bytes()));
+    /// ```
+    pub fn from_base64(mime: String, base64: &str) -> Result<Self, BinError> {
+        let data = STANDARD
+            .decode(base64)
+            .map_err(|_| BinError::InvalidBase64)?;
+        Self::new_with_data(mime, data)
+    }
+
+    /// Return this `Bin` with its binary data replaced.
+    pub fn with_data(self, data: Option<Vec<u8>>) -> Self {
+        Self {
+            mime: self.mime,
+            data,
+        }
+    }
+
+    /// Return the MIME type of this `Bin`.
+    pub fn mime(&self) -> &str {
+        &self.mime
+    }
+
+    /// Return the binary data of this `Bin`, if present.
+    pub fn data(&self) -> Option<&[u8]> {
+        self.data.as_deref()
+    }
+
+    /// Return the binary data of this `Bin` encoded as a base64 string,
+    /// if present.
+    pub fn to_base64(&self) -> Option<String> {
+        self.data().map(|data| STANDARD.encode(data))
+    }
+
+    /// Return this `Bin` as Axon code. Axon has no literal for bins, so
+    /// this is an `xstr` call, and it does not include the binary data.
+    pub fn to_axon_code(&self) -> String {
+        format!("xstr(\"Bin\", {})", zinc_str(self.mime()))
+    }
+}
+
+impl std::fmt::Display for Bin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bin({})", zinc_str(self.mime()))
+    }
+}
+
+/// Return true if the string is a valid MIME type, like `text/plain` or
+/// `text/plain; charset=utf-8`.
+fn is_mime_type(s: &str) -> bool {
+    let mut parts = s.split(';');
+    let is_valid_type = match parts.next().and_then(|t| t.split_once('/')) {
+        Some((type_name, subtype)) => is_token(type_name) && is_token(subtype),
+        None => false,
+    };
+    is_valid_type
+        && parts.all(|param| match param.trim_start().split_once('=') {
+            Some((name, value)) => {
+                is_token(name) && (is_token(value) || is_quoted(value))
+            }
+            None => false,
+        })
+}
+
+/// Return true if the string is a MIME token, which is a sequence of
+/// printable ASCII characters without spaces or special characters.
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_graphic() && !"()<>@,;:\\\"/[]?=".contains(c))
+}
+
+fn is_quoted(s: &str) -> bool {
+    s.len() >= 2
+        && s.starts_with('"')
+        && s.ends_with('"')
+        && !s[1..s.len() - 1].contains(['"', '\\'])
+}
+
+/// An error indicating that a `Bin` could not be created.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum BinError {
+    #[error("The MIME type {0} is not valid")]
+    InvalidMime(String),
+    #[error("The Bin data is not valid base64")]
+    InvalidBase64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bin, BinError};
+
+    #[test]
+    fn mime_validation_works() {
+        let valid = vec![
+            "image/png",
+            "text/plain; charset=utf-8",
+            "application/vnd.ms-excel",
+            "text/plain;format=\"flowed\"",
+        ];
+        for mime in valid {
+            assert!(Bin::new(mime.to_owned()).is_ok(), "{}", mime);
+        }
+        let invalid = vec![
+            "",
+            "png",
+            "image/",
+            "/png",
+            "image/png/x",
+            "image /png",
+            "text/plain; charset",
+            "text/plain; charset=\"utf\"8\"",
+        ];
+        for mime in invalid {
+            assert_eq!(
+                Bin::new(mime.to_owned()),
+                Err(BinError::InvalidMime(mime.to_owned())),
+                "{}",
+                mime
+            );
+        }
+    }
+
+    #[test]
+    fn base64_works() {
+        let bin =
+            Bin::new_with_data("text/plain".to_owned(), b"hello".to_vec())
+                .unwrap();
+        assert_eq!(bin.to_base64(), Some("aGVsbG8=".to_owned()));
+        let decoded =
+            Bin::from_base64("text/plain".to_owned(), "aGVsbG8=").unwrap();
+        assert_eq!(decoded, bin);
+        assert_eq!(
+            Bin::from_base64("text/plain".to_owned(), "not base64!"),
+            Err(BinError::InvalidBase64)
+        );
+        assert_eq!(Bin::new("text/plain".to_owned()).unwrap().data(), None);
+    }
+
+    #[test]
+    fn axon_code_works() {
+        let bin = Bin::new("text/plain".to_owned()).unwrap();
+        assert_eq!(bin.to_axon_code(), "xstr(\"Bin\", \"text/plain\")");
+        assert_eq!(bin.to_string(), "Bin(\"text/plain\")");
+    }
+}
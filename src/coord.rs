@@ -1,6 +1,10 @@
+use crate::BasicNumber;
+
 /// A Haystack Coord, representing a geographical
-/// coordinate.
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// coordinate. Coords are ordered by latitude and then longitude. When
+/// comparing or hashing coords, NaN is equal to itself and `-0.0` is equal
+/// to `0.0`.
+#[derive(Clone, Copy, Debug)]
 pub struct Coord {
     lat: f64,
     lng: f64,
@@ -21,6 +25,53 @@ impl Coord {
     pub fn lng(&self) -> f64 {
         self.lng
     }
+
+    /// Return this coord as Axon code, like `coord(37.55, -77.45)`.
+    pub fn to_axon_code(&self) -> String {
+        let lat = BasicNumber::new_unitless(self.lat);
+        let lng = BasicNumber::new_unitless(self.lng);
+        format!("coord({}, {})", lat.to_axon_code(), lng.to_axon_code())
+    }
+}
+
+impl std::cmp::PartialEq for Coord {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl std::cmp::Eq for Coord {}
+
+impl std::hash::Hash for Coord {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        normalize(self.lat).to_bits().hash(state);
+        normalize(self.lng).to_bits().hash(state);
+    }
+}
+
+impl std::cmp::PartialOrd for Coord {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::Ord for Coord {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        normalize(self.lat)
+            .total_cmp(&normalize(other.lat))
+            .then_with(|| normalize(self.lng).total_cmp(&normalize(other.lng)))
+    }
+}
+
+/// Return the value with a single representation of NaN and zero.
+fn normalize(value: f64) -> f64 {
+    if value.is_nan() {
+        f64::NAN
+    } else if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
 }
 
 impl std::fmt::Display for Coord {
@@ -0,0 +1,151 @@
+use chrono::{Datelike, NaiveDate};
+use thiserror::Error;
+
+/// A Haystack Date, representing a calendar date with no time or timezone.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Date(NaiveDate);
+
+/// The years which can be written in Haystack formats like Zinc, which
+/// always use four digits.
+const YEARS: std::ops::RangeInclusive<i32> = 0..=9999;
+
+impl Date {
+    /// Create a new `Date`. Returns `None` if the year, month and day
+    /// do not form a valid date, or if the year is not between 0 and 9999.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Date;
+    /// let my_date = Date::new(2024, 3, 1).unwrap();
+    /// ```
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        if is_haystack_year(year) {
+            NaiveDate::from_ymd_opt(year, month, day).map(Self)
+        } else {
+            None
+        }
+    }
+
+    /// Return the year component of this `Date`.
+    pub fn year(&self) -> i32 {
+        self.0.year()
+    }
+
+    /// Return the month component of this `Date`, starting from 1.
+    pub fn month(&self) -> u32 {
+        self.0.month()
+    }
+
+    /// Return the day of the month component of this `Date`, starting from 1.
+    pub fn day(&self) -> u32 {
+        self.0.day()
+    }
+
+    /// Return the underlying `chrono::NaiveDate`.
+    pub fn naive_date(&self) -> NaiveDate {
+        self.0
+    }
+
+    /// Return this date as an Axon date literal.
+    pub fn to_axon_code(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.format("%Y-%m-%d"))
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = ParseDateError;
+
+    /// Parse a Haystack date, like `2024-03-01`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // chrono accepts single digit months and days, Haystack does not:
+        if !matches_pattern(s, "dddd-dd-dd") {
+            return Err(ParseDateError::from_str(s));
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(Self)
+            .map_err(|_| ParseDateError::from_str(s))
+    }
+}
+
+impl std::convert::TryFrom<NaiveDate> for Date {
+    type Error = ParseDateError;
+
+    /// Convert a `chrono::NaiveDate` into a `Date`. Returns an error if the
+    /// year is not between 0 and 9999.
+    fn try_from(date: NaiveDate) -> Result<Self, Self::Error> {
+        if is_haystack_year(date.year()) {
+            Ok(Self(date))
+        } else {
+            Err(ParseDateError::from_str(&date.to_string()))
+        }
+    }
+}
+
+/// Return true if the year is between 0 and 9999.
+pub(crate) fn is_haystack_year(year: i32) -> bool {
+    YEARS.contains(&year)
+}
+
+/// Return true if the string matches the pattern, where each `d` in the
+/// pattern matches an ASCII digit and all other characters match themselves.
+pub(crate) fn matches_pattern(s: &str, pattern: &str) -> bool {
+    s.len() == pattern.len()
+        && s.bytes().zip(pattern.bytes()).all(|(c, p)| match p {
+            b'd' => c.is_ascii_digit(),
+            p => c == p,
+        })
+}
+
+/// An error indicating that a `Date` could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse a Date from the string {unparsable_date}")]
+pub struct ParseDateError {
+    unparsable_date: String,
+}
+
+impl ParseDateError {
+    pub(crate) fn from_str(s: &str) -> Self {
+        Self {
+            unparsable_date: s.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Date;
+
+    #[test]
+    fn parse_works() {
+        let date: Date = "2024-03-01".parse().unwrap();
+        assert_eq!(date, Date::new(2024, 3, 1).unwrap());
+        assert_eq!(date.to_string(), "2024-03-01");
+    }
+
+    #[test]
+    fn out_of_range_years_fail() {
+        use chrono::NaiveDate;
+        use std::convert::TryFrom;
+
+        assert_eq!(Date::new(0, 1, 1).unwrap().to_string(), "0000-01-01");
+        assert_eq!(Date::new(9999, 12, 31).unwrap().to_string(), "9999-12-31");
+        assert!(Date::new(10000, 1, 1).is_none());
+        assert!(Date::new(-1, 12, 31).is_none());
+        let naive = NaiveDate::from_ymd_opt(10000, 1, 1).unwrap();
+        assert!(Date::try_from(naive).is_err());
+    }
+
+    #[test]
+    fn parse_invalid_fails() {
+        assert!("2024-3-1".parse::<Date>().is_err());
+        assert!("2024-02-30".parse::<Date>().is_err());
+        assert!("2024-03-01T00:00:00Z".parse::<Date>().is_err());
+        assert!("".parse::<Date>().is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use crate::date::{is_haystack_year, matches_pattern};
+use crate::zinc::zinc_str;
+use crate::{Date, Time};
+use chrono::{Datelike, FixedOffset, TimeZone};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// The `parseDateTime` pattern which matches the Haystack date time format.
+const AXON_PATTERN: &str = "YYYY-MM-DD'T'hh:mm:SS.FFFFFFFFFz zzzz";
+
+/// A Haystack DateTime, representing an instant in time with a UTC offset
+/// and a Haystack timezone name (for example `New_York`). Date times are
+/// ordered by instant, and then by timezone name.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DateTime {
+    date_time: chrono::DateTime<FixedOffset>,
+    time_zone: String,
+}
+
+impl DateTime {
+    /// Create a new `DateTime`. The timezone name should be
+    /// a Haystack timezone name, like `New_York` or `UTC`. Returns `None`
+    /// if the timezone name contains characters which are not allowed in
+    /// Haystack timezone names, or if the year is not between 0 and 9999.
+    pub fn new(
+        date_time: chrono::DateTime<FixedOffset>,
+        time_zone: String,
+    ) -> Option<Self> {
+        if is_time_zone_name(&time_zone) && is_haystack_year(date_time.year()) {
+            Some(Self {
+                date_time,
+                time_zone,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Return the underlying `chrono::DateTime`.
+    pub fn date_time(&self) -> &chrono::DateTime<FixedOffset> {
+        &self.date_time
+    }
+
+    /// Return the Haystack timezone name of this `DateTime`.
+    pub fn time_zone(&self) -> &str {
+        &self.time_zone
+    }
+
+    /// Return the date component of this `DateTime`.
+    pub fn date(&self) -> Date {
+        Date::try_from(self.date_time.date_naive())
+            .expect("date times should have a year between 0 and 9999")
+    }
+
+    /// Return the time component of this `DateTime`.
+    pub fn time(&self) -> Time {
+        self.date_time.time().into()
+    }
+
+    /// Return this `DateTime` formatted without the timezone name,
+    /// like `2024-03-01T08:00:00-05:00`. A zero offset is formatted as `Z`.
+    pub fn to_offset_string(&self) -> String {
+        let date_time = self.date_time();
+        let offset = if date_time.offset().local_minus_utc() == 0 {
+            "Z".to_owned()
+        } else {
+            date_time.format("%:z").to_string()
+        };
+        format!("{}{}", date_time.format("%Y-%m-%dT%H:%M:%S%.f"), offset)
+    }
+
+    /// Return a string containing Axon code representing this date time.
+    /// Axon has no date time literals, so this is a call to `parseDateTime`
+    /// with a pattern which keeps the offset and timezone name.
+    pub fn to_axon_code(&self) -> String {
+        format!(
+            "parseDateTime({}, {})",
+            zinc_str(&self.to_string()),
+            zinc_str(AXON_PATTERN)
+        )
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.to_offset_string(), self.time_zone())
+    }
+}
+
+impl std::str::FromStr for DateTime {
+    type Err = ParseDateTimeError;
+
+    /// Parse a Haystack date time, like `2024-03-01T08:00:00-05:00 New_York`.
+    /// If the timezone name is missing, it defaults to `UTC` for a zero
+    /// offset, or the matching `GMT` timezone otherwise.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDateTimeError::from_str(s);
+
+        let (date_time, time_zone) = match s.find(' ') {
+            Some(index) => (&s[..index], Some(&s[index + 1..])),
+            None => (s, None),
+        };
+
+        if !date_time.is_ascii() || date_time.len() < 20 {
+            return Err(err());
+        }
+        let (date, rest) = date_time.split_at(10);
+        let date: Date = date.parse().map_err(|_| err())?;
+        let rest = rest.strip_prefix('T').ok_or_else(err)?;
+
+        let (time, offset_seconds) = if let Some(time) = rest.strip_suffix('Z')
+        {
+            (time, 0)
+        } else {
+            let (time, offset) = rest.split_at(rest.len() - 6);
+            if !matches_pattern(&offset[1..], "dd:dd") {
+                return Err(err());
+            }
+            let hours: i32 = offset[1..3].parse().map_err(|_| err())?;
+            let minutes: i32 = offset[4..6].parse().map_err(|_| err())?;
+            let seconds = hours * 3600 + minutes * 60;
+            match &offset[..1] {
+                "+" => (time, seconds),
+                "-" => (time, -seconds),
+                _ => return Err(err()),
+            }
+        };
+        let time: Time = time.parse().map_err(|_| err())?;
+        let offset = FixedOffset::east_opt(offset_seconds).ok_or_else(err)?;
+
+        let time_zone = match time_zone {
+            Some(time_zone) => time_zone.to_owned(),
+            None => default_time_zone_name(offset_seconds).ok_or_else(err)?,
+        };
+
+        let naive = date.naive_date().and_time(time.naive_time());
+        let date_time = offset.from_local_datetime(&naive).single();
+        let date_time = date_time.ok_or_else(err)?;
+        Self::new(date_time, time_zone).ok_or_else(err)
+    }
+}
+
+/// Return true if the string can be a Haystack timezone name.
+fn is_time_zone_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-+".contains(c))
+}
+
+/// Return the name of the Haystack `GMT` timezone for an offset, which is
+/// used when a DateTime has an offset but no timezone name. Note that the
+/// sign of `GMT` timezones is the reverse of the offset sign. Returns
+/// `None` if no `GMT` timezone has the offset, like `+05:30`.
+pub(crate) fn default_time_zone_name(offset_seconds: i32) -> Option<String> {
+    if offset_seconds == 0 {
+        return Some("UTC".to_owned());
+    }
+    if offset_seconds % 3600 != 0 {
+        return None;
+    }
+    // The GMT timezones range from GMT-14 to GMT+12:
+    match -offset_seconds / 3600 {
+        hours @ 1..=12 => Some(format!("GMT+{}", hours)),
+        hours @ -14..=-1 => Some(format!("GMT{}", hours)),
+        _ => None,
+    }
+}
+
+/// An error indicating that a `DateTime` could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse a DateTime from the string {unparsable_date_time}")]
+pub struct ParseDateTimeError {
+    unparsable_date_time: String,
+}
+
+impl ParseDateTimeError {
+    pub(crate) fn from_str(s: &str) -> Self {
+        Self {
+            unparsable_date_time: s.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DateTime;
+    use crate::{Date, Time};
+    use chrono::Datelike;
+
+    #[test]
+    fn parse_works() {
+        let s = "2024-03-01T08:00:00-05:00 New_York";
+        let dt: DateTime = s.parse().unwrap();
+        assert_eq!(dt.date(), Date::new(2024, 3, 1).unwrap());
+        assert_eq!(dt.time(), Time::new(8, 0, 0).unwrap());
+        assert_eq!(dt.date_time().offset().local_minus_utc(), -5 * 3600);
+        assert_eq!(dt.time_zone(), "New_York");
+        assert_eq!(dt.to_string(), s);
+    }
+
+    #[test]
+    fn axon_code_works() {
+        let dt: DateTime =
+            "2024-03-01T08:00:00-05:00 New_York".parse().unwrap();
+        assert_eq!(
+            dt.to_axon_code(),
+            "parseDateTime(\"2024-03-01T08:00:00-05:00 New_York\", \
+             \"YYYY-MM-DD'T'hh:mm:SS.FFFFFFFFFz zzzz\")"
+        );
+    }
+
+    #[test]
+    fn new_invalid_fails() {
+        let dt: DateTime = "2024-03-01T08:00:00Z UTC".parse().unwrap();
+        let date_time = *dt.date_time();
+        let injected = "UTC\", \"x\") + evil(\"".to_owned();
+        assert!(DateTime::new(date_time, injected).is_none());
+        assert!(DateTime::new(date_time, "New York".to_owned()).is_none());
+        assert!(DateTime::new(date_time, "".to_owned()).is_none());
+        let dt = DateTime::new(date_time, "GMT+5".to_owned()).unwrap();
+        assert_eq!(dt.time_zone(), "GMT+5");
+
+        let late = date_time.with_year(10000).unwrap();
+        assert!(DateTime::new(late, "UTC".to_owned()).is_none());
+    }
+
+    #[test]
+    fn parse_utc_works() {
+        let dt: DateTime = "2024-03-01T13:00:00.5Z UTC".parse().unwrap();
+        assert_eq!(dt.to_string(), "2024-03-01T13:00:00.500Z UTC");
+        assert_eq!(dt.to_offset_string(), "2024-03-01T13:00:00.500Z");
+    }
+
+    #[test]
+    fn parse_default_time_zone_works() {
+        let dt: DateTime = "2024-03-01T13:00:00Z".parse().unwrap();
+        assert_eq!(dt.time_zone(), "UTC");
+        let dt: DateTime = "2024-03-01T13:00:00+10:00".parse().unwrap();
+        assert_eq!(dt.time_zone(), "GMT-10");
+        let dt: DateTime = "2024-03-01T13:00:00-12:00".parse().unwrap();
+        assert_eq!(dt.time_zone(), "GMT+12");
+    }
+
+    #[test]
+    fn parse_offset_without_time_zone_fails() {
+        assert!("2024-01-01T10:00:00+05:30".parse::<DateTime>().is_err());
+        assert!("2024-01-01T10:00:00-13:00".parse::<DateTime>().is_err());
+        let dt: DateTime = "2024-01-01T10:00:00+05:30 Kolkata".parse().unwrap();
+        assert_eq!(dt.time_zone(), "Kolkata");
+        assert_eq!(dt.to_string(), "2024-01-01T10:00:00+05:30 Kolkata");
+    }
+
+    #[test]
+    fn parse_invalid_fails() {
+        let invalid = vec![
+            "2024-03-01",
+            "2024-03-01T08:00:00",
+            "2024-03-01 08:00:00Z",
+            "2024-03-01T08:00:00-0500 New_York",
+            "2024-03-01T08:00:00-05:00 New York",
+            "2024-03-01T08:00:00-05:00 ",
+        ];
+        for s in invalid {
+            assert!(s.parse::<DateTime>().is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use crate::{
+    Bin, Coord, Date, DateTime, Grid, Number, Ref, Symbol, TagName, Time, Uri,
+    Value, Xstr,
+};
+
+/// A Haystack Dict, an ordered collection of tags. Each tag name maps to
+/// a `Value`. Tags are kept in the order in which they were first inserted,
+/// but two dicts with the same tags are equal regardless of tag order.
+#[derive(Clone, Debug, Default)]
+pub struct Dict {
+    tags: Vec<(TagName, Value)>,
+}
+
+impl Dict {
+    /// Create a new, empty `Dict`.
+    pub fn new() -> Self {
+        Self { tags: Vec::new() }
+    }
+
+    /// Insert a tag into this `Dict`. If the tag was already present, its
+    /// value is replaced and the old value is returned.
+    pub fn insert(&mut self, name: TagName, value: Value) -> Option<Value> {
+        match self.position(name.as_ref()) {
+            Some(index) => {
+                Some(std::mem::replace(&mut self.tags[index].1, value))
+            }
+            None => {
+                self.tags.push((name, value));
+                None
+            }
+        }
+    }
+
+    /// Return the value of the tag with the given name, if present.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.position(name).map(|index| &self.tags[index].1)
+    }
+
+    /// Remove the tag with the given name, returning its value
+    /// if it was present.
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.position(name).map(|index| self.tags.remove(index).1)
+    }
+
+    /// Return true if this `Dict` contains a marker tag with the given name.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Dict, TagName, Value};
+    /// let mut dict = Dict::new();
+    /// dict.insert(TagName::new("equip".to_owned()).unwrap(), Value::Marker);
+    /// assert!(dict.has_marker("equip"));
+    /// ```
+    pub fn has_marker(&self, name: &str) -> bool {
+        matches!(self.get(name), Some(Value::Marker))
+    }
+
+    /// Return the bool value of the tag with the given name, if present
+    /// and a bool.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get(name).and_then(|value| value.as_bool())
+    }
+
+    /// Return the number value of the tag with the given name, if present
+    /// and a number.
+    pub fn get_number(&self, name: &str) -> Option<&Number> {
+        self.get(name).and_then(|value| value.as_number())
+    }
+
+    /// Return the string value of the tag with the given name, if present
+    /// and a string.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|value| value.as_str())
+    }
+
+    /// Return the uri value of the tag with the given name, if present
+    /// and a uri.
+    pub fn get_uri(&self, name: &str) -> Option<&Uri> {
+        self.get(name).and_then(|value| value.as_uri())
+    }
+
+    /// Return the ref value of the tag with the given name, if present
+    /// and a ref.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Dict, Ref, TagName};
+    /// let site_ref = Ref::new("@site".to_owned()).unwrap();
+    /// let mut dict = Dict::new();
+    /// dict.insert(TagName::new("siteRef".to_owned()).unwrap(), site_ref.clone().into());
+    /// assert_eq!(dict.get_ref("siteRef"), Some(&site_ref));
+    /// ```
+    pub fn get_ref(&self, name: &str) -> Option<&Ref> {
+        self.get(name).and_then(|value| value.as_ref())
+    }
+
+    /// Return the symbol value of the tag with the given name, if present
+    /// and a symbol.
+    pub fn get_symbol(&self, name: &str) -> Option<&Symbol> {
+        self.get(name).and_then(|value| value.as_symbol())
+    }
+
+    /// Return the date value of the tag with the given name, if present
+    /// and a date.
+    pub fn get_date(&self, name: &str) -> Option<&Date> {
+        self.get(name).and_then(|value| value.as_date())
+    }
+
+    /// Return the time value of the tag with the given name, if present
+    /// and a time.
+    pub fn get_time(&self, name: &str) -> Option<&Time> {
+        self.get(name).and_then(|value| value.as_time())
+    }
+
+    /// Return the date time value of the tag with the given name, if present
+    /// and a date time.
+    pub fn get_date_time(&self, name: &str) -> Option<&DateTime> {
+        self.get(name).and_then(|value| value.as_date_time())
+    }
+
+    /// Return the coord value of the tag with the given name, if present
+    /// and a coord.
+    pub fn get_coord(&self, name: &str) -> Option<&Coord> {
+        self.get(name).and_then(|value| value.as_coord())
+    }
+
+    /// Return the xstr value of the tag with the given name, if present
+    /// and an xstr.
+    pub fn get_xstr(&self, name: &str) -> Option<&Xstr> {
+        self.get(name).and_then(|value| value.as_xstr())
+    }
+
+    /// Return the bin value of the tag with the given name, if present
+    /// and a bin.
+    pub fn get_bin(&self, name: &str) -> Option<&Bin> {
+        self.get(name).and_then(|value| value.as_bin())
+    }
+
+    /// Return the list value of the tag with the given name, if present
+    /// and a list.
+    pub fn get_list(&self, name: &str) -> Option<&[Value]> {
+        self.get(name).and_then(|value| value.as_list())
+    }
+
+    /// Return the dict value of the tag with the given name, if present
+    /// and a dict.
+    pub fn get_dict(&self, name: &str) -> Option<&Dict> {
+        self.get(name).and_then(|value| value.as_dict())
+    }
+
+    /// Return the grid value of the tag with the given name, if present
+    /// and a grid.
+    pub fn get_grid(&self, name: &str) -> Option<&Grid> {
+        self.get(name).and_then(|value| value.as_grid())
+    }
+
+    /// Apply a diff to this `Dict`. Each tag in the diff is inserted into
+    /// this `Dict`, replacing any existing value, except for tags whose
+    /// value is a remove marker, which are removed from this `Dict`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Dict, TagName, Value};
+    /// let equip = TagName::new("equip".to_owned()).unwrap();
+    /// let ahu = TagName::new("ahu".to_owned()).unwrap();
+    ///
+    /// let mut base = Dict::new();
+    /// base.insert(equip.clone(), Value::Marker);
+    ///
+    /// let mut diff = Dict::new();
+    /// diff.insert(equip, Value::RemoveMarker);
+    /// diff.insert(ahu, Value::Marker);
+    ///
+    /// base.merge(diff);
+    /// assert!(!base.contains("equip"));
+    /// assert!(base.has_marker("ahu"));
+    /// ```
+    pub fn merge(&mut self, diff: Dict) {
+        for (name, value) in diff {
+            if value.is_remove_marker() {
+                self.remove(name.as_ref());
+            } else {
+                self.insert(name, value);
+            }
+        }
+    }
+
+    /// Return true if this `Dict` contains a tag with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Return the number of tags in this `Dict`.
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Return true if this `Dict` contains no tags.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Return an iterator over the tag names and values in this `Dict`.
+    pub fn iter(&self) -> impl Iterator<Item = (&TagName, &Value)> {
+        self.tags.iter().map(|(name, value)| (name, value))
+    }
+
+    /// Return an iterator over the tag names in this `Dict`.
+    pub fn tag_names(&self) -> impl Iterator<Item = &TagName> {
+        self.tags.iter().map(|(name, _)| name)
+    }
+
+    /// Return this `Dict` as an Axon dict literal. Marker tags are written
+    /// with just their name, like `{site, dis: "Site"}`.
+    pub fn to_axon_code(&self) -> String {
+        let tags: Vec<String> = self
+            .iter()
+            .map(|(name, value)| match value {
+                Value::Marker => name.to_string(),
+                value => format!("{}: {}", name, value.to_axon_code()),
+            })
+            .collect();
+        format!("{{{}}}", tags.join(", "))
+    }
+
+    /// Rename a tag, keeping its position in this `Dict`. Returns false if
+    /// there was no tag with the old name. Any existing tag with the new
+    /// name is removed.
+    pub(crate) fn rename(&mut self, old_name: &str, new_name: TagName) -> bool {
+        if !self.contains(old_name) {
+            return false;
+        }
+        let new_name_str: &str = new_name.as_ref();
+        if new_name_str != old_name {
+            self.remove(new_name_str);
+        }
+        if let Some(index) = self.position(old_name) {
+            self.tags[index].0 = new_name;
+        }
+        true
+    }
+
+    /// Return the tags in this `Dict`, sorted by tag name.
+    pub(crate) fn sorted_tags(&self) -> Vec<(&TagName, &Value)> {
+        let mut tags: Vec<_> = self.iter().collect();
+        tags.sort_by_key(|(name, _)| *name);
+        tags
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.tags.iter().position(|(tag_name, _)| {
+            let tag_name: &str = tag_name.as_ref();
+            tag_name == name
+        })
+    }
+}
+
+/// Haystack dicts are unordered, so tag order is ignored when comparing
+/// dicts. This matches `Hash` and the Haystack sort order, which both use
+/// the tags in tag name order.
+impl std::cmp::PartialEq for Dict {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(name, value)| other.get(name.as_ref()) == Some(value))
+    }
+}
+
+impl std::cmp::Eq for Dict {}
+
+impl std::hash::Hash for Dict {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        // Hash the tags in name order, so equal dicts with different tag
+        // orders have the same hash:
+        self.sorted_tags().hash(state);
+    }
+}
+
+impl std::iter::FromIterator<(TagName, Value)> for Dict {
+    fn from_iter<I: IntoIterator<Item = (TagName, Value)>>(iter: I) -> Self {
+        let mut dict = Dict::new();
+        for (name, value) in iter {
+            dict.insert(name, value);
+        }
+        dict
+    }
+}
+
+impl std::iter::IntoIterator for Dict {
+    type Item = (TagName, Value);
+    type IntoIter = std::vec::IntoIter<(TagName, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Dict;
+    use crate::tag::tn;
+    use crate::{Number, Value};
+
+    #[test]
+    fn insertion_order_is_preserved() {
+        let mut dict = Dict::new();
+        dict.insert(tn("site"), Value::Marker);
+        dict.insert(tn("dis"), "Site 1".into());
+        dict.insert(tn("area"), Number::new_unitless(1.0).into());
+        dict.insert(tn("dis"), "Site 2".into());
+
+        let names: Vec<&str> =
+            dict.tag_names().map(|name| name.as_ref()).collect();
+        assert_eq!(names, vec!["site", "dis", "area"]);
+        assert_eq!(dict.get_str("dis"), Some("Site 2"));
+    }
+
+    #[test]
+    fn typed_getters_check_kind() {
+        let mut dict = Dict::new();
+        dict.insert(
+            tn("area"),
+            Number::new(10.0, Some("ft²".to_owned())).into(),
+        );
+        dict.insert(tn("equip"), Value::Marker);
+
+        assert_eq!(dict.get_number("area").unwrap().unit(), Some("ft²"));
+        assert!(dict.get_str("area").is_none());
+        assert!(dict.has_marker("equip"));
+        assert!(!dict.has_marker("area"));
+        assert!(!dict.has_marker("missing"));
+    }
+
+    #[test]
+    fn merge_honours_remove_marker() {
+        let mut base = Dict::new();
+        base.insert(tn("site"), Value::Marker);
+        base.insert(tn("dis"), "Old".into());
+        base.insert(tn("area"), Number::new_unitless(1.0).into());
+
+        let mut diff = Dict::new();
+        diff.insert(tn("dis"), "New".into());
+        diff.insert(tn("area"), Value::RemoveMarker);
+        diff.insert(tn("geoCity"), "Sydney".into());
+        diff.insert(tn("missing"), Value::RemoveMarker);
+
+        base.merge(diff);
+
+        let names: Vec<&str> =
+            base.tag_names().map(|name| name.as_ref()).collect();
+        assert_eq!(names, vec!["site", "dis", "geoCity"]);
+        assert_eq!(base.get_str("dis"), Some("New"));
+    }
+
+    #[test]
+    fn equality_ignores_order() {
+        let mut a = Dict::new();
+        a.insert(tn("site"), Value::Marker);
+        a.insert(tn("dis"), "A".into());
+        let mut b = Dict::new();
+        b.insert(tn("dis"), "A".into());
+        b.insert(tn("site"), Value::Marker);
+        assert_eq!(a, b);
+
+        b.insert(tn("dis"), "B".into());
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,727 @@
+use crate::tokenizer::{Token, TokenError, Tokenizer};
+use crate::{Dict, Position, Ref, Symbol, TagName, Value, Zinc};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A Haystack filter, used to query for records, like
+/// `site and area > 1000ft²`.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::Filter;
+/// let filter: Filter = "equip and siteRef->geoCity == \"Sydney\"".parse().unwrap();
+/// assert_eq!(filter.to_string(), "equip and siteRef->geoCity == \"Sydney\"");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    /// Matches records which have a value at the path.
+    Has(Path),
+    /// Matches records which do not have a value at the path.
+    Missing(Path),
+    /// Matches records whose value at the path compares to the literal value
+    /// using the operator.
+    Cmp {
+        path: Path,
+        op: CmpOp,
+        value: Value,
+    },
+    /// Matches records which implement the symbol's definition.
+    IsA(Symbol),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+impl Filter {
+    /// Return a filter which matches records matching both this filter and
+    /// the other filter.
+    pub fn and(self, other: Filter) -> Self {
+        Self::And(Box::new(self), Box::new(other))
+    }
+
+    /// Return a filter which matches records matching either this filter or
+    /// the other filter.
+    pub fn or(self, other: Filter) -> Self {
+        Self::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Return true if the record matches this filter. Refs in paths like
+    /// `equipRef->siteRef` are dereferenced using the resolver.
+    ///
+    /// Comparisons only match records which have a value at the path, and
+    /// values of different kinds are never equal. Numbers are only
+    /// ordered if they have the same unit.
+    ///
+    /// Symbol terms like `^steam-boiler` are evaluated without a defs
+    /// namespace, and match records which have a marker tag for each part of
+    /// the symbol's name (`steam` and `boiler`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Dict, Filter, Ref, TagName, Value};
+    /// let site_id = Ref::new("@site".to_owned()).unwrap();
+    /// let mut site = Dict::new();
+    /// site.insert(TagName::new("id".to_owned()).unwrap(), site_id.clone().into());
+    /// site.insert(TagName::new("geoCity".to_owned()).unwrap(), "Sydney".into());
+    /// let mut equip = Dict::new();
+    /// equip.insert(TagName::new("equip".to_owned()).unwrap(), Value::Marker);
+    /// equip.insert(TagName::new("siteRef".to_owned()).unwrap(), site_id.into());
+    /// let records = vec![site, equip];
+    ///
+    /// let filter: Filter = "equip and siteRef->geoCity == \"Sydney\"".parse().unwrap();
+    /// assert!(filter.matches(&records[1], records.as_slice()));
+    /// ```
+    pub fn matches<R>(&self, record: &Dict, resolver: &R) -> bool
+    where
+        R: RefResolver + ?Sized,
+    {
+        match self {
+            Self::Has(path) => path.resolve(record, resolver).is_some(),
+            Self::Missing(path) => path.resolve(record, resolver).is_none(),
+            Self::Cmp { path, op, value } => {
+                match path.resolve(record, resolver) {
+                    Some(actual) => compare(actual, *op, value),
+                    None => false,
+                }
+            }
+            Self::IsA(symbol) => {
+                let name = symbol.as_ref().trim_start_matches('^');
+                !name.contains(':')
+                    && name.split('-').all(|part| record.has_marker(part))
+            }
+            Self::And(left, right) => {
+                left.matches(record, resolver)
+                    && right.matches(record, resolver)
+            }
+            Self::Or(left, right) => {
+                left.matches(record, resolver)
+                    || right.matches(record, resolver)
+            }
+        }
+    }
+
+    /// Return the records which match this filter, using the records
+    /// themselves to dereference refs in paths.
+    pub fn select<'a>(&self, records: &'a [Dict]) -> Vec<&'a Dict> {
+        records
+            .iter()
+            .filter(|record| self.matches(record, records))
+            .collect()
+    }
+
+    /// Return true if the value can be used as a literal in a filter.
+    pub fn is_literal(value: &Value) -> bool {
+        matches!(
+            value,
+            Value::Bool(_)
+                | Value::Number(_)
+                | Value::Str(_)
+                | Value::Uri(_)
+                | Value::Ref(_)
+                | Value::Symbol(_)
+                | Value::Date(_)
+                | Value::Time(_)
+        )
+    }
+
+    fn fmt_operand(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        parent: &Filter,
+        is_right: bool,
+    ) -> std::fmt::Result {
+        let needs_parens = match (parent, self) {
+            (Self::And(_, _), Self::Or(_, _)) => true,
+            (Self::And(_, _), Self::And(_, _)) => is_right,
+            (Self::Or(_, _), Self::Or(_, _)) => is_right,
+            _ => false,
+        };
+        if needs_parens {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Has(path) => write!(f, "{}", path),
+            Self::Missing(path) => write!(f, "not {}", path),
+            Self::Cmp { path, op, value } => {
+                write!(f, "{} {} {}", path, op, literal_to_string(value))
+            }
+            Self::IsA(symbol) => write!(f, "{}", symbol),
+            Self::And(left, right) => {
+                left.fmt_operand(f, self, false)?;
+                write!(f, " and ")?;
+                right.fmt_operand(f, self, true)
+            }
+            Self::Or(left, right) => {
+                left.fmt_operand(f, self, false)?;
+                write!(f, " or ")?;
+                right.fmt_operand(f, self, true)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let filter = parser.parse_or()?;
+        if parser.cur != Token::Eof {
+            return parser.error(format!("Unexpected {}", parser.cur));
+        }
+        Ok(filter)
+    }
+}
+
+/// Return the filter literal for a value.
+fn literal_to_string(value: &Value) -> String {
+    match value {
+        Value::Bool(true) => "true".to_owned(),
+        Value::Bool(false) => "false".to_owned(),
+        // Display strings are not part of filter ref literals.
+        Value::Ref(hsref) => hsref.to_axon_code().to_owned(),
+        value => value.to_zinc(),
+    }
+}
+
+/// A path of tag names in a filter, like `equipRef->siteRef->dis`. Each tag
+/// name after the first is looked up in the record referenced by the
+/// previous tag.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Path(Vec<TagName>);
+
+impl Path {
+    /// Create a new `Path`, returning `None` if there are no tag names.
+    pub fn new(names: Vec<TagName>) -> Option<Self> {
+        if names.is_empty() {
+            None
+        } else {
+            Some(Self(names))
+        }
+    }
+
+    /// Return the tag names in this path.
+    pub fn names(&self) -> &[TagName] {
+        &self.0
+    }
+}
+
+impl Path {
+    /// Return the value at the end of this path, dereferencing refs using
+    /// the resolver. Null values are treated as missing.
+    fn resolve<'a, R>(
+        &self,
+        record: &'a Dict,
+        resolver: &'a R,
+    ) -> Option<&'a Value>
+    where
+        R: RefResolver + ?Sized,
+    {
+        let mut names = self.0.iter();
+        let first = names.next()?;
+        let mut value = record.get(first.as_ref())?;
+        for name in names {
+            let hsref = value.as_ref()?;
+            value = resolver.resolve(hsref)?.get(name.as_ref())?;
+        }
+        if value.is_null() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+impl std::convert::From<TagName> for Path {
+    fn from(name: TagName) -> Self {
+        Self(vec![name])
+    }
+}
+
+impl std::fmt::Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> =
+            self.0.iter().map(|name| name.as_ref()).collect();
+        write!(f, "{}", names.join("->"))
+    }
+}
+
+/// Finds the record referred to by a ref, so filter paths like
+/// `equipRef->siteRef` can be evaluated.
+pub trait RefResolver {
+    /// Return the record whose `id` tag is the given ref, if there is one.
+    fn resolve(&self, hsref: &Ref) -> Option<&Dict>;
+}
+
+impl RefResolver for [Dict] {
+    fn resolve(&self, hsref: &Ref) -> Option<&Dict> {
+        self.iter()
+            .find(|record| record.get_ref("id") == Some(hsref))
+    }
+}
+
+impl RefResolver for Vec<Dict> {
+    fn resolve(&self, hsref: &Ref) -> Option<&Dict> {
+        self.as_slice().resolve(hsref)
+    }
+}
+
+impl RefResolver for HashMap<Ref, Dict> {
+    fn resolve(&self, hsref: &Ref) -> Option<&Dict> {
+        self.get(hsref)
+    }
+}
+
+/// Return true if the actual value compares to the expected value using the
+/// operator.
+fn compare(actual: &Value, op: CmpOp, expected: &Value) -> bool {
+    let ordering = compare_values(actual, expected);
+    match op {
+        CmpOp::Eq => is_equal(actual, expected, ordering),
+        CmpOp::NotEq => !is_equal(actual, expected, ordering),
+        CmpOp::Lt => ordering == Some(Ordering::Less),
+        CmpOp::LtEq => {
+            matches!(ordering, Some(Ordering::Less) | Some(Ordering::Equal))
+        }
+        CmpOp::Gt => ordering == Some(Ordering::Greater),
+        CmpOp::GtEq => {
+            matches!(ordering, Some(Ordering::Greater) | Some(Ordering::Equal))
+        }
+    }
+}
+
+fn is_equal(
+    actual: &Value,
+    expected: &Value,
+    ordering: Option<Ordering>,
+) -> bool {
+    match ordering {
+        Some(ordering) => ordering == Ordering::Equal,
+        None => actual == expected,
+    }
+}
+
+/// Return the ordering of two values of the same kind, or `None` if the
+/// values cannot be ordered.
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) if a.unit() == b.unit() => {
+            a.to_f64().partial_cmp(&b.to_f64())
+        }
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        (Value::Uri(a), Value::Uri(b)) => Some(a.as_ref().cmp(b.as_ref())),
+        (Value::Ref(a), Value::Ref(b)) => Some(a.as_ref().cmp(b.as_ref())),
+        (Value::Symbol(a), Value::Symbol(b)) => {
+            Some(a.as_ref().cmp(b.as_ref()))
+        }
+        (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+        (Value::Time(a), Value::Time(b)) => Some(a.cmp(b)),
+        (Value::DateTime(a), Value::DateTime(b)) => {
+            Some(a.date_time().cmp(b.date_time()))
+        }
+        _ => None,
+    }
+}
+
+/// A comparison operator in a filter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CmpOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CmpOp {
+    fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::Eq => Some(Self::Eq),
+            Token::NotEq => Some(Self::NotEq),
+            Token::Lt => Some(Self::Lt),
+            Token::LtEq => Some(Self::LtEq),
+            Token::Gt => Some(Self::Gt),
+            Token::GtEq => Some(Self::GtEq),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CmpOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Eq => "==",
+            Self::NotEq => "!=",
+            Self::Lt => "<",
+            Self::LtEq => "<=",
+            Self::Gt => ">",
+            Self::GtEq => ">=",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// An error indicating that a `Filter` could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse filter at line {line}, column {column}: {message}")]
+pub struct ParseFilterError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseFilterError {
+    fn new(position: Position, message: String) -> Self {
+        Self {
+            line: position.line,
+            column: position.column,
+            message,
+        }
+    }
+
+    /// Return the line at which the error occurred, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Return the column at which the error occurred, starting from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Return a description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::convert::From<TokenError> for ParseFilterError {
+    fn from(err: TokenError) -> Self {
+        Self::new(err.position, err.message)
+    }
+}
+
+struct Parser<'a> {
+    tokenizer: Tokenizer<&'a [u8]>,
+    cur: Token,
+    cur_position: Position,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Result<Self, ParseFilterError> {
+        let mut parser = Self {
+            tokenizer: Tokenizer::new(s.as_bytes()),
+            cur: Token::Eof,
+            cur_position: Position { line: 1, column: 1 },
+        };
+        parser.advance()?;
+        Ok(parser)
+    }
+
+    /// Move to the next token, treating newlines as whitespace.
+    fn advance(&mut self) -> Result<(), ParseFilterError> {
+        loop {
+            let (token, position) = self.tokenizer.next_token()?;
+            if token != Token::Nl {
+                self.cur = token;
+                self.cur_position = position;
+                return Ok(());
+            }
+        }
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseFilterError> {
+        Err(ParseFilterError::new(self.cur_position, message))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.cur, Token::Id(id) if id == keyword)
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, ParseFilterError> {
+        let mut filter = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.advance()?;
+            filter = filter.or(self.parse_and()?);
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, ParseFilterError> {
+        let mut filter = self.parse_term()?;
+        while self.is_keyword("and") {
+            self.advance()?;
+            filter = filter.and(self.parse_term()?);
+        }
+        Ok(filter)
+    }
+
+    fn parse_term(&mut self) -> Result<Filter, ParseFilterError> {
+        match &self.cur {
+            Token::LParen => {
+                self.advance()?;
+                let filter = self.parse_or()?;
+                if self.cur != Token::RParen {
+                    return self
+                        .error(format!("Expected ')' but found {}", self.cur));
+                }
+                self.advance()?;
+                Ok(filter)
+            }
+            Token::Val(Value::Symbol(symbol)) => {
+                let symbol = symbol.clone();
+                self.advance()?;
+                Ok(Filter::IsA(symbol))
+            }
+            Token::Id(id) if id == "not" => {
+                self.advance()?;
+                Ok(Filter::Missing(self.parse_path()?))
+            }
+            Token::Id(_) => {
+                let path = self.parse_path()?;
+                match CmpOp::from_token(&self.cur) {
+                    Some(op) => {
+                        self.advance()?;
+                        let value = self.parse_literal()?;
+                        Ok(Filter::Cmp { path, op, value })
+                    }
+                    None => Ok(Filter::Has(path)),
+                }
+            }
+            token => {
+                self.error(format!("Expected a filter but found {}", token))
+            }
+        }
+    }
+
+    fn parse_path(&mut self) -> Result<Path, ParseFilterError> {
+        let mut names = vec![self.parse_tag_name()?];
+        while self.cur == Token::Arrow {
+            self.advance()?;
+            names.push(self.parse_tag_name()?);
+        }
+        Ok(Path(names))
+    }
+
+    fn parse_tag_name(&mut self) -> Result<TagName, ParseFilterError> {
+        let name = match &self.cur {
+            Token::Id(id) if is_keyword(id) => {
+                return self.error(format!("Unexpected keyword '{}'", id))
+            }
+            Token::Id(id) => match TagName::new(id.clone()) {
+                Some(name) => name,
+                None => {
+                    return self.error(format!("Invalid tag name '{}'", id))
+                }
+            },
+            token => {
+                return self
+                    .error(format!("Expected a tag name but found {}", token))
+            }
+        };
+        self.advance()?;
+        Ok(name)
+    }
+
+    fn parse_literal(&mut self) -> Result<Value, ParseFilterError> {
+        let value = match &self.cur {
+            Token::Id(id) if id == "true" => Value::Bool(true),
+            Token::Id(id) if id == "false" => Value::Bool(false),
+            Token::Val(value) if Filter::is_literal(value) => value.clone(),
+            token => {
+                return self
+                    .error(format!("Expected a literal but found {}", token))
+            }
+        };
+        self.advance()?;
+        Ok(value)
+    }
+}
+
+fn is_keyword(id: &str) -> bool {
+    id == "and" || id == "or" || id == "not"
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CmpOp, Filter, Path};
+    use crate::tag::tn;
+    use crate::{Date, Dict, Number, Ref, Value};
+    use std::collections::HashMap;
+
+    #[test]
+    fn parse_works() {
+        let filter: Filter =
+            "site and area > 1000ft² and equipRef->siteRef == @x"
+                .parse()
+                .unwrap();
+        let site = Filter::Has(tn("site").into());
+        let area = Filter::Cmp {
+            path: tn("area").into(),
+            op: CmpOp::Gt,
+            value: Number::new(1000.0, Some("ft²".to_owned())).into(),
+        };
+        let site_ref = Filter::Cmp {
+            path: Path::new(vec![tn("equipRef"), tn("siteRef")]).unwrap(),
+            op: CmpOp::Eq,
+            value: Ref::new("@x".to_owned()).unwrap().into(),
+        };
+        assert_eq!(filter, site.and(area).and(site_ref));
+    }
+
+    #[test]
+    fn precedence_works() {
+        let filter: Filter = "a or b and not c".parse().unwrap();
+        let expected = Filter::Has(tn("a").into())
+            .or(Filter::Has(tn("b").into())
+                .and(Filter::Missing(tn("c").into())));
+        assert_eq!(filter, expected);
+
+        let filter: Filter = "(a or b) and ^steam-boiler".parse().unwrap();
+        assert_eq!(filter.to_string(), "(a or b) and ^steam-boiler");
+    }
+
+    #[test]
+    fn print_round_trip_works() {
+        let filters = vec![
+            "site",
+            "not equip",
+            "dis == \"A \\\"quoted\\\" name\"",
+            "curVal >= -1.5°C or enabled != false",
+            "a and (b and c)",
+            "a or (b or c)",
+            "uri == `http://a.com/` and kind < ^steam",
+            "installed >= 2020-01-31 and occupiedStart < 08:30:00",
+        ];
+        for s in filters {
+            let filter: Filter = s.parse().unwrap();
+            assert_eq!(filter.to_string(), s);
+            assert_eq!(filter.to_string().parse::<Filter>().unwrap(), filter);
+        }
+
+        let filter = Filter::Cmp {
+            path: tn("id").into(),
+            op: CmpOp::Eq,
+            value: Ref::new_with_dis("@x".to_owned(), "X".to_owned())
+                .unwrap()
+                .into(),
+        };
+        assert_eq!(filter.to_string(), "id == @x");
+        assert!(!Filter::is_literal(&Value::Marker));
+    }
+
+    fn records() -> Vec<Dict> {
+        let site_id = Ref::new("@site".to_owned()).unwrap();
+        let mut site = Dict::new();
+        site.insert(tn("id"), site_id.clone().into());
+        site.insert(tn("site"), Value::Marker);
+        site.insert(tn("dis"), "Site".into());
+        site.insert(
+            tn("area"),
+            Number::new(1500.0, Some("ft²".to_owned())).into(),
+        );
+        site.insert(tn("installed"), Date::new(2019, 6, 1).unwrap().into());
+
+        let mut equip = Dict::new();
+        equip.insert(tn("id"), Ref::new("@ahu".to_owned()).unwrap().into());
+        equip.insert(tn("equip"), Value::Marker);
+        equip.insert(tn("ahu"), Value::Marker);
+        equip.insert(tn("dis"), "AHU".into());
+        equip.insert(tn("siteRef"), site_id.into());
+
+        let mut point = Dict::new();
+        point.insert(tn("id"), Ref::new("@temp".to_owned()).unwrap().into());
+        point.insert(tn("point"), Value::Marker);
+        point.insert(
+            tn("equipRef"),
+            Ref::new("@ahu".to_owned()).unwrap().into(),
+        );
+        point.insert(
+            tn("curVal"),
+            Number::new_scientific(7.25, 1, Some("°F".to_owned()))
+                .unwrap()
+                .into(),
+        );
+        point.insert(tn("missing"), Value::Null);
+
+        vec![site, equip, point]
+    }
+
+    fn select(filter: &str) -> Vec<String> {
+        let records = records();
+        let filter: Filter = filter.parse().unwrap();
+        filter
+            .select(&records)
+            .iter()
+            .map(|record| record.get_str("dis").unwrap_or("point").to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn matches_works() {
+        assert_eq!(select("site or equip"), vec!["Site", "AHU"]);
+        assert_eq!(select("not site"), vec!["AHU", "point"]);
+        assert_eq!(select("area > 1000ft²"), vec!["Site"]);
+        assert!(select("area > 1000m²").is_empty());
+        assert_eq!(select("curVal == 72.5°F"), vec!["point"]);
+        assert_eq!(
+            select("curVal <= 72.5°F and curVal >= 72.5°F"),
+            vec!["point"]
+        );
+        assert_eq!(select("dis < \"B\""), vec!["AHU"]);
+        assert_eq!(select("dis != \"AHU\""), vec!["Site"]);
+        assert_eq!(select("dis == 1"), Vec::<String>::new());
+        assert!(select("missing").is_empty());
+        assert_eq!(select("^ahu"), vec!["AHU"]);
+        assert_eq!(select("installed < 2020-01-01"), vec!["Site"]);
+        assert!(select("installed == 2019-06-02").is_empty());
+    }
+
+    #[test]
+    fn paths_are_dereferenced() {
+        assert_eq!(select("siteRef->site"), vec!["AHU"]);
+        assert_eq!(select("equipRef->siteRef->dis == \"Site\""), vec!["point"]);
+        assert_eq!(select("siteRef == @site"), vec!["AHU"]);
+        assert!(select("dis->site").is_empty());
+
+        let records = records();
+        let by_id: HashMap<Ref, Dict> = records
+            .iter()
+            .map(|record| {
+                (record.get_ref("id").unwrap().clone(), record.clone())
+            })
+            .collect();
+        let filter: Filter = "equipRef->ahu".parse().unwrap();
+        assert!(filter.matches(&records[2], &by_id));
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        let err = "site and".parse::<Filter>().unwrap_err();
+        assert_eq!(err.column(), 9);
+
+        let err = "site and (equip or".parse::<Filter>().unwrap_err();
+        assert_eq!(err.column(), 19);
+
+        let err = "area > site".parse::<Filter>().unwrap_err();
+        assert_eq!(err.column(), 8);
+
+        let err = "site equip".parse::<Filter>().unwrap_err();
+        assert_eq!(err.column(), 6);
+
+        let err = "siteRef->".parse::<Filter>().unwrap_err();
+        assert_eq!(err.column(), 10);
+
+        let err = "Site".parse::<Filter>().unwrap_err();
+        assert_eq!(err.column(), 1);
+    }
+}
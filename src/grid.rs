@@ -0,0 +1,416 @@
+use crate::zinc::zinc_str;
+use crate::{is_tag_name, Dict, TagName, Value};
+use thiserror::Error;
+
+/// A Haystack Grid, a two-dimensional table of values with grid-level
+/// metadata and named columns. Each row is a `Dict` whose tag names
+/// are column names.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Grid {
+    meta: Dict,
+    columns: Vec<Column>,
+    rows: Vec<Dict>,
+}
+
+impl Grid {
+    /// Create a new `Grid`. Rows should only contain tags whose names
+    /// are the names of columns in this grid.
+    pub fn new(meta: Dict, columns: Vec<Column>, rows: Vec<Dict>) -> Self {
+        Self {
+            meta,
+            columns,
+            rows,
+        }
+    }
+
+    /// Create a new `Grid` with no metadata, columns or rows.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Create a new `Grid` from rows, with one column for each distinct tag
+    /// name found in the rows. Columns are ordered by first appearance.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Dict, Grid, TagName, Value};
+    /// let mut row = Dict::new();
+    /// row.insert(TagName::new("site".to_owned()).unwrap(), Value::Marker);
+    /// let grid = Grid::from_rows(vec![row]);
+    /// assert!(grid.has_column("site"));
+    /// ```
+    pub fn from_rows(rows: Vec<Dict>) -> Self {
+        let mut columns: Vec<Column> = Vec::new();
+        for row in &rows {
+            for name in row.tag_names() {
+                let name_str: &str = name.as_ref();
+                if !columns.iter().any(|col| col.name_str() == name_str) {
+                    columns.push(Column::new(name.clone(), Dict::new()));
+                }
+            }
+        }
+        Self::new(Dict::new(), columns, rows)
+    }
+
+    /// Return the grid-level metadata.
+    pub fn meta(&self) -> &Dict {
+        &self.meta
+    }
+
+    /// Return a mutable reference to the grid-level metadata.
+    pub fn meta_mut(&mut self) -> &mut Dict {
+        &mut self.meta
+    }
+
+    /// Return the columns of this grid.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Return an iterator over the names of the columns in this grid.
+    pub fn column_names(&self) -> impl Iterator<Item = &TagName> {
+        self.columns.iter().map(|col| col.name())
+    }
+
+    /// Return the column with the given name, if present.
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|col| col.name_str() == name)
+    }
+
+    /// Return a mutable reference to the metadata of the column with the
+    /// given name, if present.
+    pub fn column_meta_mut(&mut self, name: &str) -> Option<&mut Dict> {
+        self.columns
+            .iter_mut()
+            .find(|col| col.name_str() == name)
+            .map(|col| &mut col.meta)
+    }
+
+    /// Return true if this grid has a column with the given name.
+    pub fn has_column(&self, name: &str) -> bool {
+        self.column(name).is_some()
+    }
+
+    /// Return an iterator over the values in the column with the given name,
+    /// one item per row. Rows without a value for the column yield `None`.
+    pub fn column_values<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = Option<&'a Value>> + 'a {
+        self.rows.iter().map(move |row| row.get(name))
+    }
+
+    /// Add a new column to the end of this grid. The name must be a valid
+    /// tag name which is not already used by another column.
+    pub fn add_column(
+        &mut self,
+        name: &str,
+        meta: Dict,
+    ) -> Result<(), GridError> {
+        let name = Self::validate_new_column_name(&self.columns, name)?;
+        self.columns.push(Column::new(name, meta));
+        Ok(())
+    }
+
+    /// Remove the column with the given name, also removing its values
+    /// from every row. Returns the removed column, if it was present.
+    pub fn remove_column(&mut self, name: &str) -> Option<Column> {
+        let index =
+            self.columns.iter().position(|col| col.name_str() == name)?;
+        for row in &mut self.rows {
+            row.remove(name);
+        }
+        Some(self.columns.remove(index))
+    }
+
+    /// Rename a column, keeping its metadata and position, and renaming the
+    /// corresponding tag in every row.
+    pub fn rename_column(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), GridError> {
+        let index = self
+            .columns
+            .iter()
+            .position(|col| col.name_str() == old_name)
+            .ok_or_else(|| GridError::MissingColumn(old_name.to_owned()))?;
+
+        if old_name == new_name {
+            return Ok(());
+        }
+
+        let new_name = Self::validate_new_column_name(&self.columns, new_name)?;
+        for row in &mut self.rows {
+            row.rename(old_name, new_name.clone());
+        }
+        self.columns[index].name = new_name;
+        Ok(())
+    }
+
+    /// Return the rows of this grid.
+    pub fn rows(&self) -> &[Dict] {
+        &self.rows
+    }
+
+    /// Return the row at the given index, if present.
+    pub fn row(&self, index: usize) -> Option<&Dict> {
+        self.rows.get(index)
+    }
+
+    /// Append a row to this grid. Every tag in the row must correspond
+    /// to a column in this grid.
+    pub fn add_row(&mut self, row: Dict) -> Result<(), GridError> {
+        for name in row.tag_names() {
+            let name: &str = name.as_ref();
+            if !self.has_column(name) {
+                return Err(GridError::MissingColumn(name.to_owned()));
+            }
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    /// Sort the rows of this grid by the values in a column, using
+    /// Haystack's sort order (see `Value::haystack_cmp`). Missing values
+    /// are sorted as nulls, so they come first. The sort is stable.
+    pub fn sort_by_column(&mut self, name: &str) -> Result<(), GridError> {
+        if !self.has_column(name) {
+            return Err(GridError::MissingColumn(name.to_owned()));
+        }
+        self.rows.sort_by(|a, b| {
+            let a = a.get(name).unwrap_or(&Value::Null);
+            let b = b.get(name).unwrap_or(&Value::Null);
+            a.haystack_cmp(b)
+        });
+        Ok(())
+    }
+
+    /// Return the number of rows in this grid.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Return true if this grid has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Consume this grid, returning its rows.
+    pub fn into_rows(self) -> Vec<Dict> {
+        self.rows
+    }
+
+    /// Return this grid as Axon code. Axon has no grid literal, so the
+    /// rows are converted with `toGrid`, and then any metadata and columns
+    /// which cannot be inferred from the rows are added.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Dict, Grid, TagName, Value};
+    /// let mut row = Dict::new();
+    /// row.insert(TagName::new("site".to_owned()).unwrap(), Value::Marker);
+    /// let grid = Grid::from_rows(vec![row]);
+    /// assert_eq!(grid.to_axon_code(), "[{site}].toGrid");
+    /// ```
+    pub fn to_axon_code(&self) -> String {
+        let rows: Vec<String> =
+            self.rows.iter().map(|row| row.to_axon_code()).collect();
+        let mut code = format!("[{}].toGrid", rows.join(", "));
+        if !self.meta.is_empty() {
+            code.push_str(&format!(".addMeta({})", self.meta.to_axon_code()));
+        }
+
+        // toGrid orders columns by their first appearance in the rows:
+        let mut names: Vec<&TagName> = Vec::new();
+        for name in self.rows.iter().flat_map(|row| row.tag_names()) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        for column in &self.columns {
+            let name = zinc_str(column.name_str());
+            if !names.contains(&column.name()) {
+                code.push_str(&format!(".addCol({}, row => null)", name));
+                names.push(column.name());
+            }
+            if !column.meta().is_empty() {
+                let meta = column.meta().to_axon_code();
+                code.push_str(&format!(".addColMeta({}, {})", name, meta));
+            }
+        }
+        if !names.into_iter().eq(self.column_names()) {
+            let names: Vec<String> = self
+                .column_names()
+                .map(|name| zinc_str(name.as_ref()))
+                .collect();
+            code.push_str(&format!(".reorderCols([{}])", names.join(", ")));
+        }
+        code
+    }
+
+    fn validate_new_column_name(
+        columns: &[Column],
+        name: &str,
+    ) -> Result<TagName, GridError> {
+        if !is_tag_name(name) {
+            return Err(GridError::InvalidColumnName(name.to_owned()));
+        }
+        if columns.iter().any(|col| col.name_str() == name) {
+            return Err(GridError::DuplicateColumn(name.to_owned()));
+        }
+        Ok(TagName::new(name.to_owned()).unwrap())
+    }
+}
+
+/// A column in a Haystack Grid, with a name and column metadata.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Column {
+    name: TagName,
+    meta: Dict,
+}
+
+impl Column {
+    /// Create a new `Column`.
+    pub fn new(name: TagName, meta: Dict) -> Self {
+        Self { name, meta }
+    }
+
+    /// Return the name of this column.
+    pub fn name(&self) -> &TagName {
+        &self.name
+    }
+
+    /// Return the metadata of this column.
+    pub fn meta(&self) -> &Dict {
+        &self.meta
+    }
+
+    /// Return a mutable reference to the metadata of this column.
+    pub fn meta_mut(&mut self) -> &mut Dict {
+        &mut self.meta
+    }
+
+    fn name_str(&self) -> &str {
+        self.name.as_ref()
+    }
+}
+
+/// An error indicating that a `Grid` operation could not be performed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum GridError {
+    #[error("The column name {0} is not a valid tag name")]
+    InvalidColumnName(String),
+    #[error("The grid already has a column named {0}")]
+    DuplicateColumn(String),
+    #[error("The grid has no column named {0}")]
+    MissingColumn(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Grid, GridError};
+    use crate::tag::tn;
+    use crate::{Dict, Number, Value};
+
+    fn grid() -> Grid {
+        let mut row1 = Dict::new();
+        row1.insert(tn("id"), "a".into());
+        row1.insert(tn("site"), Value::Marker);
+        let mut row2 = Dict::new();
+        row2.insert(tn("id"), "b".into());
+        row2.insert(tn("equip"), Value::Marker);
+        Grid::from_rows(vec![row1, row2])
+    }
+
+    #[test]
+    fn from_rows_collects_columns() {
+        let grid = grid();
+        let names: Vec<&str> =
+            grid.column_names().map(|name| name.as_ref()).collect();
+        assert_eq!(names, vec!["id", "site", "equip"]);
+        let sites: Vec<Option<&Value>> = grid.column_values("site").collect();
+        assert_eq!(sites, vec![Some(&Value::Marker), None]);
+    }
+
+    #[test]
+    fn add_column_validates_name() {
+        let mut grid = grid();
+        assert_eq!(
+            grid.add_column("Bad", Dict::new()),
+            Err(GridError::InvalidColumnName("Bad".to_owned()))
+        );
+        assert_eq!(
+            grid.add_column("id", Dict::new()),
+            Err(GridError::DuplicateColumn("id".to_owned()))
+        );
+        grid.add_column("dis", Dict::new()).unwrap();
+        assert!(grid.has_column("dis"));
+    }
+
+    #[test]
+    fn rename_column_keeps_meta_and_rows_in_sync() {
+        let mut grid = grid();
+        grid.column_meta_mut("site")
+            .unwrap()
+            .insert(tn("dis"), "Site".into());
+        grid.rename_column("site", "isSite").unwrap();
+
+        assert!(!grid.has_column("site"));
+        assert_eq!(
+            grid.column("isSite").unwrap().meta().get_str("dis"),
+            Some("Site")
+        );
+        assert!(grid.row(0).unwrap().has_marker("isSite"));
+        assert!(!grid.row(0).unwrap().contains("site"));
+        assert_eq!(
+            grid.rename_column("isSite", "id"),
+            Err(GridError::DuplicateColumn("id".to_owned()))
+        );
+    }
+
+    #[test]
+    fn remove_column_removes_row_values() {
+        let mut grid = grid();
+        let removed = grid.remove_column("equip").unwrap();
+        assert_eq!(removed.name().as_ref() as &str, "equip");
+        assert!(!grid.row(1).unwrap().contains("equip"));
+        assert!(grid.remove_column("equip").is_none());
+    }
+
+    #[test]
+    fn add_row_checks_columns() {
+        let mut grid = grid();
+        let mut row = Dict::new();
+        row.insert(tn("unknown"), Value::Marker);
+        assert_eq!(
+            grid.add_row(row),
+            Err(GridError::MissingColumn("unknown".to_owned()))
+        );
+        assert_eq!(grid.len(), 2);
+    }
+
+    #[test]
+    fn sort_by_column_works() {
+        let mut grid = grid();
+        let mut row = Dict::new();
+        row.insert(tn("id"), Number::new_unitless(1.0).into());
+        grid.add_row(row).unwrap();
+        grid.add_row(Dict::new()).unwrap();
+        grid.sort_by_column("id").unwrap();
+        let ids: Vec<Option<&Value>> = grid.column_values("id").collect();
+        assert_eq!(
+            ids,
+            vec![
+                None,
+                Some(&Number::new_unitless(1.0).into()),
+                Some(&"a".into()),
+                Some(&"b".into()),
+            ]
+        );
+        assert_eq!(
+            grid.sort_by_column("missing"),
+            Err(GridError::MissingColumn("missing".to_owned()))
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{Coord, Marker, Na, Number, Ref, RemoveMarker, Symbol, Uri, Xstr};
+use crate::number::parse_json_numeric;
+use crate::{
+    Bin, Coord, Date, DateTime, Marker, Na, Number, Qname, Ref, RemoveMarker,
+    Symbol, Time, Uri, Xstr,
+};
 use serde_json::json;
 use serde_json::Value;
 
@@ -100,6 +104,123 @@ impl Hayson for Coord {
     }
 }
 
+impl Hayson for Date {
+    fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
+        match &value {
+            Value::Object(obj) => {
+                if let Some(kind_err) = check_kind("date", value) {
+                    return Err(kind_err);
+                }
+                let val = obj.get("val");
+
+                if val.is_none() {
+                    return error("Date val is missing");
+                }
+
+                let val = val.unwrap().as_str();
+                if val.is_none() {
+                    return error("Date val is not a string");
+                }
+
+                match val.unwrap().parse() {
+                    Ok(date) => Ok(date),
+                    Err(_) => error("Date val is not a valid date"),
+                }
+            }
+            _ => error("Date JSON value must be an object"),
+        }
+    }
+
+    fn to_hayson(&self) -> Value {
+        json!({
+            KIND: "date",
+            "val": self.to_string(),
+        })
+    }
+}
+
+impl Hayson for Time {
+    fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
+        match &value {
+            Value::Object(obj) => {
+                if let Some(kind_err) = check_kind("time", value) {
+                    return Err(kind_err);
+                }
+                let val = obj.get("val");
+
+                if val.is_none() {
+                    return error("Time val is missing");
+                }
+
+                let val = val.unwrap().as_str();
+                if val.is_none() {
+                    return error("Time val is not a string");
+                }
+
+                match val.unwrap().parse() {
+                    Ok(time) => Ok(time),
+                    Err(_) => error("Time val is not a valid time"),
+                }
+            }
+            _ => error("Time JSON value must be an object"),
+        }
+    }
+
+    fn to_hayson(&self) -> Value {
+        json!({
+            KIND: "time",
+            "val": self.to_string(),
+        })
+    }
+}
+
+impl Hayson for DateTime {
+    fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
+        match &value {
+            Value::Object(obj) => {
+                if let Some(kind_err) = check_kind("dateTime", value) {
+                    return Err(kind_err);
+                }
+                let val = obj.get("val");
+
+                if val.is_none() {
+                    return error("DateTime val is missing");
+                }
+
+                let val = val.unwrap().as_str();
+                if val.is_none() {
+                    return error("DateTime val is not a string");
+                }
+                let val = val.unwrap();
+
+                // The tz is optional, and defaults to a timezone matching
+                // the offset in val.
+                let s = match obj.get("tz") {
+                    Some(tz) => match tz.as_str() {
+                        Some(tz) => format!("{} {}", val, tz),
+                        None => return error("DateTime tz is not a string"),
+                    },
+                    None => val.to_owned(),
+                };
+
+                match s.parse() {
+                    Ok(date_time) => Ok(date_time),
+                    Err(_) => error("DateTime val is not a valid date time"),
+                }
+            }
+            _ => error("DateTime JSON value must be an object"),
+        }
+    }
+
+    fn to_hayson(&self) -> Value {
+        json!({
+            KIND: "dateTime",
+            "val": self.to_offset_string(),
+            "tz": self.time_zone(),
+        })
+    }
+}
+
 impl Hayson for Ref {
     fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
         match &value {
@@ -125,17 +246,34 @@ impl Hayson for Ref {
                     return error(format!("Ref val is not valid: {}", ref_str));
                 }
 
-                Ok(Ref::new(ref_str).unwrap())
+                // The display string is optional:
+                let dis = match obj.get("dis") {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(dis)) => Some(dis.to_owned()),
+                    Some(_) => return error("Ref dis is not a string"),
+                };
+
+                let mut hsref = Ref::new(ref_str).unwrap();
+                hsref.set_dis(dis);
+                Ok(hsref)
             }
             _ => error("Ref JSON value must be an object"),
         }
     }
 
     fn to_hayson(&self) -> Value {
-        json!({
-            KIND: "ref",
-            "val": self.to_axon_code().replacen("@", "", 1),
-        })
+        let val = self.to_axon_code().replacen("@", "", 1);
+        match self.dis() {
+            Some(dis) => json!({
+                KIND: "ref",
+                "val": val,
+                "dis": dis,
+            }),
+            None => json!({
+                KIND: "ref",
+                "val": val,
+            }),
+        }
     }
 }
 
@@ -143,10 +281,9 @@ impl Hayson for Number {
     fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
         match &value {
             Value::Number(num) => {
-                let float = num.as_f64();
-                match float {
-                    Some(float) => Ok(Number::new(float, None)),
-                    None => error(format!("Number is not a f64: {}", num)),
+                match parse_json_numeric(&num.to_string(), None) {
+                    Some(num) => Ok(num),
+                    None => error(format!("Number is not valid: {}", num)),
                 }
             }
             Value::Object(obj) => {
@@ -195,10 +332,9 @@ impl Hayson for Number {
                         }
                     },
                     Value::Number(num) => {
-                        let float = num.as_f64();
-                        match float {
-                            Some(float) => Ok(Number::new(float, unit)),
-                            None => error(format!("Number val is not a f64: {}", num)),
+                        match parse_json_numeric(&num.to_string(), unit) {
+                            Some(num) => Ok(num),
+                            None => error(format!("Number val is not valid: {}", num)),
                         }
                     },
                     _ => error("Number val must be either a number or a string"),
@@ -211,55 +347,74 @@ impl Hayson for Number {
     fn to_hayson(&self) -> Value {
         match self {
             Self::Basic(basic_num) => {
-                let kind = "number";
-                let value = basic_num.value();
-                if value.is_nan() {
-                    // SkySpark does not return units for NaN in its
-                    // Hayson encoding, despite SkySpark's 'unit' function
-                    // suggesting that NaN can have units.
-                    json!({
-                        KIND: kind,
-                        "val": "NaN",
-                    })
-                } else if value.is_infinite() && value.is_sign_positive() {
-                    // SkySpark does not return units for INF in its
-                    // Hayson encoding, despite SkySpark's 'unit' function
-                    // suggesting that INF can have units.
-                    json!({
-                        KIND: kind,
-                        "val": "INF",
-                    })
-                } else if value.is_infinite() && value.is_sign_negative() {
-                    // SkySpark does not return units for -INF in its
-                    // Hayson encoding, despite SkySpark's 'unit' function
-                    // suggesting that -INF can have units.
-                    json!({
-                        KIND: kind,
-                        "val": "-INF",
-                    })
-                } else {
-                    json!({
-                        KIND: kind,
-                        "val": value,
-                        "unit": self.unit(),
-                    })
-                }
+                basic_number_hayson(basic_num.value(), self.unit())
             }
             Self::Scientific(sci_num) => {
-                let kind = "number";
                 let sig = sci_num.significand();
                 let exp = sci_num.exponent();
-                let value = sig * 10f64.powi(exp);
-                json!({
-                    KIND: kind,
-                    "val": value,
-                    "unit": self.unit(),
-                })
+                exact_number_hayson(&format!("{}e{}", sig, exp), self)
+            }
+            Self::Integer(int_num) => {
+                exact_number_hayson(&int_num.value().to_string(), self)
+            }
+            Self::Decimal(dec_num) => {
+                exact_number_hayson(&dec_num.to_digits(), self)
             }
         }
     }
 }
 
+fn basic_number_hayson(value: f64, unit: Option<&str>) -> Value {
+    let kind = "number";
+    if value.is_nan() {
+        // SkySpark does not return units for NaN in its
+        // Hayson encoding, despite SkySpark's 'unit' function
+        // suggesting that NaN can have units.
+        json!({
+            KIND: kind,
+            "val": "NaN",
+        })
+    } else if value.is_infinite() && value.is_sign_positive() {
+        // SkySpark does not return units for INF in its
+        // Hayson encoding, despite SkySpark's 'unit' function
+        // suggesting that INF can have units.
+        json!({
+            KIND: kind,
+            "val": "INF",
+        })
+    } else if value.is_infinite() && value.is_sign_negative() {
+        // SkySpark does not return units for -INF in its
+        // Hayson encoding, despite SkySpark's 'unit' function
+        // suggesting that -INF can have units.
+        json!({
+            KIND: kind,
+            "val": "-INF",
+        })
+    } else {
+        json!({
+            KIND: kind,
+            "val": value,
+            "unit": unit,
+        })
+    }
+}
+
+/// Encode a number using the digits in the string. The digits are only
+/// kept exactly when the `exact-json` feature is enabled. Otherwise
+/// serde_json stores the number as an integer if it fits into a `u64` or
+/// `i64`, or as a `f64`. Numbers which are too large for a `f64` are
+/// encoded like a `BasicNumber`.
+fn exact_number_hayson(digits: &str, number: &Number) -> Value {
+    match digits.parse::<serde_json::Number>() {
+        Ok(value) => json!({
+            KIND: "number",
+            "val": value,
+            "unit": number.unit(),
+        }),
+        Err(_) => basic_number_hayson(number.to_f64(), number.unit()),
+    }
+}
+
 impl Hayson for Symbol {
     fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
         match &value {
@@ -301,6 +456,24 @@ impl Hayson for Symbol {
     }
 }
 
+/// Hayson has no kind for qualified names, so a `Qname` is encoded as
+/// a string.
+impl Hayson for Qname {
+    fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
+        match value {
+            Value::String(s) => match Qname::new(s.to_owned()) {
+                Ok(qname) => Ok(qname),
+                Err(_) => error(format!("Qname is not valid: {}", s)),
+            },
+            _ => error("Qname JSON value must be a string"),
+        }
+    }
+
+    fn to_hayson(&self) -> Value {
+        Value::String(self.qname().to_owned())
+    }
+}
+
 impl Hayson for Marker {
     fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
         match &value {
@@ -387,6 +560,48 @@ impl Hayson for Uri {
     }
 }
 
+impl Hayson for Bin {
+    fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
+        match &value {
+            Value::Object(obj) => {
+                if let Some(kind_err) = check_kind("bin", value) {
+                    return Err(kind_err);
+                }
+
+                let mime = match obj.get("mime") {
+                    Some(Value::String(mime)) => mime.clone(),
+                    Some(_) => return error("Bin mime is not a string"),
+                    None => return error("Bin mime is missing"),
+                };
+
+                // The binary data is not part of the Hayson specification,
+                // so it is optional:
+                let bin = match obj.get("data") {
+                    Some(Value::String(data)) => Bin::from_base64(mime, data),
+                    Some(Value::Null) | None => Bin::new(mime),
+                    Some(_) => return error("Bin data is not a string"),
+                };
+                bin.or_else(|err| error(err.to_string()))
+            }
+            _ => error("Bin JSON value must be an object"),
+        }
+    }
+
+    fn to_hayson(&self) -> Value {
+        match self.to_base64() {
+            Some(data) => json!({
+                KIND: "bin",
+                "mime": self.mime(),
+                "data": data,
+            }),
+            None => json!({
+                KIND: "bin",
+                "mime": self.mime(),
+            }),
+        }
+    }
+}
+
 impl Hayson for Xstr {
     fn from_hayson(value: &Value) -> Result<Self, FromHaysonError> {
         match &value {
@@ -419,7 +634,10 @@ impl Hayson for Xstr {
                 let val = val.unwrap().to_owned();
                 let type_name = type_name.unwrap().to_owned();
 
-                Ok(Xstr::new(type_name, val))
+                match Xstr::new(type_name, val) {
+                    Ok(xstr) => Ok(xstr),
+                    Err(_) => error("Xstr type is not a valid type name"),
+                }
             }
             _ => error("Xstr JSON value must be an object"),
         }
@@ -438,8 +656,10 @@ impl Hayson for Xstr {
 mod test {
     use super::Hayson;
     use crate::{
-        Coord, Marker, Na, Number, Ref, RemoveMarker, Symbol, Uri, Xstr,
+        Bin, Coord, Date, DateTime, Marker, Na, Number, Qname, Ref,
+        RemoveMarker, Symbol, Time, Uri, Xstr,
     };
+    use serde_json::json;
 
     #[test]
     fn serde_coord_works() {
@@ -449,6 +669,45 @@ mod test {
         assert_eq!(coord, deserialized);
     }
 
+    #[test]
+    fn serde_date_works() {
+        let date = Date::new(2021, 2, 28).unwrap();
+        let value = date.to_hayson();
+        assert_eq!(value["val"], "2021-02-28");
+        let deserialized = Date::from_hayson(&value).unwrap();
+        assert_eq!(date, deserialized);
+    }
+
+    #[test]
+    fn serde_time_works() {
+        let time = Time::new_nano(13, 5, 9, 250_000_000).unwrap();
+        let value = time.to_hayson();
+        assert_eq!(value["val"], "13:05:09.250");
+        let deserialized = Time::from_hayson(&value).unwrap();
+        assert_eq!(time, deserialized);
+    }
+
+    #[test]
+    fn serde_date_time_works() {
+        let date_time: DateTime =
+            "2021-02-28T13:05:09+10:00 Brisbane".parse().unwrap();
+        let value = date_time.to_hayson();
+        assert_eq!(value["val"], "2021-02-28T13:05:09+10:00");
+        assert_eq!(value["tz"], "Brisbane");
+        let deserialized = DateTime::from_hayson(&value).unwrap();
+        assert_eq!(date_time, deserialized);
+    }
+
+    #[test]
+    fn serde_date_time_without_tz_works() {
+        let value = json!({
+            "_kind": "dateTime",
+            "val": "2021-02-28T13:05:09-05:00",
+        });
+        let deserialized = DateTime::from_hayson(&value).unwrap();
+        assert_eq!(deserialized.time_zone(), "GMT+5");
+    }
+
     #[test]
     fn serde_ref_works() {
         let hsref = Ref::new("@abc".to_owned()).unwrap();
@@ -457,6 +716,16 @@ mod test {
         assert_eq!(hsref, deserialized);
     }
 
+    #[test]
+    fn serde_ref_with_dis_works() {
+        let hsref =
+            Ref::new_with_dis("@abc".to_owned(), "Site".to_owned()).unwrap();
+        let value = hsref.to_hayson();
+        assert_eq!(value["dis"], "Site");
+        let deserialized = Ref::from_hayson(&value).unwrap();
+        assert_eq!(deserialized.dis(), Some("Site"));
+    }
+
     #[test]
     fn serde_number_nan_works() {
         let num = Number::new(f64::NAN, None);
@@ -531,8 +800,7 @@ mod test {
         let value = num.to_hayson();
         let deserialized = Number::from_hayson(&value).unwrap();
 
-        // Currently, scientific notation numbers are evaluated then
-        // serialized as a float which is not in scientific notation.
+        // Scientific notation numbers are decoded as basic numbers:
         let basic = deserialized.as_number().unwrap();
         assert_eq!(basic.value(), 0.000000000000000000000000000000000662607015);
     }
@@ -545,13 +813,55 @@ mod test {
         let value = num.to_hayson();
         let deserialized = Number::from_hayson(&value).unwrap();
 
-        // Currently, scientific notation numbers are evaluated then
-        // serialized as a float which is not in scientific notation.
+        // Scientific notation numbers are decoded as basic numbers:
         let basic = deserialized.as_number().unwrap();
         assert_eq!(basic.value(), 0.000000000000000000000000000000000662607015);
         assert_eq!(basic.unit(), Some("m/s"));
     }
 
+    #[test]
+    fn serde_number_scientific_is_exact() {
+        let num = Number::new_scientific_unitless(1.23, 300).unwrap();
+        let value = num.to_hayson();
+        assert_eq!(value["val"].as_f64(), Some(1.23e300));
+        let deserialized = Number::from_hayson(&value).unwrap();
+        assert_eq!(deserialized, Number::new_unitless(1.23e300));
+    }
+
+    #[cfg(feature = "exact-json")]
+    #[test]
+    fn serde_number_exact_works() {
+        let numbers = vec![
+            Number::new_integer(u64::MAX.into(), Some("Wh".to_owned())),
+            Number::new_decimal(-12345678901234567891, -3, None),
+            Number::new_decimal(1, 400, Some("J".to_owned())),
+        ];
+        for num in numbers {
+            let value = num.to_hayson();
+            let deserialized = Number::from_hayson(&value).unwrap();
+            assert_eq!(num, deserialized);
+        }
+
+        let value: serde_json::Value =
+            serde_json::from_str("18446744073709551615").unwrap();
+        assert_eq!(
+            Number::from_hayson(&value).unwrap(),
+            Number::new_integer(u64::MAX.into(), None)
+        );
+    }
+
+    #[test]
+    fn serde_number_large_works() {
+        let num = Number::new_integer(u64::MAX.into(), Some("Wh".to_owned()));
+        let value = num.to_hayson();
+        assert_eq!(value["val"], json!(u64::MAX));
+        assert_eq!(Number::from_hayson(&value).unwrap(), num);
+
+        let num = Number::new_decimal(1, 400, Some("J".to_owned()));
+        let value = num.to_hayson();
+        assert!(Number::from_hayson(&value).is_ok());
+    }
+
     #[test]
     fn serde_symbol_works() {
         let sym = Symbol::new("^abc".to_owned()).unwrap();
@@ -560,6 +870,16 @@ mod test {
         assert_eq!(sym, deserialized);
     }
 
+    #[test]
+    fn serde_qname_works() {
+        let qname = Qname::new("core::parseNumber".to_owned()).unwrap();
+        let value = qname.to_hayson();
+        assert_eq!(value, json!("core::parseNumber"));
+        assert_eq!(Qname::from_hayson(&value).unwrap(), qname);
+        assert!(Qname::from_hayson(&json!("core::Bad")).is_err());
+        assert!(Qname::from_hayson(&json!(1)).is_err());
+    }
+
     #[test]
     fn serde_marker_works() {
         let x = Marker::new();
@@ -594,9 +914,25 @@ mod test {
 
     #[test]
     fn serde_xstr_works() {
-        let x = Xstr::new("Color".to_owned(), "red".to_owned());
+        let x = Xstr::new("Color".to_owned(), "red".to_owned()).unwrap();
         let value = x.to_hayson();
         let deserialized = Xstr::from_hayson(&value).unwrap();
         assert_eq!(x, deserialized);
     }
+
+    #[test]
+    fn serde_bin_works() {
+        let bin = Bin::new("image/png".to_owned()).unwrap();
+        let value = bin.to_hayson();
+        assert_eq!(value, json!({"_kind": "bin", "mime": "image/png"}));
+        assert_eq!(Bin::from_hayson(&value).unwrap(), bin);
+
+        let bin = bin.with_data(Some(vec![0, 1, 2, 255]));
+        let value = bin.to_hayson();
+        assert_eq!(value["data"], json!("AAEC/w=="));
+        assert_eq!(Bin::from_hayson(&value).unwrap(), bin);
+
+        let invalid = json!({"_kind": "bin", "mime": "png"});
+        assert!(Bin::from_hayson(&invalid).is_err());
+    }
 }
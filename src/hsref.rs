@@ -1,8 +1,13 @@
 use thiserror::Error;
 
-/// A Haystack Ref.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Ref(String);
+/// A Haystack Ref, with an optional display string. Refs are compared,
+/// hashed and ordered using their id only, ignoring the display string.
+/// Use `Value::haystack_cmp` to sort refs by their display strings.
+#[derive(Clone, Debug)]
+pub struct Ref {
+    id: String,
+    dis: Option<String>,
+}
 
 impl Ref {
     /// Create a new `Ref`.
@@ -14,41 +19,75 @@ impl Ref {
     /// ```
     pub fn new(s: String) -> Result<Self, ParseRefError> {
         if Self::is_valid_ref(&s) {
-            Ok(Ref(s))
+            Ok(Ref { id: s, dis: None })
         } else {
             Err(ParseRefError::from_string(s))
         }
     }
+
+    /// Create a new `Ref` with a display string.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Ref;
+    /// let my_ref = Ref::new_with_dis("@site".to_string(), "Site 1".to_string()).unwrap();
+    /// assert_eq!(my_ref.dis(), Some("Site 1"));
+    /// ```
+    pub fn new_with_dis(s: String, dis: String) -> Result<Self, ParseRefError> {
+        let mut hsref = Self::new(s)?;
+        hsref.dis = Some(dis);
+        Ok(hsref)
+    }
+
     /// Return a Ref by decoding a ref which was encoded in a JSON string. In
     /// raw JSON strings, refs are formatted with a `r:` prefix instead of
-    /// an `@` sign.
+    /// an `@` sign, and may be followed by a space and a display string.
     ///
     /// # Example
     /// ```rust
     /// use raystack_core::Ref;
-    /// let json_str = "r:p:bigProject:r:24efe1c4-24aef280";
+    /// let json_str = "r:p:bigProject:r:24efe1c4-24aef280 Main Site";
     /// let my_ref = Ref::from_encoded_json_string(json_str).unwrap();
+    /// assert_eq!(my_ref.dis(), Some("Main Site"));
     /// ```
     pub fn from_encoded_json_string(
         json_string: &str,
     ) -> Result<Self, ParseRefError> {
-        if let Some(raw_id) = json_string.split(' ').next() {
-            Self::new(raw_id.replacen("r:", "@", 1))
+        let mut parts = json_string.splitn(2, ' ');
+        if let Some(raw_id) = parts.next() {
+            let mut hsref = Self::new(raw_id.replacen("r:", "@", 1))?;
+            hsref.dis = parts.next().map(|dis| dis.to_owned());
+            Ok(hsref)
         } else {
             Err(ParseRefError::from_str(json_string))
         }
     }
 
     /// Return a string containing this ref, encoded with a `r:` prefix instead
-    /// of with an `@` sign. This representation for refs is used in raw
+    /// of with an `@` sign, followed by a space and the display string if
+    /// present. This representation for refs is used in raw
     /// JSON strings sent to and from a Haystack server.
     pub fn to_encoded_json_string(&self) -> String {
-        self.0.replacen("@", "r:", 1)
+        let id = self.id.replacen('@', "r:", 1);
+        match self.dis() {
+            Some(dis) => format!("{} {}", id, dis),
+            None => id,
+        }
+    }
+
+    /// Return the display string of this ref, if present.
+    pub fn dis(&self) -> Option<&str> {
+        self.dis.as_deref()
+    }
+
+    /// Set the display string of this ref.
+    pub fn set_dis(&mut self, dis: Option<String>) {
+        self.dis = dis;
     }
 
-    /// Convert this ref into a string.
+    /// Convert this ref into a string, discarding any display string.
     pub fn into_string(self) -> String {
-        self.0
+        self.id
     }
 
     /// Return this ref as an Axon ref literal.
@@ -103,7 +142,10 @@ impl std::str::FromStr for Ref {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if Self::is_valid_ref(s) {
-            Ok(Ref(s.to_owned()))
+            Ok(Ref {
+                id: s.to_owned(),
+                dis: None,
+            })
         } else {
             let unparsable_ref = s.to_owned();
             Err(ParseRefError { unparsable_ref })
@@ -119,7 +161,33 @@ impl std::fmt::Display for Ref {
 
 impl std::convert::AsRef<str> for Ref {
     fn as_ref(&self) -> &str {
-        &self.0
+        &self.id
+    }
+}
+
+impl std::cmp::PartialEq for Ref {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl std::cmp::Eq for Ref {}
+
+impl std::hash::Hash for Ref {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl std::cmp::PartialOrd for Ref {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::Ord for Ref {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id.cmp(&other.id)
     }
 }
 
@@ -154,4 +222,28 @@ mod test {
         assert_eq!(Ref::is_valid_ref("@o,o"), false);
         assert_eq!(Ref::is_valid_ref("@o|o"), false);
     }
+
+    #[test]
+    fn equality_ignores_dis() {
+        use std::collections::HashSet;
+        let plain = Ref::new("@site".to_owned()).unwrap();
+        let with_dis =
+            Ref::new_with_dis("@site".to_owned(), "Site".to_owned()).unwrap();
+        assert_eq!(plain, with_dis);
+
+        let set: HashSet<Ref> = vec![plain, with_dis].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn json_string_keeps_dis() {
+        let hsref = Ref::from_encoded_json_string("r:site Main Site").unwrap();
+        assert_eq!(hsref.as_ref() as &str, "@site");
+        assert_eq!(hsref.dis(), Some("Main Site"));
+        assert_eq!(hsref.to_encoded_json_string(), "r:site Main Site");
+
+        let hsref = Ref::from_encoded_json_string("r:site").unwrap();
+        assert_eq!(hsref.dis(), None);
+        assert_eq!(hsref.to_encoded_json_string(), "r:site");
+    }
 }
@@ -0,0 +1,706 @@
+use crate::number::{parse_json_numeric, parse_numeric};
+use crate::{
+    Bin, Column, Coord, Date, DateTime, Dict, Grid, Marker, Na, Number, Ref,
+    RemoveMarker, Symbol, TagName, Time, Uri, Value, Xstr,
+};
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+
+/// An error indicating that a value could not be decoded from the
+/// Haystack 3 JSON encoding.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not decode Haystack JSON: {message}")]
+pub struct FromHaystackJsonV3Error {
+    message: String,
+}
+
+impl FromHaystackJsonV3Error {
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+
+    /// Return a description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+fn error<T, M>(message: M) -> Result<T, FromHaystackJsonV3Error>
+where
+    M: AsRef<str>,
+{
+    Err(FromHaystackJsonV3Error::new(message.as_ref().to_owned()))
+}
+
+/// Something which can be converted to and from the JSON encoding used by
+/// Project Haystack 3, where scalars are encoded as strings with a
+/// type prefix, such as `"r:"` for refs.
+pub trait HaystackJsonV3: Sized {
+    fn from_json_v3(value: &JsonValue)
+        -> Result<Self, FromHaystackJsonV3Error>;
+    fn to_json_v3(&self) -> JsonValue;
+}
+
+/// Split an encoded scalar like `"n:12 kW"` into its prefix and the rest of
+/// the string. Returns `None` if the string has no prefix.
+fn split_prefix(s: &str) -> Option<(char, &str)> {
+    let mut chars = s.chars();
+    let prefix = chars.next()?;
+    if chars.next() == Some(':') {
+        Some((prefix, &s[prefix.len_utf8() + 1..]))
+    } else {
+        None
+    }
+}
+
+/// Return the string contents of a JSON value which should be a string with
+/// the given prefix.
+fn prefixed_str<'a>(
+    value: &'a JsonValue,
+    prefix: char,
+    kind: &str,
+) -> Result<&'a str, FromHaystackJsonV3Error> {
+    match value.as_str().and_then(split_prefix) {
+        Some((actual_prefix, rest)) if actual_prefix == prefix => Ok(rest),
+        _ => error(format!(
+            "{} JSON value must be a string starting with '{}:'",
+            kind, prefix
+        )),
+    }
+}
+
+fn parse_number(s: &str) -> Result<Number, FromHaystackJsonV3Error> {
+    let (value, unit) = match s.find(' ') {
+        Some(index) => (&s[..index], Some(s[index + 1..].to_owned())),
+        None => (s, None),
+    };
+
+    match value {
+        "INF" => return Ok(Number::new(f64::INFINITY, unit)),
+        "-INF" => return Ok(Number::new(f64::NEG_INFINITY, unit)),
+        "NaN" => return Ok(Number::new(f64::NAN, unit)),
+        _ => (),
+    }
+
+    match parse_numeric(value, unit) {
+        Some(number) => Ok(number),
+        None => error(format!("Number is not valid: {}", s)),
+    }
+}
+
+fn number_to_string(number: &Number) -> String {
+    match number {
+        Number::Basic(num) => {
+            let value = num.value();
+            // Like Zinc, units are not written for NaN, INF or -INF.
+            if value.is_nan() {
+                "NaN".to_owned()
+            } else if value.is_infinite() && value.is_sign_positive() {
+                "INF".to_owned()
+            } else if value.is_infinite() && value.is_sign_negative() {
+                "-INF".to_owned()
+            } else if let Some(unit) = num.unit() {
+                format!("{} {}", value, unit)
+            } else {
+                format!("{}", value)
+            }
+        }
+        Number::Scientific(num) => {
+            let sig = num.significand();
+            let exp = num.exponent();
+            if let Some(unit) = num.unit() {
+                format!("{}e{} {}", sig, exp, unit)
+            } else {
+                format!("{}e{}", sig, exp)
+            }
+        }
+        Number::Integer(num) => match num.unit() {
+            Some(unit) => format!("{} {}", num.value(), unit),
+            None => num.value().to_string(),
+        },
+        Number::Decimal(num) => match num.unit() {
+            Some(unit) => format!("{} {}", num.to_digits(), unit),
+            None => num.to_digits(),
+        },
+    }
+}
+
+fn parse_coord(s: &str) -> Result<Coord, FromHaystackJsonV3Error> {
+    let parts: Vec<&str> = s.split(',').collect();
+    if parts.len() != 2 {
+        return error(format!("Coord is not valid: {}", s));
+    }
+    match (parts[0].trim().parse(), parts[1].trim().parse()) {
+        (Ok(lat), Ok(lng)) => Ok(Coord::new(lat, lng)),
+        _ => error(format!("Coord is not valid: {}", s)),
+    }
+}
+
+fn parse_xstr(s: &str) -> Result<Xstr, FromHaystackJsonV3Error> {
+    match s.find(':') {
+        Some(index) => {
+            let type_name = s[..index].to_owned();
+            let value = s[index + 1..].to_owned();
+            Xstr::new(type_name, value)
+                .or_else(|_| error(format!("Xstr is not valid: {}", s)))
+        }
+        None => error(format!("Xstr is not valid: {}", s)),
+    }
+}
+
+fn parse_date(s: &str) -> Result<Date, FromHaystackJsonV3Error> {
+    match s.parse() {
+        Ok(date) => Ok(date),
+        Err(_) => error(format!("Date is not valid: {}", s)),
+    }
+}
+
+fn parse_time(s: &str) -> Result<Time, FromHaystackJsonV3Error> {
+    match s.parse() {
+        Ok(time) => Ok(time),
+        Err(_) => error(format!("Time is not valid: {}", s)),
+    }
+}
+
+fn parse_date_time(s: &str) -> Result<DateTime, FromHaystackJsonV3Error> {
+    match s.parse() {
+        Ok(date_time) => Ok(date_time),
+        Err(_) => error(format!("DateTime is not valid: {}", s)),
+    }
+}
+
+fn parse_bin(s: &str) -> Result<Bin, FromHaystackJsonV3Error> {
+    match Bin::new(s.to_owned()) {
+        Ok(bin) => Ok(bin),
+        Err(_) => error(format!("Bin is not valid: {}", s)),
+    }
+}
+
+/// Decode a string from the Haystack 3 JSON encoding, which may be any
+/// scalar kind depending on its prefix.
+fn parse_scalar(s: &str) -> Result<Value, FromHaystackJsonV3Error> {
+    let (prefix, rest) = match split_prefix(s) {
+        Some(split) => split,
+        None => return Ok(Value::Str(s.to_owned())),
+    };
+    let value = match prefix {
+        'm' => Value::Marker,
+        '-' => Value::RemoveMarker,
+        'z' => Value::Na,
+        's' => Value::Str(rest.to_owned()),
+        'n' => Value::Number(parse_number(rest)?),
+        'r' => match Ref::from_encoded_json_string(s) {
+            Ok(hsref) => Value::Ref(hsref),
+            Err(_) => return error(format!("Ref is not valid: {}", s)),
+        },
+        'y' => match Symbol::from_encoded_json_string(s) {
+            Ok(symbol) => Value::Symbol(symbol),
+            Err(_) => return error(format!("Symbol is not valid: {}", s)),
+        },
+        'u' => Value::Uri(Uri::new(rest.to_owned())),
+        'c' => Value::Coord(parse_coord(rest)?),
+        'x' => Value::Xstr(parse_xstr(rest)?),
+        'd' => Value::Date(parse_date(rest)?),
+        'h' => Value::Time(parse_time(rest)?),
+        't' => Value::DateTime(parse_date_time(rest)?),
+        'b' => Value::Bin(parse_bin(rest)?),
+        _ => Value::Str(s.to_owned()),
+    };
+    Ok(value)
+}
+
+/// Encode a string, adding the `s:` prefix if the string could otherwise be
+/// mistaken for a prefixed scalar.
+fn str_to_json_v3(s: &str) -> JsonValue {
+    if split_prefix(s).is_some() {
+        JsonValue::String(format!("s:{}", s))
+    } else {
+        JsonValue::String(s.to_owned())
+    }
+}
+
+fn tag_name(name: &str) -> Result<TagName, FromHaystackJsonV3Error> {
+    match TagName::new(name.to_owned()) {
+        Some(name) => Ok(name),
+        None => error(format!("Tag name is not valid: {}", name)),
+    }
+}
+
+/// Encode the tags of a dict as entries in a JSON object.
+fn insert_tags(obj: &mut Map<String, JsonValue>, dict: &Dict) {
+    for (name, value) in dict.iter() {
+        obj.insert(name.to_string(), value.to_json_v3());
+    }
+}
+
+/// Decode the entries of a JSON object as dict tags, skipping the entries
+/// with the given names.
+fn dict_from_obj(
+    obj: &Map<String, JsonValue>,
+    skip: &[&str],
+) -> Result<Dict, FromHaystackJsonV3Error> {
+    let mut dict = Dict::new();
+    for (name, value) in obj {
+        if !skip.contains(&name.as_str()) {
+            dict.insert(tag_name(name)?, Value::from_json_v3(value)?);
+        }
+    }
+    Ok(dict)
+}
+
+fn is_grid(obj: &Map<String, JsonValue>) -> bool {
+    obj.get("meta")
+        .is_some_and(|meta| meta.get("ver").is_some())
+        && obj.get("cols").is_some_and(|cols| cols.is_array())
+}
+
+impl HaystackJsonV3 for Coord {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        parse_coord(prefixed_str(value, 'c', "Coord")?)
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("c:{},{}", self.lat(), self.lng()))
+    }
+}
+
+impl HaystackJsonV3 for Date {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        parse_date(prefixed_str(value, 'd', "Date")?)
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("d:{}", self))
+    }
+}
+
+impl HaystackJsonV3 for Time {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        parse_time(prefixed_str(value, 'h', "Time")?)
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("h:{}", self))
+    }
+}
+
+impl HaystackJsonV3 for DateTime {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        parse_date_time(prefixed_str(value, 't', "DateTime")?)
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("t:{}", self))
+    }
+}
+
+impl HaystackJsonV3 for Ref {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        let s = prefixed_str(value, 'r', "Ref")?;
+        match Ref::from_encoded_json_string(value.as_str().unwrap()) {
+            Ok(hsref) => Ok(hsref),
+            Err(_) => error(format!("Ref is not valid: {}", s)),
+        }
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(self.to_encoded_json_string())
+    }
+}
+
+impl HaystackJsonV3 for Number {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        parse_number(prefixed_str(value, 'n', "Number")?)
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("n:{}", number_to_string(self)))
+    }
+}
+
+impl HaystackJsonV3 for Symbol {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        let s = prefixed_str(value, 'y', "Symbol")?;
+        match Symbol::from_encoded_json_string(value.as_str().unwrap()) {
+            Ok(symbol) => Ok(symbol),
+            Err(_) => error(format!("Symbol is not valid: {}", s)),
+        }
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(self.to_encoded_json_string())
+    }
+}
+
+impl HaystackJsonV3 for Marker {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        prefixed_str(value, 'm', "Marker")?;
+        Ok(Marker::new())
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!("m:")
+    }
+}
+
+impl HaystackJsonV3 for RemoveMarker {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        prefixed_str(value, '-', "RemoveMarker")?;
+        Ok(RemoveMarker::new())
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!("-:")
+    }
+}
+
+impl HaystackJsonV3 for Na {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        prefixed_str(value, 'z', "NA")?;
+        Ok(Na::new())
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!("z:")
+    }
+}
+
+impl HaystackJsonV3 for Uri {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        let s = prefixed_str(value, 'u', "Uri")?;
+        Ok(Uri::new(s.to_owned()))
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("u:{}", self.as_ref()))
+    }
+}
+
+impl HaystackJsonV3 for Xstr {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        parse_xstr(prefixed_str(value, 'x', "Xstr")?)
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("x:{}:{}", self.type_name(), self.value()))
+    }
+}
+
+impl HaystackJsonV3 for Bin {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        parse_bin(prefixed_str(value, 'b', "Bin")?)
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        json!(format!("b:{}", self.mime()))
+    }
+}
+
+impl HaystackJsonV3 for Dict {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        match value {
+            JsonValue::Object(obj) => dict_from_obj(obj, &[]),
+            _ => error("Dict JSON value must be an object"),
+        }
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        let mut obj = Map::new();
+        insert_tags(&mut obj, self);
+        JsonValue::Object(obj)
+    }
+}
+
+impl HaystackJsonV3 for Grid {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        let obj = match value {
+            JsonValue::Object(obj) => obj,
+            _ => return error("Grid JSON value must be an object"),
+        };
+
+        let meta = match obj.get("meta") {
+            Some(JsonValue::Object(meta)) => dict_from_obj(meta, &["ver"])?,
+            _ => return error("Grid meta is missing or is not an object"),
+        };
+
+        let json_cols = match obj.get("cols") {
+            Some(JsonValue::Array(cols)) => cols,
+            _ => return error("Grid cols is missing or is not an array"),
+        };
+        let mut columns = Vec::new();
+        for json_col in json_cols {
+            let col = match json_col {
+                JsonValue::Object(col) => col,
+                _ => return error("Grid column must be an object"),
+            };
+            let name = match col.get("name").and_then(|name| name.as_str()) {
+                Some(name) => tag_name(name)?,
+                None => return error("Grid column name is missing"),
+            };
+            // The column named "empty" is only used for grids with no
+            // columns.
+            if name.as_ref() as &str == "empty" && json_cols.len() == 1 {
+                continue;
+            }
+            columns.push(Column::new(name, dict_from_obj(col, &["name"])?));
+        }
+
+        let json_rows = match obj.get("rows") {
+            Some(JsonValue::Array(rows)) => rows.as_slice(),
+            None => &[],
+            _ => return error("Grid rows is not an array"),
+        };
+        let mut rows = Vec::new();
+        for json_row in json_rows {
+            let mut row = Dict::from_json_v3(json_row)?;
+            let null_names: Vec<TagName> = row
+                .iter()
+                .filter(|(_, value)| value.is_null())
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in null_names {
+                row.remove(name.as_ref());
+            }
+            rows.push(row);
+        }
+
+        Ok(Grid::new(meta, columns, rows))
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        let mut meta = Map::new();
+        meta.insert("ver".to_owned(), json!("3.0"));
+        insert_tags(&mut meta, self.meta());
+
+        let cols: Vec<JsonValue> = if self.columns().is_empty() {
+            vec![json!({ "name": "empty" })]
+        } else {
+            self.columns()
+                .iter()
+                .map(|col| {
+                    let mut obj = Map::new();
+                    obj.insert(
+                        "name".to_owned(),
+                        json!(col.name().to_string()),
+                    );
+                    insert_tags(&mut obj, col.meta());
+                    JsonValue::Object(obj)
+                })
+                .collect()
+        };
+
+        let rows: Vec<JsonValue> =
+            self.rows().iter().map(|row| row.to_json_v3()).collect();
+
+        json!({
+            "meta": meta,
+            "cols": cols,
+            "rows": rows,
+        })
+    }
+}
+
+impl HaystackJsonV3 for Value {
+    fn from_json_v3(
+        value: &JsonValue,
+    ) -> Result<Self, FromHaystackJsonV3Error> {
+        match value {
+            JsonValue::Null => Ok(Value::Null),
+            JsonValue::Bool(b) => Ok(Value::Bool(*b)),
+            JsonValue::Number(num) => {
+                match parse_json_numeric(&num.to_string(), None) {
+                    Some(num) => Ok(num.into()),
+                    None => error(format!("Number is not valid: {}", num)),
+                }
+            }
+            JsonValue::String(s) => parse_scalar(s),
+            JsonValue::Array(values) => {
+                let list: Result<Vec<Value>, _> =
+                    values.iter().map(Value::from_json_v3).collect();
+                Ok(Value::List(list?))
+            }
+            JsonValue::Object(obj) => {
+                if is_grid(obj) {
+                    Ok(Grid::from_json_v3(value)?.into())
+                } else {
+                    Ok(Value::Dict(dict_from_obj(obj, &[])?))
+                }
+            }
+        }
+    }
+
+    fn to_json_v3(&self) -> JsonValue {
+        match self {
+            Self::Null => JsonValue::Null,
+            Self::Marker => Marker.to_json_v3(),
+            Self::RemoveMarker => RemoveMarker.to_json_v3(),
+            Self::Na => Na.to_json_v3(),
+            Self::Bool(b) => json!(b),
+            Self::Number(number) => number.to_json_v3(),
+            Self::Str(s) => str_to_json_v3(s),
+            Self::Uri(uri) => uri.to_json_v3(),
+            Self::Ref(hsref) => hsref.to_json_v3(),
+            Self::Symbol(symbol) => symbol.to_json_v3(),
+            Self::Date(date) => date.to_json_v3(),
+            Self::Time(time) => time.to_json_v3(),
+            Self::DateTime(date_time) => date_time.to_json_v3(),
+            Self::Coord(coord) => coord.to_json_v3(),
+            Self::Xstr(xstr) => xstr.to_json_v3(),
+            Self::Bin(bin) => bin.to_json_v3(),
+            Self::List(list) => {
+                JsonValue::Array(list.iter().map(|v| v.to_json_v3()).collect())
+            }
+            Self::Dict(dict) => dict.to_json_v3(),
+            Self::Grid(grid) => grid.to_json_v3(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HaystackJsonV3;
+    use crate::tag::tn;
+    use crate::{
+        Bin, Coord, Date, DateTime, Dict, Grid, Number, Ref, Symbol, Time, Uri,
+        Value, Xstr,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn scalars_round_trip() {
+        let values: Vec<Value> = vec![
+            Value::Null,
+            Value::Marker,
+            Value::RemoveMarker,
+            Value::Na,
+            true.into(),
+            "hello".into(),
+            "s:looks prefixed".into(),
+            Number::new(72.5, Some("°F".to_owned())).into(),
+            Number::new_scientific(1.2, -3, Some("kW".to_owned()))
+                .unwrap()
+                .into(),
+            Uri::new("http://a.com/".to_owned()).into(),
+            Ref::new("@p:demo:r:1".to_owned()).unwrap().into(),
+            Symbol::new("^steam-boiler".to_owned()).unwrap().into(),
+            Coord::new(37.55, -77.45).into(),
+            Bin::new("text/plain; charset=utf-8".to_owned())
+                .unwrap()
+                .into(),
+            Number::new_integer(u64::MAX.into(), Some("Wh".to_owned())).into(),
+            Number::new_decimal(12345678901234567891, 281, None).into(),
+            Date::new(2024, 1, 31).unwrap().into(),
+            Time::new_nano(23, 19, 0, 500_000_000).unwrap().into(),
+            "2015-06-08T15:47:41-04:00 New_York"
+                .parse::<DateTime>()
+                .unwrap()
+                .into(),
+            Xstr::new("Span".to_owned(), "2024-01-01,today".to_owned())
+                .unwrap()
+                .into(),
+        ];
+        for value in values {
+            let json = value.to_json_v3();
+            assert_eq!(Value::from_json_v3(&json).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn scalars_to_json_v3_works() {
+        assert_eq!(
+            Number::new(72.5, Some("°F".to_owned())).to_json_v3(),
+            json!("n:72.5 °F")
+        );
+        assert_eq!(
+            Number::new_unitless(f64::NEG_INFINITY).to_json_v3(),
+            json!("n:-INF")
+        );
+        assert_eq!(Coord::new(1.5, -2.0).to_json_v3(), json!("c:1.5,-2"));
+        let xstr = Xstr::new("Color".to_owned(), "red".to_owned()).unwrap();
+        assert_eq!(xstr.to_json_v3(), json!("x:Color:red"));
+        let value: Value = "s:abc".into();
+        assert_eq!(value.to_json_v3(), json!("s:s:abc"));
+    }
+
+    #[test]
+    fn from_json_v3_works() {
+        let hsref = Ref::from_json_v3(&json!("r:abc Site A")).unwrap();
+        assert_eq!(hsref, Ref::new("@abc".to_owned()).unwrap());
+        assert_eq!(hsref.dis(), Some("Site A"));
+        assert_eq!(hsref.to_json_v3(), json!("r:abc Site A"));
+        let nan = Number::from_json_v3(&json!("n:NaN")).unwrap();
+        assert!(nan.as_number().unwrap().value().is_nan());
+        assert!(Ref::from_json_v3(&json!("y:abc")).is_err());
+        let date = Value::from_json_v3(&json!("d:2024-01-01")).unwrap();
+        assert_eq!(date, Date::new(2024, 1, 1).unwrap().into());
+        let date_time =
+            DateTime::from_json_v3(&json!("t:2024-01-01T00:00:00Z")).unwrap();
+        assert_eq!(date_time.time_zone(), "UTC");
+        assert_eq!(date_time.to_json_v3(), json!("t:2024-01-01T00:00:00Z UTC"));
+        assert!(Value::from_json_v3(&json!("d:2024-13-01")).is_err());
+        assert!(Time::from_json_v3(&json!("d:2024-01-01")).is_err());
+        assert!(Value::from_json_v3(&json!("b:png")).is_err());
+    }
+
+    #[test]
+    fn grid_round_trip() {
+        let mut row1 = Dict::new();
+        row1.insert(tn("id"), Ref::new("@a".to_owned()).unwrap().into());
+        row1.insert(tn("site"), Value::Marker);
+        let mut row2 = Dict::new();
+        row2.insert(tn("id"), Ref::new("@b".to_owned()).unwrap().into());
+        row2.insert(
+            tn("tags"),
+            vec![Value::Null, Number::new_unitless(1.0).into()].into(),
+        );
+        let mut grid = Grid::from_rows(vec![row1, row2]);
+        grid.meta_mut().insert(tn("dis"), "Sites".into());
+        grid.column_meta_mut("id")
+            .unwrap()
+            .insert(tn("dis"), "Id".into());
+
+        let json = grid.to_json_v3();
+        assert_eq!(json["meta"], json!({"ver": "3.0", "dis": "Sites"}));
+        assert_eq!(json["cols"][0], json!({"name": "id", "dis": "Id"}));
+        assert_eq!(json["rows"][0], json!({"id": "r:a", "site": "m:"}));
+        assert_eq!(Grid::from_json_v3(&json).unwrap(), grid);
+
+        let nested: Value = grid.into();
+        assert_eq!(Value::from_json_v3(&nested.to_json_v3()).unwrap(), nested);
+
+        let empty = Grid::empty();
+        assert_eq!(Grid::from_json_v3(&empty.to_json_v3()).unwrap(), empty);
+    }
+}
@@ -1,28 +1,89 @@
+mod axon;
+#[cfg(feature = "axon")]
+mod axon_analysis;
+#[cfg(feature = "axon")]
+mod axon_format;
+#[cfg(feature = "axon")]
+mod axon_parser;
+mod bin;
 mod coord;
+mod date;
+mod datetime;
+mod dict;
+#[cfg(feature = "filter")]
+mod filter;
+mod grid;
 #[cfg(feature = "json")]
 mod hayson;
 mod hsref;
+#[cfg(feature = "json")]
+mod json_v3;
 mod marker;
 mod na;
 mod number;
+mod position;
 mod qname;
+mod span;
 mod symbol;
 mod tag;
+mod time;
+mod timezone;
+#[cfg(any(feature = "axon", feature = "zinc", feature = "filter"))]
+mod tokenizer;
+#[cfg(feature = "trio")]
+mod trio;
+mod unit;
 mod uri;
+mod value;
 mod xstr;
+mod zinc;
+#[cfg(feature = "zinc")]
+mod zinc_reader;
 
+pub use axon::{
+    BinaryOp, Expr, ExprKind, KeywordError, Param, SourceRange, UnaryOp,
+};
+#[cfg(feature = "axon")]
+pub use axon_analysis::{Finding, FindingKind};
+#[cfg(feature = "axon")]
+pub use axon_format::format_axon;
+#[cfg(feature = "axon")]
+pub use axon_parser::{AxonSource, Comment, ParseAxonError};
+pub use bin::{Bin, BinError};
 pub use coord::Coord;
+pub use date::{Date, ParseDateError};
+pub use datetime::{DateTime, ParseDateTimeError};
+pub use dict::Dict;
+#[cfg(feature = "filter")]
+pub use filter::{CmpOp, Filter, ParseFilterError, Path, RefResolver};
+pub use grid::{Column, Grid, GridError};
 #[cfg(feature = "json")]
 pub use hayson::{FromHaysonError, Hayson};
 pub use hsref::{ParseRefError, Ref};
+#[cfg(feature = "json")]
+pub use json_v3::{FromHaystackJsonV3Error, HaystackJsonV3};
 pub use marker::{Marker, RemoveMarker};
 pub use na::Na;
-pub use number::{BasicNumber, Number, ScientificNumber};
-pub use qname::Qname;
+pub use number::{
+    BasicNumber, DecimalNumber, IncompatibleUnitsError, IntegerNumber, Number,
+    ParseNumberError, ScientificNumber,
+};
+pub use position::Position;
+pub use qname::{ParseQnameError, Qname};
+pub use span::{ParseSpanError, Span, SpanMode};
 pub use symbol::{ParseSymbolError, Symbol};
 pub use tag::{is_tag_name, ParseTagNameError, TagName};
+pub use time::{ParseTimeError, Time};
+pub use timezone::TimeZone;
+#[cfg(feature = "trio")]
+pub use trio::{FromTrio, ParseTrioError, Trio, TrioReader};
+pub use unit::{is_unit, ConvertUnitError, Dimension, Unit, UnknownUnitError};
 pub use uri::Uri;
-pub use xstr::Xstr;
+pub use value::{Kind, Value};
+pub use xstr::{ParseXstrError, Xstr};
+pub use zinc::Zinc;
+#[cfg(feature = "zinc")]
+pub use zinc_reader::{FromZinc, ParseZincError, ZincReader};
 
 #[cfg(test)]
 mod tests {
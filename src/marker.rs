@@ -1,11 +1,16 @@
 /// A Haystack marker.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Marker;
 
 impl Marker {
     pub fn new() -> Self {
         Self
     }
+
+    /// Return this marker as Axon code.
+    pub fn to_axon_code(&self) -> String {
+        "marker()".to_owned()
+    }
 }
 
 impl std::default::Default for Marker {
@@ -15,13 +20,18 @@ impl std::default::Default for Marker {
 }
 
 /// A Haystack remove marker.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct RemoveMarker;
 
 impl RemoveMarker {
     pub fn new() -> Self {
         Self
     }
+
+    /// Return this remove marker as Axon code.
+    pub fn to_axon_code(&self) -> String {
+        "removeMarker()".to_owned()
+    }
 }
 
 impl std::default::Default for RemoveMarker {
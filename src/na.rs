@@ -1,11 +1,16 @@
 /// A Haystack NA (not available).
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Na;
 
 impl Na {
     pub fn new() -> Self {
         Self
     }
+
+    /// Return this NA as Axon code.
+    pub fn to_axon_code(&self) -> String {
+        "na()".to_owned()
+    }
 }
 
 impl std::default::Default for Na {
@@ -1,8 +1,409 @@
+use crate::unit::{is_unit, ConvertUnitError, Unit, UnknownUnitError};
+use crate::zinc::zinc_str;
+use std::convert::TryFrom;
+use thiserror::Error;
+
 /// A Haystack number.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Most numbers are stored as an `f64`. Numbers which cannot be represented
+/// exactly by an `f64`, like large 64-bit counters, are stored as an
+/// `IntegerNumber` or `DecimalNumber` so they are not changed when they are
+/// encoded again.
+///
+/// Numbers are equal if they have the same unit and the same decimal value,
+/// regardless of how they are represented, and NaN is equal to itself.
+/// Numbers are ordered by unit, with unitless numbers first, and then by
+/// value, with NaN after all other values.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::Number;
+/// let nan = Number::new_unitless(f64::NAN);
+/// assert_eq!(nan, nan.clone());
+/// let basic = Number::new(1500.0, Some("kW".to_owned()));
+/// let scientific = Number::new_scientific(1.5, 3, Some("kW".to_owned())).unwrap();
+/// assert_eq!(basic, scientific);
+/// assert!(Number::new_unitless(10.0) < Number::new(1.0, Some("kW".to_owned())));
+/// ```
+#[derive(Clone, Debug)]
 pub enum Number {
     Basic(BasicNumber),
     Scientific(ScientificNumber),
+    Integer(IntegerNumber),
+    Decimal(DecimalNumber),
+}
+
+impl std::cmp::PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.unit() == other.unit() && self.exact_value() == other.exact_value()
+    }
+}
+
+impl std::cmp::Eq for Number {}
+
+impl std::hash::Hash for Number {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.unit().hash(state);
+        self.exact_value().hash(state);
+    }
+}
+
+impl std::cmp::PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::Ord for Number {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.unit()
+            .cmp(&other.unit())
+            .then_with(|| self.exact_value().cmp(&other.exact_value()))
+    }
+}
+
+/// The value of a number, which can be compared and hashed regardless of
+/// how the number is represented.
+#[derive(Debug, Eq, Hash, PartialEq)]
+enum ExactValue {
+    NegInf,
+    /// A normalized mantissa and exponent. The exponent is wider than the
+    /// exponents of numbers, so it cannot overflow.
+    Finite(i128, i64),
+    PosInf,
+    NaN,
+}
+
+impl ExactValue {
+    fn rank(&self) -> u8 {
+        match self {
+            Self::NegInf => 0,
+            Self::Finite(_, _) => 1,
+            Self::PosInf => 2,
+            Self::NaN => 3,
+        }
+    }
+}
+
+impl std::cmp::PartialOrd for ExactValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::Ord for ExactValue {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (Self::Finite(a, a_exp), Self::Finite(b, b_exp)) => {
+                cmp_decimal((*a, *a_exp), (*b, *b_exp))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// Compare two normalized decimal numbers, each made of an integer mantissa
+/// and a power of ten exponent.
+fn cmp_decimal(a: (i128, i64), b: (i128, i64)) -> std::cmp::Ordering {
+    let (a, a_exp) = a;
+    let (b, b_exp) = b;
+    let ordering = a.signum().cmp(&b.signum());
+    if ordering != std::cmp::Ordering::Equal || a == 0 {
+        return ordering;
+    }
+    // Compare the magnitudes, first by the position of their leading digit,
+    // and then digit by digit:
+    let a_digits = a.unsigned_abs().to_string();
+    let b_digits = b.unsigned_abs().to_string();
+    let leading = |digits: &str, exp: i64| digits.len() as i64 + exp;
+    let magnitude = leading(&a_digits, a_exp)
+        .cmp(&leading(&b_digits, b_exp))
+        .then_with(|| a_digits.cmp(&b_digits));
+    if a < 0 {
+        magnitude.reverse()
+    } else {
+        magnitude
+    }
+}
+
+impl std::ops::Neg for Number {
+    type Output = Number;
+
+    /// Negate this number. Exact numbers which cannot be negated exactly,
+    /// because their value or mantissa is `i128::MIN`, are converted into
+    /// inexact numbers first.
+    fn neg(self) -> Number {
+        match self {
+            Self::Basic(num) => Self::Basic(-num),
+            Self::Scientific(ex) => Self::Scientific(-ex),
+            Self::Integer(int) => match int.checked_neg() {
+                Some(int) => Self::Integer(int),
+                None => Self::Basic(-int.to_basic()),
+            },
+            Self::Decimal(dec) => match dec.checked_neg() {
+                Some(dec) => Self::Decimal(dec),
+                None => Self::Scientific(-dec.to_scientific()),
+            },
+        }
+    }
+}
+
+impl std::ops::Neg for BasicNumber {
+    type Output = BasicNumber;
+
+    fn neg(self) -> BasicNumber {
+        BasicNumber::new(-self.value, self.unit)
+    }
+}
+
+impl std::ops::Neg for ScientificNumber {
+    type Output = ScientificNumber;
+
+    fn neg(self) -> ScientificNumber {
+        ScientificNumber {
+            significand: -self.significand,
+            ..self
+        }
+    }
+}
+
+impl std::str::FromStr for Number {
+    type Err = ParseNumberError;
+
+    /// Parse a number from its Zinc or Axon representation, like `72.5°F`,
+    /// `1.2e-3kW`, `INF`, `-INF`, `NaN` or `nan().as("kW")`. The unit may
+    /// also be separated from the number by a space, as it is in the
+    /// `Display` representation. Numbers with an exponent are parsed
+    /// as a `ScientificNumber`. Numbers whose digits cannot be represented
+    /// exactly by an `f64` are parsed as an `IntegerNumber` or
+    /// `DecimalNumber`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Number;
+    /// let num: Number = "72.5°F".parse().unwrap();
+    /// assert_eq!(num, Number::new(72.5, Some("°F".to_owned())));
+    /// let num: Number = "1.2e-3kW".parse().unwrap();
+    /// assert_eq!(num, Number::new_scientific(1.2, -3, Some("kW".to_owned())).unwrap());
+    /// let num: Number = "18446744073709551615".parse().unwrap();
+    /// assert_eq!(num, Number::new_integer(u64::MAX.into(), None));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseNumberError::from_str(s);
+
+        if let Some(num) = parse_axon_function(s.trim()) {
+            return num.ok_or_else(err);
+        }
+
+        let (numeric, unit) = split_unit(s.trim()).ok_or_else(err)?;
+        let unit = match unit.strip_prefix(' ').unwrap_or(unit) {
+            "" => None,
+            unit if unit.chars().all(is_unit_char) => Some(unit.to_owned()),
+            _ => return Err(err()),
+        };
+
+        match numeric {
+            "INF" => return Ok(Number::new(f64::INFINITY, unit)),
+            "-INF" => return Ok(Number::new(f64::NEG_INFINITY, unit)),
+            "NaN" => return Ok(Number::new(f64::NAN, unit)),
+            _ => (),
+        }
+
+        parse_numeric(&numeric.replace('_', ""), unit).ok_or_else(err)
+    }
+}
+
+/// Parse the numeric part of a Zinc or Axon number, like `-12.5` or
+/// `1.2e-3`, which must not contain underscores. The number is parsed as a
+/// `BasicNumber` or `ScientificNumber` if an `f64` can represent its digits
+/// exactly, and as an `IntegerNumber` or `DecimalNumber` otherwise.
+pub(crate) fn parse_numeric(
+    numeric: &str,
+    unit: Option<String>,
+) -> Option<Number> {
+    let (digits, exponent) = match numeric.find(['e', 'E']) {
+        Some(index) => (&numeric[..index], Some(&numeric[index + 1..])),
+        None => (numeric, None),
+    };
+    let value: f64 = digits.parse().ok()?;
+    let exponent: Option<i32> = match exponent {
+        Some(exponent) => Some(exponent.parse().ok()?),
+        None => None,
+    };
+    if !is_exact(digits, value) {
+        if let Some(number) = Number::from_exact_str(numeric, unit.clone()) {
+            return Some(number);
+        }
+    }
+    match exponent {
+        Some(exponent) => Number::new_scientific(value, exponent, unit),
+        None => Some(Number::new(value, unit)),
+    }
+}
+
+/// Parse a number from a JSON number, like `-12.5` or `1.2e-3`. The number
+/// is parsed as a `BasicNumber` if an `f64` can represent it exactly, and as
+/// an `IntegerNumber` or `DecimalNumber` otherwise.
+#[cfg(feature = "json")]
+pub(crate) fn parse_json_numeric(
+    numeric: &str,
+    unit: Option<String>,
+) -> Option<Number> {
+    let value: f64 = numeric.parse().ok()?;
+    if !is_exact(numeric, value) {
+        if let Some(number) = Number::from_exact_str(numeric, unit.clone()) {
+            return Some(number);
+        }
+    }
+    Some(Number::new(value, unit))
+}
+
+/// Return true if the `f64` is exactly the decimal number in the string,
+/// meaning the `f64` can be written out again without changing any digits.
+fn is_exact(numeric: &str, value: f64) -> bool {
+    value.is_finite()
+        && decimal_parts(numeric).map(normalize)
+            == decimal_parts(&format!("{:e}", value)).map(normalize)
+}
+
+/// Split a decimal number string, like `-12.5e3`, into an integer mantissa
+/// and a power of ten exponent, so `-12.5e3` is `(-125, 2)`. Returns `None`
+/// if the string is not a decimal number or the mantissa does not fit into
+/// an `i128`.
+fn decimal_parts(numeric: &str) -> Option<(i128, i32)> {
+    let (digits, exponent) = match numeric.find(['e', 'E']) {
+        Some(index) => {
+            (&numeric[..index], numeric[index + 1..].parse::<i32>().ok()?)
+        }
+        None => (numeric, 0),
+    };
+    let (int_part, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let unsigned = int_part.strip_prefix('-').unwrap_or(int_part);
+    if unsigned.is_empty()
+        || !unsigned
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let mantissa = format!("{}{}", int_part, fraction).parse().ok()?;
+    let exponent = exponent.checked_sub(i32::try_from(fraction.len()).ok()?)?;
+    Some((mantissa, exponent))
+}
+
+/// Remove trailing zeros from a mantissa, adjusting the exponent so the
+/// value does not change.
+fn normalize((mut mantissa, mut exponent): (i128, i32)) -> (i128, i32) {
+    if mantissa == 0 {
+        return (0, 0);
+    }
+    while mantissa % 10 == 0 && exponent < i32::MAX {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    (mantissa, exponent)
+}
+
+/// Split a string into its numeric part and the rest of the string, which
+/// should be the unit. Returns `None` if the string does not start with
+/// a number.
+fn split_unit(s: &str) -> Option<(&str, &str)> {
+    for special in &["-INF", "INF", "NaN"] {
+        if let Some(rest) = s.strip_prefix(special) {
+            if !rest.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+                return Some((&s[..special.len()], rest));
+            }
+        }
+    }
+
+    let bytes = s.as_bytes();
+    let mut end = 0;
+    let skip_digits = |end: &mut usize, allow_underscores: bool| {
+        let start = *end;
+        while *end < bytes.len()
+            && (bytes[*end].is_ascii_digit()
+                || (allow_underscores && *end > start && bytes[*end] == b'_'))
+        {
+            *end += 1;
+        }
+        *end > start
+    };
+
+    if bytes.first() == Some(&b'-') {
+        end += 1;
+    }
+    if !skip_digits(&mut end, true) {
+        return None;
+    }
+    if bytes.get(end) == Some(&b'.') {
+        end += 1;
+        if !skip_digits(&mut end, true) {
+            return None;
+        }
+    }
+    if matches!(bytes.get(end), Some(b'e') | Some(b'E')) {
+        let mut exponent_end = end + 1;
+        if matches!(bytes.get(exponent_end), Some(b'-') | Some(b'+')) {
+            exponent_end += 1;
+        }
+        // An `e` which is not followed by digits is part of the unit:
+        if skip_digits(&mut exponent_end, false) {
+            end = exponent_end;
+        }
+    }
+    Some((&s[..end], &s[end..]))
+}
+
+/// Parse the Axon functions used to represent special numbers, like
+/// `posInf()` or `nan().as("kW")`. Returns `None` if the string is not one
+/// of these functions, or `Some(None)` if it is but it is invalid.
+fn parse_axon_function(s: &str) -> Option<Option<Number>> {
+    let (value, rest) = if let Some(rest) = s.strip_prefix("nan()") {
+        (f64::NAN, rest)
+    } else if let Some(rest) = s.strip_prefix("posInf()") {
+        (f64::INFINITY, rest)
+    } else if let Some(rest) = s.strip_prefix("negInf()") {
+        (f64::NEG_INFINITY, rest)
+    } else {
+        return None;
+    };
+
+    if rest.is_empty() {
+        return Some(Some(Number::new(value, None)));
+    }
+    let unit = rest
+        .strip_prefix(".as(\"")
+        .and_then(|rest| rest.strip_suffix("\")"))
+        .filter(|unit| !unit.is_empty() && unit.chars().all(is_unit_char));
+    Some(unit.map(|unit| Number::new(value, Some(unit.to_owned()))))
+}
+
+/// Return Axon code for a number with the given digits and unit. Units which
+/// cannot be written as part of a number literal are applied with the
+/// `as` function instead.
+fn axon_number(digits: &str, unit: Option<&str>) -> String {
+    match unit {
+        Some(unit) if is_literal_unit(unit) => format!("{}{}", digits, unit),
+        Some(unit) => format!("{}.as({})", digits, zinc_str(unit)),
+        None => digits.to_owned(),
+    }
+}
+
+/// Return true if the unit can directly follow the digits of a number
+/// literal. A leading `e` would be read as an exponent.
+fn is_literal_unit(unit: &str) -> bool {
+    !unit.starts_with(['e', 'E']) && unit.chars().all(is_unit_char)
+}
+
+/// Return true if the character can be part of a number's unit.
+pub(crate) fn is_unit_char(c: char) -> bool {
+    c.is_ascii_alphabetic()
+        || c == '%'
+        || c == '_'
+        || c == '/'
+        || c == '$'
+        || (c as u32) > 127
 }
 
 impl std::fmt::Display for Number {
@@ -10,6 +411,8 @@ impl std::fmt::Display for Number {
         match self {
             Self::Basic(num) => write!(f, "{}", num),
             Self::Scientific(ex) => write!(f, "{}", ex),
+            Self::Integer(int) => write!(f, "{}", int),
+            Self::Decimal(dec) => write!(f, "{}", dec),
         }
     }
 }
@@ -27,6 +430,22 @@ impl Number {
         Self::new(value, None)
     }
 
+    /// Create a new `Number`, returning an error if the unit is not in
+    /// Project Haystack's unit database.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Number;
+    /// assert!(Number::new_checked(72.0, Some("°F".to_owned())).is_ok());
+    /// assert!(Number::new_checked(72.0, Some("degF".to_owned())).is_err());
+    /// ```
+    pub fn new_checked(
+        value: f64,
+        unit: Option<String>,
+    ) -> Result<Self, UnknownUnitError> {
+        Ok(Number::Basic(BasicNumber::new_checked(value, unit)?))
+    }
+
     /// Create a new scientific notation `Number`. If present, the unit should
     /// be a valid unit string from Project Haystack's
     /// unit database.
@@ -42,6 +461,18 @@ impl Number {
         )?))
     }
 
+    /// Create a new scientific notation `Number`, returning an error if the
+    /// unit is not in Project Haystack's unit database. The inner value is
+    /// `None` if the significand is not a finite number.
+    pub fn new_scientific_checked(
+        significand: f64,
+        exponent: i32,
+        unit: Option<String>,
+    ) -> Result<Option<Self>, UnknownUnitError> {
+        Ok(ScientificNumber::new_checked(significand, exponent, unit)?
+            .map(Number::Scientific))
+    }
+
     /// Create a new scientific notation `Number` and no unit.
     pub fn new_scientific_unitless(
         significand: f64,
@@ -50,6 +481,44 @@ impl Number {
         Self::new_scientific(significand, exponent, None)
     }
 
+    /// Create a new exact integer `Number`. If present, the unit should
+    /// be a valid unit string from Project Haystack's
+    /// unit database.
+    pub fn new_integer(value: i128, unit: Option<String>) -> Self {
+        Number::Integer(IntegerNumber::new(value, unit))
+    }
+
+    /// Create a new exact decimal `Number`, equal to
+    /// `mantissa * 10^exponent`. If present, the unit should
+    /// be a valid unit string from Project Haystack's
+    /// unit database.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Number;
+    /// let num = Number::new_decimal(123, 298, None);
+    /// assert_eq!(num.to_axon_code(), "1.23e300");
+    /// ```
+    pub fn new_decimal(
+        mantissa: i128,
+        exponent: i32,
+        unit: Option<String>,
+    ) -> Self {
+        Number::Decimal(DecimalNumber::new(mantissa, exponent, unit))
+    }
+
+    /// Create an exact `Number` from a decimal number string like `-12.5`
+    /// or `1.23e300`. Strings without a fraction or exponent become an
+    /// `IntegerNumber`, and other strings become a `DecimalNumber`.
+    fn from_exact_str(numeric: &str, unit: Option<String>) -> Option<Self> {
+        if numeric.contains(['.', 'e', 'E']) {
+            let (mantissa, exponent) = decimal_parts(numeric)?;
+            Some(Self::new_decimal(mantissa, exponent, unit))
+        } else {
+            Some(Self::new_integer(numeric.parse().ok()?, unit))
+        }
+    }
+
     /// If this represents a non-scientific notation number, return the number.
     pub fn as_number(&self) -> Option<&BasicNumber> {
         match self {
@@ -67,11 +536,391 @@ impl Number {
         }
     }
 
+    /// If this represents an exact integer, return the integer number.
+    pub fn as_integer_number(&self) -> Option<&IntegerNumber> {
+        match self {
+            Self::Integer(int) => Some(int),
+            _ => None,
+        }
+    }
+
+    /// If this represents an exact decimal, return the decimal number.
+    pub fn as_decimal_number(&self) -> Option<&DecimalNumber> {
+        match self {
+            Self::Decimal(dec) => Some(dec),
+            _ => None,
+        }
+    }
+
     /// Return the unit component of this `Number`, if present.
     pub fn unit(&self) -> Option<&str> {
         match self {
             Self::Basic(num) => num.unit(),
             Self::Scientific(ex) => ex.unit(),
+            Self::Integer(int) => int.unit(),
+            Self::Decimal(dec) => dec.unit(),
+        }
+    }
+
+    /// Return the numeric value of this `Number`. Scientific notation
+    /// numbers are evaluated, which may lose precision.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Self::Basic(num) => num.value(),
+            Self::Scientific(ex) => ex.to_f64(),
+            Self::Integer(int) => int.to_f64(),
+            Self::Decimal(dec) => dec.to_f64(),
+        }
+    }
+
+    /// Convert this number into another unit, which can be the name, symbol
+    /// or alias of a unit in Project Haystack's unit database. The
+    /// returned number has the symbol of the new unit.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Number;
+    /// let power = Number::new(2.5, Some("kW".to_owned()));
+    /// let power = power.convert_to("watt").unwrap();
+    /// assert_eq!(power, Number::new(2500.0, Some("W".to_owned())));
+    /// ```
+    pub fn convert_to(&self, unit: &str) -> Result<Self, ConvertUnitError> {
+        match self {
+            Self::Basic(num) => num.convert_to(unit).map(Self::Basic),
+            Self::Scientific(ex) => ex.convert_to(unit).map(Self::Scientific),
+            Self::Integer(int) => {
+                int.to_basic().convert_to(unit).map(Self::Basic)
+            }
+            Self::Decimal(dec) => {
+                dec.to_scientific().convert_to(unit).map(Self::Scientific)
+            }
+        }
+    }
+
+    /// Add two numbers, following the unit rules of Axon. If both numbers
+    /// have units, the units must be the same. If only one number has a unit,
+    /// the result has that unit. If either number is an exact integer or
+    /// decimal, the result is exact too, unless it has too many digits.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Number;
+    /// let a = Number::new(1.5, Some("kWh".to_owned()));
+    /// let b = Number::new_unitless(2.0);
+    /// let sum = a.checked_add(&b).unwrap();
+    /// assert_eq!(sum, Number::new(3.5, Some("kWh".to_owned())));
+    ///
+    /// let c = Number::new(2.0, Some("kW".to_owned()));
+    /// assert!(a.checked_add(&c).is_err());
+    ///
+    /// let counter = Number::new_integer(u64::MAX.into(), None);
+    /// let sum = counter.checked_add(&Number::new_unitless(1.0)).unwrap();
+    /// assert_eq!(sum.to_string(), "18446744073709551616");
+    /// ```
+    pub fn checked_add(
+        &self,
+        other: &Number,
+    ) -> Result<Number, IncompatibleUnitsError> {
+        let unit = self.same_unit("add", other)?;
+        if let Some(sum) = self.exact_sum(other, &unit, i128::checked_add) {
+            return Ok(sum);
+        }
+        Ok(self.sum(other, unit, 1.0))
+    }
+
+    /// Subtract a number from this number, following the unit rules of
+    /// Axon. See `checked_add`.
+    pub fn checked_sub(
+        &self,
+        other: &Number,
+    ) -> Result<Number, IncompatibleUnitsError> {
+        let unit = self.same_unit("subtract", other)?;
+        if let Some(diff) = self.exact_sum(other, &unit, i128::checked_sub) {
+            return Ok(diff);
+        }
+        Ok(self.sum(other, unit, -1.0))
+    }
+
+    /// Multiply two numbers, following the unit rules of Axon. If only one
+    /// number has a unit, the result has that unit. If both numbers have
+    /// units, the result has the unit with the combined dimension, like
+    /// `kWh` for `kW` and `h`, and it is an error if there is no such unit
+    /// in Project Haystack's unit database. When units of several
+    /// quantities have the combined dimension, like `kWh` and `kVAh`, the
+    /// unit written in terms of the two units is used, like `kVAh` for
+    /// `kVA` and `h`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Number;
+    /// let power = Number::new(2.0, Some("kW".to_owned()));
+    /// let time = Number::new(3.0, Some("h".to_owned()));
+    /// let energy = power.checked_mul(&time).unwrap();
+    /// assert_eq!(energy, Number::new(6.0, Some("kWh".to_owned())));
+    /// ```
+    pub fn checked_mul(
+        &self,
+        other: &Number,
+    ) -> Result<Number, IncompatibleUnitsError> {
+        let (unit, factor) =
+            self.derived_unit("multiply", other, Unit::multiply)?;
+        if factor == 1.0 {
+            if let Some(product) = self.exact_product(other, &unit) {
+                return Ok(product);
+            }
+        }
+        Ok(self.combine(other, unit, factor, |a, b| a * b, |a, b| a + b))
+    }
+
+    /// Divide this number by another number, following the unit rules of
+    /// Axon. If only one number has a unit, the result has that unit. If both
+    /// numbers have the same unit, the result has no unit. Otherwise the
+    /// result has the unit with the combined dimension, like `kW` for
+    /// `kWh` and `h`. See `checked_mul`.
+    pub fn checked_div(
+        &self,
+        other: &Number,
+    ) -> Result<Number, IncompatibleUnitsError> {
+        if self.unit().is_some() && self.unit() == other.unit() {
+            return Ok(self.combine(
+                other,
+                None,
+                1.0,
+                |a, b| a / b,
+                |a, b| a - b,
+            ));
+        }
+        let (unit, factor) =
+            self.derived_unit("divide", other, Unit::divide)?;
+        Ok(self.combine(other, unit, factor, |a, b| a / b, |a, b| a - b))
+    }
+
+    /// Return the unit of the result of adding or subtracting two numbers.
+    fn same_unit(
+        &self,
+        operation: &'static str,
+        other: &Number,
+    ) -> Result<Option<String>, IncompatibleUnitsError> {
+        match (self.unit(), other.unit()) {
+            (Some(a), Some(b)) if a != b => {
+                Err(IncompatibleUnitsError::new(operation, a, b))
+            }
+            (Some(unit), _) | (None, Some(unit)) => Ok(Some(unit.to_owned())),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Return the unit of the result of multiplying or dividing two numbers,
+    /// and the factor the result must be multiplied by.
+    fn derived_unit<F>(
+        &self,
+        operation: &'static str,
+        other: &Number,
+        derive: F,
+    ) -> Result<(Option<String>, f64), IncompatibleUnitsError>
+    where
+        F: Fn(&Unit, &Unit) -> Option<(Option<&'static Unit>, f64)>,
+    {
+        match (self.unit(), other.unit()) {
+            (Some(a), Some(b)) => {
+                let derived = match (Unit::find(a), Unit::find(b)) {
+                    (Some(a), Some(b)) => derive(a, b),
+                    _ => None,
+                };
+                match derived {
+                    Some((unit, factor)) => {
+                        Ok((unit.map(|u| u.symbol().to_owned()), factor))
+                    }
+                    None => Err(IncompatibleUnitsError::new(operation, a, b)),
+                }
+            }
+            (Some(unit), None) | (None, Some(unit)) => {
+                Ok((Some(unit.to_owned()), 1.0))
+            }
+            (None, None) => Ok((None, 1.0)),
+        }
+    }
+
+    /// Add or subtract two numbers exactly. Returns `None` if neither number
+    /// is an exact integer or decimal, or if the result has too many digits.
+    fn exact_sum(
+        &self,
+        other: &Number,
+        unit: &Option<String>,
+        op: fn(i128, i128) -> Option<i128>,
+    ) -> Option<Number> {
+        if !self.is_exact() && !other.is_exact() {
+            return None;
+        }
+        let (a, a_exp) = self.exact_parts()?;
+        let (b, b_exp) = other.exact_parts()?;
+        let exponent = a_exp.min(b_exp);
+        let rescale = |mantissa: i128, exp: i32| {
+            let shift = u32::try_from(exp.checked_sub(exponent)?).ok()?;
+            10i128.checked_pow(shift)?.checked_mul(mantissa)
+        };
+        let mantissa = op(rescale(a, a_exp)?, rescale(b, b_exp)?)?;
+        self.exact_result(other, mantissa, exponent, unit)
+    }
+
+    /// Multiply two numbers exactly. Returns `None` if neither number is an
+    /// exact integer or decimal, or if the result has too many digits.
+    fn exact_product(
+        &self,
+        other: &Number,
+        unit: &Option<String>,
+    ) -> Option<Number> {
+        if !self.is_exact() && !other.is_exact() {
+            return None;
+        }
+        let (a, a_exp) = self.exact_parts()?;
+        let (b, b_exp) = other.exact_parts()?;
+        let mantissa = a.checked_mul(b)?;
+        let exponent = a_exp.checked_add(b_exp)?;
+        self.exact_result(other, mantissa, exponent, unit)
+    }
+
+    /// Return the exact result of an operation on two numbers, which is an
+    /// integer if it is a whole number and neither number is a decimal.
+    fn exact_result(
+        &self,
+        other: &Number,
+        mantissa: i128,
+        exponent: i32,
+        unit: &Option<String>,
+    ) -> Option<Number> {
+        let is_decimal = |num: &Number| matches!(num, Self::Decimal(_));
+        if exponent >= 0 && !is_decimal(self) && !is_decimal(other) {
+            let scale = 10i128.checked_pow(u32::try_from(exponent).ok()?)?;
+            let value = mantissa.checked_mul(scale)?;
+            Some(Number::new_integer(value, unit.clone()))
+        } else {
+            Some(Number::new_decimal(mantissa, exponent, unit.clone()))
+        }
+    }
+
+    /// Return the value of this number in a form which can be compared
+    /// and hashed.
+    fn exact_value(&self) -> ExactValue {
+        let parts = match self {
+            Self::Scientific(ex) => {
+                let (mantissa, exponent) =
+                    decimal_parts(&format!("{:e}", ex.significand))
+                        .expect("significands should be finite");
+                Some((mantissa, i64::from(exponent) + i64::from(ex.exponent)))
+            }
+            _ => self
+                .exact_parts()
+                .map(|(mantissa, exponent)| (mantissa, exponent.into())),
+        };
+        match parts {
+            Some((0, _)) => ExactValue::Finite(0, 0),
+            Some((mut mantissa, mut exponent)) => {
+                while mantissa % 10 == 0 {
+                    mantissa /= 10;
+                    exponent += 1;
+                }
+                ExactValue::Finite(mantissa, exponent)
+            }
+            None if self.to_f64().is_nan() => ExactValue::NaN,
+            None if self.to_f64() > 0.0 => ExactValue::PosInf,
+            None => ExactValue::NegInf,
+        }
+    }
+
+    /// Return true if this is an exact integer or decimal number.
+    fn is_exact(&self) -> bool {
+        matches!(self, Self::Integer(_) | Self::Decimal(_))
+    }
+
+    /// Return the digits of this number as an integer mantissa and a power
+    /// of ten exponent. `f64` values are converted using their shortest
+    /// decimal representation. Returns `None` for NaN and infinite values.
+    fn exact_parts(&self) -> Option<(i128, i32)> {
+        match self {
+            Self::Basic(num) if num.value.is_finite() => {
+                decimal_parts(&format!("{:e}", num.value))
+            }
+            Self::Basic(_) => None,
+            Self::Scientific(ex) => {
+                let (mantissa, exponent) =
+                    decimal_parts(&format!("{:e}", ex.significand))?;
+                Some((mantissa, exponent.checked_add(ex.exponent)?))
+            }
+            Self::Integer(int) => Some((int.value, 0)),
+            Self::Decimal(dec) => Some((dec.mantissa, dec.exponent)),
+        }
+    }
+
+    /// Add the other number, multiplied by `sign`, to this number. The
+    /// result is in scientific notation if either number is in scientific
+    /// notation or is a decimal, so that very large or small values do
+    /// not overflow.
+    fn sum(&self, other: &Number, unit: Option<String>, sign: f64) -> Number {
+        if self.is_small() && other.is_small() {
+            return Number::new(self.to_f64() + sign * other.to_f64(), unit);
+        }
+        let (b_sig, b_exp) = other.scientific_parts();
+        let (significand, exponent) =
+            add_scientific(self.scientific_parts(), (sign * b_sig, b_exp));
+        Self::from_scientific_parts(significand, exponent.into(), unit)
+    }
+
+    /// Multiply or divide two numbers. The result is in scientific notation
+    /// if either number is in scientific notation or is a decimal, so that
+    /// very large or small values do not overflow.
+    fn combine(
+        &self,
+        other: &Number,
+        unit: Option<String>,
+        factor: f64,
+        value_op: fn(f64, f64) -> f64,
+        exponent_op: fn(i64, i64) -> i64,
+    ) -> Number {
+        if self.is_small() && other.is_small() {
+            let value = value_op(self.to_f64(), other.to_f64());
+            return Number::new(value * factor, unit);
+        }
+        let (a_sig, a_exp) = self.scientific_parts();
+        let (b_sig, b_exp) = other.scientific_parts();
+        let significand = value_op(a_sig, b_sig) * factor;
+        let exponent = exponent_op(a_exp.into(), b_exp.into());
+        Self::from_scientific_parts(significand, exponent, unit)
+    }
+
+    /// Return true if this number is stored as a plain `f64` or integer.
+    fn is_small(&self) -> bool {
+        matches!(self, Self::Basic(_) | Self::Integer(_))
+    }
+
+    /// Create a number in scientific notation from its parts. If it cannot
+    /// be represented, because the exponent does not fit in an `i32`, it is
+    /// evaluated as an `f64` instead.
+    fn from_scientific_parts(
+        significand: f64,
+        exponent: i64,
+        unit: Option<String>,
+    ) -> Number {
+        let normalized = i32::try_from(exponent).ok().and_then(|exponent| {
+            ScientificNumber::normalized(significand, exponent, unit.clone())
+        });
+        match normalized {
+            Some(num) => Number::Scientific(num),
+            None => {
+                let exponent =
+                    exponent.clamp(i32::MIN.into(), i32::MAX.into()) as i32;
+                Number::new(significand * 10f64.powi(exponent), unit)
+            }
+        }
+    }
+
+    /// Return the significand and exponent of this number.
+    fn scientific_parts(&self) -> (f64, i32) {
+        match self {
+            Self::Basic(num) => (num.value, 0),
+            Self::Scientific(ex) => (ex.significand, ex.exponent),
+            Self::Integer(int) => (int.to_f64(), 0),
+            Self::Decimal(dec) => (dec.mantissa as f64, dec.exponent),
         }
     }
 
@@ -80,6 +929,8 @@ impl Number {
         match self {
             Self::Basic(num) => num.to_axon_code(),
             Self::Scientific(ex) => ex.to_axon_code(),
+            Self::Integer(int) => int.to_axon_code(),
+            Self::Decimal(dec) => dec.to_axon_code(),
         }
     }
 }
@@ -106,6 +957,16 @@ impl BasicNumber {
         Self::new(value, None)
     }
 
+    /// Create a new `BasicNumber`, returning an error if the unit is not in
+    /// Project Haystack's unit database.
+    pub fn new_checked(
+        value: f64,
+        unit: Option<String>,
+    ) -> Result<Self, UnknownUnitError> {
+        check_unit(unit.as_deref())?;
+        Ok(Self::new(value, unit))
+    }
+
     /// Return the numeric component of this number.
     pub fn value(&self) -> f64 {
         self.value
@@ -116,18 +977,25 @@ impl BasicNumber {
         self.unit.as_ref().map(|unit| unit.as_ref())
     }
 
+    /// Convert this number into another unit. See `Number::convert_to`.
+    pub fn convert_to(&self, unit: &str) -> Result<Self, ConvertUnitError> {
+        let (from, to) = find_units(self.unit(), unit)?;
+        let value = from.convert(self.value, to)?;
+        Ok(Self::new(value, Some(to.symbol().to_owned())))
+    }
+
     /// Return a string containing Axon code representing this number.
     pub fn to_axon_code(&self) -> String {
         let value = self.value();
         if let Some(unit) = self.unit() {
             if value.is_nan() {
-                format!("nan().as(\"{}\")", unit)
+                format!("nan().as({})", zinc_str(unit))
             } else if value.is_infinite() && value.is_sign_positive() {
-                format!("posInf().as(\"{}\")", unit)
+                format!("posInf().as({})", zinc_str(unit))
             } else if value.is_infinite() && value.is_sign_negative() {
-                format!("negInf().as(\"{}\")", unit)
+                format!("negInf().as({})", zinc_str(unit))
             } else {
-                format!("{}{}", value, unit)
+                axon_number(&value.to_string(), Some(unit))
             }
         } else {
             if value.is_nan() {
@@ -203,6 +1071,18 @@ impl ScientificNumber {
         }
     }
 
+    /// Create a new `ScientificNumber`, returning an error if the unit is
+    /// not in Project Haystack's unit database. The inner value is `None` if
+    /// the significand is not a finite number.
+    pub fn new_checked(
+        significand: f64,
+        exponent: i32,
+        unit: Option<String>,
+    ) -> Result<Option<Self>, UnknownUnitError> {
+        check_unit(unit.as_deref())?;
+        Ok(Self::new(significand, exponent, unit))
+    }
+
     /// Create a new `ScientificNumber` with no unit. The significand must
     /// be a finite number which is not NaN.
     pub fn new_unitless(significand: f64, exponent: i32) -> Option<Self> {
@@ -224,15 +1104,56 @@ impl ScientificNumber {
         self.unit.as_ref().map(|unit| unit.as_ref())
     }
 
-    /// Return a string containing Axon code representing this number.
-    pub fn to_axon_code(&self) -> String {
-        let exp = self.exponent();
-        let sig = self.significand();
-        if let Some(unit) = self.unit() {
-            format!("{}e{}{}", sig, exp, unit)
+    /// Return the numeric value of this number. The number is evaluated,
+    /// which may lose precision.
+    pub fn to_f64(&self) -> f64 {
+        self.significand * 10f64.powi(self.exponent)
+    }
+
+    /// Convert this number into another unit, keeping it in scientific
+    /// notation. See `Number::convert_to`.
+    pub fn convert_to(&self, unit: &str) -> Result<Self, ConvertUnitError> {
+        let (from, to) = find_units(self.unit(), unit)?;
+        let unit = Some(to.symbol().to_owned());
+        // Only scale the significand, so very large or small numbers
+        // do not overflow:
+        let converted = if from.has_same_offset(to) {
+            let significand = from.convert(self.significand, to)?;
+            Self::normalized(significand, self.exponent, unit)
         } else {
-            format!("{}e{}", sig, exp)
+            // Check that the units are compatible:
+            from.convert(0.0, to)?;
+            let (factor, offset) = from.linear_conversion(to);
+            let scaled = (self.significand * factor, self.exponent);
+            let (significand, exponent) = add_scientific(scaled, (offset, 0));
+            Self::normalized(significand, exponent, unit)
+        };
+        converted.ok_or_else(|| ConvertUnitError::OutOfRange {
+            from: from.symbol().to_owned(),
+            to: to.symbol().to_owned(),
+        })
+    }
+
+    /// Create a new `ScientificNumber`, adjusting the significand so its
+    /// magnitude is between 1 and 10. Returns `None` if the significand is
+    /// not finite, or if the exponent overflows.
+    fn normalized(
+        significand: f64,
+        exponent: i32,
+        unit: Option<String>,
+    ) -> Option<Self> {
+        if significand == 0.0 || !significand.is_finite() {
+            return Self::new(significand, exponent, unit);
         }
+        let shift = significand.abs().log10().floor() as i32;
+        let significand = significand / 10f64.powi(shift);
+        Self::new(significand, exponent.checked_add(shift)?, unit)
+    }
+
+    /// Return a string containing Axon code representing this number.
+    pub fn to_axon_code(&self) -> String {
+        let digits = format!("{}e{}", self.significand(), self.exponent());
+        axon_number(&digits, self.unit())
     }
 }
 
@@ -247,3 +1168,718 @@ impl std::fmt::Display for ScientificNumber {
         }
     }
 }
+
+/// An exact Haystack integer Number, encapsulating a value and an optional
+/// unit value. This is used for integers which cannot be represented
+/// exactly by an `f64`, like large 64-bit counters.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct IntegerNumber {
+    value: i128,
+    unit: Option<String>,
+}
+
+impl IntegerNumber {
+    /// Create a new `IntegerNumber`. If present, the unit should
+    /// be a valid unit string from Project Haystack's
+    /// unit database.
+    pub fn new(value: i128, unit: Option<String>) -> Self {
+        Self { value, unit }
+    }
+
+    /// Create a new `IntegerNumber` with no unit.
+    pub fn new_unitless(value: i128) -> Self {
+        Self::new(value, None)
+    }
+
+    /// Return the numeric component of this number.
+    pub fn value(&self) -> i128 {
+        self.value
+    }
+
+    /// Return the unit component of this number, if present.
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_ref().map(|unit| unit.as_ref())
+    }
+
+    /// Return the value of this number as the nearest `f64`,
+    /// ignoring the unit.
+    pub fn to_f64(&self) -> f64 {
+        self.value as f64
+    }
+
+    /// Return this number as the nearest `BasicNumber`.
+    fn to_basic(&self) -> BasicNumber {
+        BasicNumber::new(self.to_f64(), self.unit.clone())
+    }
+
+    /// Negate this number, returning `None` if the value is `i128::MIN`.
+    pub fn checked_neg(&self) -> Option<Self> {
+        let value = self.value.checked_neg()?;
+        Some(Self::new(value, self.unit.clone()))
+    }
+
+    /// Return a string containing Axon code representing this number.
+    pub fn to_axon_code(&self) -> String {
+        axon_number(&self.value.to_string(), self.unit())
+    }
+}
+
+impl std::fmt::Display for IntegerNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(unit) = self.unit() {
+            write!(f, "{} {}", self.value, unit)
+        } else {
+            write!(f, "{}", self.value)
+        }
+    }
+}
+
+/// An exact Haystack decimal Number, equal to `mantissa * 10^exponent`,
+/// with an optional unit value. This is used for decimals which cannot be
+/// represented exactly by an `f64`, like values with many significant
+/// digits or exponents outside the range of an `f64`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DecimalNumber {
+    mantissa: i128,
+    exponent: i32,
+    unit: Option<String>,
+}
+
+impl DecimalNumber {
+    /// Create a new `DecimalNumber` equal to `mantissa * 10^exponent`.
+    /// If present, the unit should be a valid unit string from
+    /// Project Haystack's unit database. Trailing zeros are removed from
+    /// the mantissa, so numbers with the same value are equal.
+    pub fn new(mantissa: i128, exponent: i32, unit: Option<String>) -> Self {
+        let (mantissa, exponent) = normalize((mantissa, exponent));
+        Self {
+            mantissa,
+            exponent,
+            unit,
+        }
+    }
+
+    /// Create a new `DecimalNumber` with no unit.
+    pub fn new_unitless(mantissa: i128, exponent: i32) -> Self {
+        Self::new(mantissa, exponent, None)
+    }
+
+    /// Return the integer mantissa of this number, which has no trailing
+    /// zeros.
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Return the power of ten exponent of this number.
+    pub fn exponent(&self) -> i32 {
+        self.exponent
+    }
+
+    /// Return the unit component of this number, if present.
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_ref().map(|unit| unit.as_ref())
+    }
+
+    /// Return the value of this number as the nearest `f64`,
+    /// ignoring the unit.
+    pub fn to_f64(&self) -> f64 {
+        format!("{}e{}", self.mantissa, self.exponent)
+            .parse()
+            .expect("a mantissa and exponent should parse as an f64")
+    }
+
+    /// Return this number as the nearest `ScientificNumber`.
+    fn to_scientific(&self) -> ScientificNumber {
+        ScientificNumber::new(
+            self.mantissa as f64,
+            self.exponent,
+            self.unit.clone(),
+        )
+        .expect("an i128 mantissa should be a finite f64")
+    }
+
+    /// Negate this number, returning `None` if the mantissa is `i128::MIN`.
+    pub fn checked_neg(&self) -> Option<Self> {
+        let mantissa = self.mantissa.checked_neg()?;
+        Some(Self {
+            mantissa,
+            exponent: self.exponent,
+            unit: self.unit.clone(),
+        })
+    }
+
+    /// Return the digits of this number, without a unit, in the format used
+    /// by Zinc and Axon. Small exponents are written with a decimal point,
+    /// like `0.1234567890123456789`, and large exponents are written in
+    /// scientific notation, like `1.23e300`.
+    pub(crate) fn to_digits(&self) -> String {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let len = digits.len() as i64;
+        let exponent = i64::from(self.exponent);
+        if (0..=6).contains(&exponent) {
+            let zeros = "0".repeat(exponent as usize);
+            format!("{}{}{}", sign, digits, zeros)
+        } else if exponent < 0 && -exponent < len {
+            let (int_part, fraction) =
+                digits.split_at((len + exponent) as usize);
+            format!("{}{}.{}", sign, int_part, fraction)
+        } else if exponent < 0 && -exponent <= len + 6 {
+            let zeros = "0".repeat((-exponent - len) as usize);
+            format!("{}0.{}{}", sign, zeros, digits)
+        } else {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            let exponent = exponent + len - 1;
+            format!("{}{}{}{}e{}", sign, first, point, rest, exponent)
+        }
+    }
+
+    /// Return a string containing Axon code representing this number.
+    pub fn to_axon_code(&self) -> String {
+        axon_number(&self.to_digits(), self.unit())
+    }
+}
+
+impl std::fmt::Display for DecimalNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(unit) = self.unit() {
+            write!(f, "{} {}", self.to_digits(), unit)
+        } else {
+            write!(f, "{}", self.to_digits())
+        }
+    }
+}
+
+/// An error indicating that a `Number` could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse a Number from the string {unparsable_number}")]
+pub struct ParseNumberError {
+    unparsable_number: String,
+}
+
+impl ParseNumberError {
+    pub(crate) fn from_str(s: &str) -> Self {
+        Self {
+            unparsable_number: s.to_owned(),
+        }
+    }
+}
+
+/// An error indicating that an arithmetic operation could not be done
+/// because the units of the numbers are not compatible.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Cannot {operation} numbers with units {left} and {right}")]
+pub struct IncompatibleUnitsError {
+    operation: &'static str,
+    left: String,
+    right: String,
+}
+
+impl IncompatibleUnitsError {
+    fn new(operation: &'static str, left: &str, right: &str) -> Self {
+        Self {
+            operation,
+            left: left.to_owned(),
+            right: right.to_owned(),
+        }
+    }
+}
+
+/// Find the units to convert between, where the first unit belongs to the
+/// number being converted.
+fn find_units(
+    from: Option<&str>,
+    to: &str,
+) -> Result<(&'static Unit, &'static Unit), ConvertUnitError> {
+    let from = from.ok_or(ConvertUnitError::MissingUnit)?;
+    let find = |unit: &str| {
+        Unit::find(unit)
+            .ok_or_else(|| ConvertUnitError::UnknownUnit(unit.to_owned()))
+    };
+    Ok((find(from)?, find(to)?))
+}
+
+/// Add two numbers in scientific notation, given as significands and
+/// exponents, by aligning their exponents. The result has the larger of
+/// the two exponents.
+fn add_scientific(a: (f64, i32), b: (f64, i32)) -> (f64, i32) {
+    let (large, small) = if a.1 >= b.1 { (a, b) } else { (b, a) };
+    if large.0 == 0.0 {
+        return small;
+    }
+    let shift = i64::from(large.1) - i64::from(small.1);
+    // Beyond this shift, the smaller number underflows to zero:
+    let shift = shift.min(1000) as i32;
+    (large.0 + small.0 / 10f64.powi(shift), large.1)
+}
+
+fn check_unit(unit: Option<&str>) -> Result<(), UnknownUnitError> {
+    match unit {
+        Some(unit) if !is_unit(unit) => {
+            Err(UnknownUnitError::new(unit.to_owned()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DecimalNumber, Number, ScientificNumber};
+    use crate::ConvertUnitError;
+
+    #[test]
+    fn convert_to_works() {
+        let power = Number::new(10.0, Some("kW".to_owned()));
+        let power = power.convert_to("W").unwrap();
+        assert_eq!(power, Number::new(10000.0, Some("W".to_owned())));
+
+        let area = Number::new(100.0, Some("square_meter".to_owned()));
+        let area = area.convert_to("ft²").unwrap();
+        assert_eq!(area.unit(), Some("ft²"));
+        assert!((area.to_f64() - 1076.3910416709722).abs() < 1e-9);
+    }
+
+    #[test]
+    fn convert_to_scientific_works() {
+        let energy =
+            Number::new_scientific(2.5, 12, Some("kWh".to_owned())).unwrap();
+        let energy = energy.convert_to("J").unwrap();
+        let energy = energy.as_scientific_number().unwrap();
+        assert_eq!(energy.exponent(), 18);
+        assert!((energy.significand() - 9.0).abs() < 1e-12);
+        assert_eq!(energy.unit(), Some("J"));
+
+        // Very large numbers should not overflow:
+        let big = ScientificNumber::new(1.0, 306, Some("MWh".to_owned()));
+        let big = big.unwrap().convert_to("J").unwrap();
+        assert_eq!(big.exponent(), 315);
+
+        let temp = Number::new_scientific(1.0, 2, Some("°C".to_owned()));
+        let temp = temp.unwrap().convert_to("°F").unwrap();
+        assert!((temp.to_f64() - 212.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn convert_to_errors_work() {
+        let unitless = Number::new_unitless(1.0);
+        assert_eq!(
+            unitless.convert_to("W"),
+            Err(ConvertUnitError::MissingUnit)
+        );
+
+        let unknown = Number::new(1.0, Some("furlong".to_owned()));
+        assert_eq!(
+            unknown.convert_to("m"),
+            Err(ConvertUnitError::UnknownUnit("furlong".to_owned()))
+        );
+
+        let power = Number::new(1.0, Some("kW".to_owned()));
+        assert_eq!(
+            power.convert_to("kWh"),
+            Err(ConvertUnitError::Incompatible {
+                from: "kW".to_owned(),
+                to: "kWh".to_owned()
+            })
+        );
+
+        let huge = Number::new_scientific(5.0, i32::MAX, Some("kW".to_owned()));
+        assert_eq!(
+            huge.unwrap().convert_to("W"),
+            Err(ConvertUnitError::OutOfRange {
+                from: "kW".to_owned(),
+                to: "W".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn convert_large_temperatures_works() {
+        let temp = Number::new_scientific(5.0, 400, Some("°F".to_owned()));
+        let temp = temp.unwrap().convert_to("°C").unwrap();
+        let temp = temp.as_scientific_number().unwrap();
+        assert_eq!(temp.exponent(), 400);
+        assert!((temp.significand() - 5.0 * 5.0 / 9.0).abs() < 1e-9);
+
+        let temp = Number::new_scientific(5.0, -400, Some("°C".to_owned()));
+        let temp = temp.unwrap().convert_to("°F").unwrap();
+        assert!((temp.to_f64() - 32.0).abs() < 1e-9);
+    }
+
+    fn num(value: f64, unit: &str) -> Number {
+        Number::new(value, Some(unit.to_owned()))
+    }
+
+    #[test]
+    fn add_and_sub_work() {
+        let a = num(10.0, "kWh");
+        assert_eq!(a.checked_add(&num(5.0, "kWh")).unwrap(), num(15.0, "kWh"));
+        assert_eq!(a.checked_sub(&num(15.0, "kWh")).unwrap(), num(-5.0, "kWh"));
+        let b = Number::new_unitless(1.0);
+        assert_eq!(a.checked_add(&b).unwrap(), num(11.0, "kWh"));
+        assert_eq!(b.checked_sub(&a).unwrap(), num(-9.0, "kWh"));
+        assert!(a.checked_add(&num(1.0, "kW")).is_err());
+        assert!(a.checked_sub(&num(1.0, "Wh")).is_err());
+    }
+
+    #[test]
+    fn scientific_add_and_sub_work() {
+        let sci = |sig, exp| Number::new_scientific(sig, exp, None).unwrap();
+        let sum = sci(5.0, 300).checked_add(&sci(1.0, 400)).unwrap();
+        let sum = sum.as_scientific_number().unwrap();
+        assert_eq!(sum.exponent(), 400);
+        assert!((sum.significand() - 1.0).abs() < 1e-9);
+
+        let diff = sci(3.0, 400).checked_sub(&sci(5.0, 399)).unwrap();
+        let diff = diff.as_scientific_number().unwrap();
+        assert_eq!(diff.exponent(), 400);
+        assert!((diff.significand() - 2.5).abs() < 1e-9);
+
+        let diff = Number::new_unitless(1.0).checked_sub(&sci(2.0, -400));
+        assert!((diff.unwrap().to_f64() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exponent_overflow_works() {
+        let sci = |sig, exp| Number::new_scientific(sig, exp, None).unwrap();
+        let product = sci(5.0, i32::MAX).checked_mul(&sci(5.0, 1)).unwrap();
+        assert_eq!(product.to_f64(), f64::INFINITY);
+        let quotient = sci(5.0, i32::MIN).checked_div(&sci(5.0, 1)).unwrap();
+        assert_eq!(quotient.to_f64(), 0.0);
+        let sum = sci(9.0, i32::MAX).checked_add(&sci(9.0, i32::MAX)).unwrap();
+        assert_eq!(sum.to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    fn mul_and_div_work() {
+        let power = num(2.0, "kW");
+        let time = num(30.0, "min");
+        assert!(power.checked_mul(&time).is_err());
+        let time = num(1.5, "h");
+        let energy = power.checked_mul(&time).unwrap();
+        assert_eq!(energy, num(3.0, "kWh"));
+        assert_eq!(energy.checked_div(&time).unwrap(), power);
+
+        let ratio = energy.checked_div(&num(6.0, "kWh")).unwrap();
+        assert_eq!(ratio, Number::new_unitless(0.5));
+        let ratio = num(1.0, "kW").checked_div(&num(500.0, "W")).unwrap();
+        assert_eq!(ratio, Number::new_unitless(2.0));
+
+        let scaled = power.checked_mul(&Number::new_unitless(3.0)).unwrap();
+        assert_eq!(scaled, num(6.0, "kW"));
+        let scaled = Number::new_unitless(3.0).checked_div(&power).unwrap();
+        assert_eq!(scaled, num(1.5, "kW"));
+
+        let area = num(2.0, "m").checked_mul(&num(3.0, "m")).unwrap();
+        assert_eq!(area, num(6.0, "m²"));
+        assert!(num(1.0, "°F").checked_mul(&num(1.0, "h")).is_err());
+    }
+
+    #[test]
+    fn mul_and_div_keep_quantity() {
+        let time = num(3.0, "h");
+        let apparent = num(2.0, "kVA").checked_mul(&time).unwrap();
+        assert_eq!(apparent, num(6.0, "kVAh"));
+        assert_eq!(apparent.checked_div(&time).unwrap(), num(2.0, "kVA"));
+        let reactive = time.checked_mul(&num(2.0, "kvar")).unwrap();
+        assert_eq!(reactive, num(6.0, "kvarh"));
+
+        let energy = num(6.0, "kWh").checked_div(&num(2.0, "m²")).unwrap();
+        assert_eq!(energy, num(3.0, "kWh/m²"));
+
+        // Pressure, energy by volume and power by volumetric flow all have
+        // the same dimension, and none of them is written as `N/m²`:
+        assert!(num(1.0, "N").checked_div(&num(1.0, "m²")).is_err());
+    }
+
+    #[test]
+    fn scientific_arithmetic_works() {
+        let a = Number::new_scientific_unitless(5.0, 300).unwrap();
+        let b = Number::new_scientific(4.0, 200, Some("W".to_owned())).unwrap();
+        let product = a.checked_mul(&b).unwrap();
+        let product = product.as_scientific_number().unwrap();
+        assert!((product.significand() - 2.0).abs() < 1e-12);
+        assert_eq!(product.exponent(), 501);
+        assert_eq!(product.unit(), Some("W"));
+
+        let quotient = b.checked_div(&a).unwrap();
+        let quotient = quotient.as_scientific_number().unwrap();
+        assert!((quotient.significand() - 8.0).abs() < 1e-12);
+        assert_eq!(quotient.exponent(), -101);
+    }
+
+    #[test]
+    fn special_values_propagate() {
+        let inf = num(f64::INFINITY, "kW");
+        let nan = Number::new_unitless(f64::NAN);
+        let one = num(1.0, "kW");
+        assert_eq!(inf.checked_add(&one).unwrap(), inf);
+        assert_eq!(inf.checked_sub(&inf).unwrap().unit(), Some("kW"));
+        assert!(inf.checked_sub(&inf).unwrap().to_f64().is_nan());
+        assert!(nan.checked_mul(&one).unwrap().to_f64().is_nan());
+        let neg_inf = one.checked_div(&Number::new_unitless(-0.0)).unwrap();
+        assert_eq!(neg_inf, num(f64::NEG_INFINITY, "kW"));
+    }
+
+    #[test]
+    fn neg_works() {
+        assert_eq!(-num(1.0, "kW"), num(-1.0, "kW"));
+        let sci = Number::new_scientific_unitless(1.5, 10).unwrap();
+        let neg = Number::new_scientific_unitless(-1.5, 10).unwrap();
+        assert_eq!(-sci, neg);
+    }
+
+    #[test]
+    fn parse_works() {
+        let parse = |s: &str| s.parse::<Number>().unwrap();
+        assert_eq!(parse("72.5°F"), num(72.5, "°F"));
+        assert_eq!(parse("-3"), Number::new_unitless(-3.0));
+        assert_eq!(parse("1_000.5kWh"), num(1000.5, "kWh"));
+        assert_eq!(parse("100ft²"), num(100.0, "ft²"));
+        assert_eq!(parse("5 m/s"), num(5.0, "m/s"));
+        assert_eq!(parse("5/min"), num(5.0, "/min"));
+        assert_eq!(parse("5em"), num(5.0, "em"));
+        assert_eq!(
+            parse("1.2e-3kW"),
+            Number::new_scientific(1.2, -3, Some("kW".to_owned())).unwrap()
+        );
+        assert_eq!(
+            parse("6E+23"),
+            Number::new_scientific_unitless(6.0, 23).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_special_values_works() {
+        let parse = |s: &str| s.parse::<Number>().unwrap();
+        assert_eq!(parse("INF"), Number::new_unitless(f64::INFINITY));
+        assert_eq!(parse("-INF"), Number::new_unitless(f64::NEG_INFINITY));
+        assert!(parse("NaN").to_f64().is_nan());
+        assert_eq!(parse("INF kW"), num(f64::INFINITY, "kW"));
+        assert_eq!(parse("posInf()"), Number::new_unitless(f64::INFINITY));
+        assert_eq!(parse("negInf().as(\"kW\")"), num(f64::NEG_INFINITY, "kW"));
+        let nan = parse("nan().as(\"kW\")");
+        assert!(nan.to_f64().is_nan());
+        assert_eq!(nan.unit(), Some("kW"));
+    }
+
+    #[test]
+    fn parse_round_trip_works() {
+        let numbers = vec![
+            num(72.5, "°F"),
+            Number::new_unitless(-0.25),
+            num(f64::NEG_INFINITY, "kW"),
+            Number::new_scientific(1.5, -7, Some("m³".to_owned())).unwrap(),
+        ];
+        for number in numbers {
+            assert_eq!(number.to_string().parse::<Number>().unwrap(), number);
+            assert_eq!(
+                number.to_axon_code().parse::<Number>().unwrap(),
+                number
+            );
+        }
+    }
+
+    #[test]
+    fn parse_invalid_fails() {
+        let invalid = vec![
+            "",
+            "kW",
+            "1.",
+            ".5",
+            "--1",
+            "1.2.3",
+            "5 k W",
+            "5k1",
+            "INFkW",
+            "nan().as(kW)",
+            "nan().as(\"\")",
+            "_1",
+        ];
+        for s in invalid {
+            assert!(s.parse::<Number>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_exact_works() {
+        let parse = |s: &str| s.parse::<Number>().unwrap();
+        let counter = parse("18446744073709551615Wh");
+        assert_eq!(
+            counter,
+            Number::new_integer(u64::MAX.into(), Some("Wh".to_owned()))
+        );
+        assert_eq!(
+            parse("-12345678901234567.891"),
+            Number::new_decimal(-12345678901234567891, -3, None)
+        );
+        assert_eq!(
+            parse("1.2345678901234567891e-300"),
+            Number::new_decimal(12345678901234567891, -319, None)
+        );
+        // Numbers which an f64 represents exactly are not exact numbers:
+        assert_eq!(
+            parse("9007199254740992"),
+            Number::new_unitless(9.007199254740992e15)
+        );
+        assert_eq!(parse("0.1"), Number::new_unitless(0.1));
+        assert_eq!(
+            parse("1.23e300"),
+            Number::new_scientific_unitless(1.23, 300).unwrap()
+        );
+    }
+
+    #[test]
+    fn exact_round_trip_works() {
+        let numbers = vec![
+            Number::new_integer(u64::MAX.into(), Some("Wh".to_owned())),
+            Number::new_integer(i64::MIN.into(), None),
+            Number::new_decimal(-12345678901234567891, -3, None),
+            Number::new_decimal(
+                12345678901234567891,
+                -25,
+                Some("m".to_owned()),
+            ),
+            Number::new_decimal(12345678901234567891, 400, None),
+        ];
+        for number in numbers {
+            assert_eq!(number.to_string().parse::<Number>().unwrap(), number);
+            assert_eq!(
+                number.to_axon_code().parse::<Number>().unwrap(),
+                number
+            );
+        }
+    }
+
+    #[test]
+    fn decimal_digits_work() {
+        let digits = |mantissa, exponent| {
+            DecimalNumber::new_unitless(mantissa, exponent).to_string()
+        };
+        assert_eq!(digits(1500, 0), "1500");
+        assert_eq!(digits(123, 3), "123000");
+        assert_eq!(digits(-125, -1), "-12.5");
+        assert_eq!(digits(125, -5), "0.00125");
+        assert_eq!(digits(123, 298), "1.23e300");
+        assert_eq!(digits(-5, -20), "-5e-20");
+        assert_eq!(digits(0, 10), "0");
+        assert_eq!(
+            DecimalNumber::new(120, -1, Some("kW".to_owned())).to_string(),
+            "12 kW"
+        );
+        assert_eq!(DecimalNumber::new(120, -1, None).exponent(), 0);
+        let big = DecimalNumber::new_unitless(123, 298);
+        assert_eq!(big.to_f64(), 1.23e300);
+    }
+
+    #[test]
+    fn exact_neg_never_overflows() {
+        let int = Number::new_integer(-42, Some("Wh".to_owned()));
+        assert_eq!(-int, Number::new_integer(42, Some("Wh".to_owned())));
+        let dec = Number::new_decimal(-15, -1, None);
+        assert_eq!(-dec, Number::new_decimal(15, -1, None));
+
+        let min = -Number::new_integer(i128::MIN, Some("Wh".to_owned()));
+        assert!(!min.is_exact());
+        assert_eq!(min.to_f64(), 2f64.powi(127));
+        assert_eq!(min.unit(), Some("Wh"));
+        let min = -Number::new_decimal(i128::MIN, -2, None);
+        assert!(!min.is_exact());
+        assert!(min.to_f64() > 0.0);
+    }
+
+    #[test]
+    fn exact_arithmetic_works() {
+        let counter =
+            Number::new_integer(u64::MAX.into(), Some("Wh".to_owned()));
+        let one = num(1.0, "Wh");
+        let sum = counter.checked_add(&one).unwrap();
+        assert_eq!(
+            sum,
+            Number::new_integer(
+                i128::from(u64::MAX) + 1,
+                Some("Wh".to_owned())
+            )
+        );
+        assert_eq!(
+            sum.checked_sub(&counter).unwrap(),
+            Number::new_integer(1, Some("Wh".to_owned()))
+        );
+        assert!(counter.checked_add(&num(1.0, "kWh")).is_err());
+
+        let half = Number::new_unitless(0.5);
+        assert_eq!(
+            counter.checked_mul(&half).unwrap(),
+            Number::new_decimal(
+                i128::from(u64::MAX) * 5,
+                -1,
+                Some("Wh".to_owned())
+            )
+        );
+        let tenth = Number::new_decimal(1, -1, None);
+        assert_eq!(
+            tenth.checked_add(&Number::new_unitless(0.2)).unwrap(),
+            Number::new_decimal(3, -1, None)
+        );
+        assert_eq!(-tenth, Number::new_decimal(-1, -1, None));
+
+        let kwh = counter.convert_to("kWh").unwrap();
+        assert!((kwh.to_f64() - u64::MAX as f64 / 1000.0).abs() < 1e3);
+    }
+
+    #[test]
+    fn eq_and_ord_work() {
+        let nan = Number::new_unitless(f64::NAN);
+        assert_eq!(nan, Number::new_unitless(f64::NAN));
+        assert_eq!(num(0.0, "kW"), num(-0.0, "kW"));
+        assert_eq!(
+            Number::new_scientific_unitless(1.23, 300).unwrap(),
+            Number::new_decimal(123, 298, None)
+        );
+        assert_eq!(
+            num(100.0, "kW"),
+            Number::new_integer(100, Some("kW".to_owned()))
+        );
+        assert_ne!(num(100.0, "kW"), num(100.0, "W"));
+
+        let mut numbers = [
+            num(1.0, "kW"),
+            nan,
+            Number::new_unitless(f64::INFINITY),
+            Number::new_decimal(-1, 400, None),
+            Number::new_unitless(-2.5),
+            Number::new_scientific_unitless(-2.4, 0).unwrap(),
+            Number::new_integer(u64::MAX.into(), None),
+            Number::new_decimal(18446744073709551615, -1, None),
+        ];
+        numbers.sort();
+        let sorted: Vec<String> =
+            numbers.iter().map(|number| number.to_string()).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "-1e400",
+                "-2.5",
+                "-2.4e0",
+                "1844674407370955161.5",
+                "18446744073709551615",
+                "INF",
+                "NaN",
+                "1 kW",
+            ]
+        );
+    }
+
+    #[test]
+    fn extreme_exponents_compare_by_value() {
+        let sci = |sig, exp| Number::new_scientific_unitless(sig, exp).unwrap();
+        let zero = Number::new_unitless(0.0);
+        assert_ne!(sci(1.5, i32::MIN), zero);
+        assert_ne!(sci(1.5, i32::MIN), sci(2.5, i32::MIN));
+        assert_eq!(sci(15.0, i32::MAX - 1), sci(1.5, i32::MAX));
+        assert!(sci(-1.5, i32::MIN) < zero);
+        assert!(zero < sci(1.5, i32::MIN));
+        assert!(sci(1.5, i32::MIN) < sci(2.5, i32::MIN));
+        assert!(sci(9.0, i32::MAX) < Number::new_unitless(f64::INFINITY));
+    }
+}
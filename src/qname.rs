@@ -1,16 +1,105 @@
-#[derive(Clone, Debug, PartialEq)]
+use crate::axon::is_axon_keyword;
+use crate::{is_tag_name, Expr, TagName, Value};
+use thiserror::Error;
+
+/// A qualified name of an Axon function, like `core::parseNumber`. The
+/// library is optional, so `parseNumber` is also a valid `Qname`. The
+/// library and the name must both be valid Axon identifiers, which follow
+/// the same rules as tag names but cannot be Axon keywords like `do`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Qname(String);
 
-/// A qualified name, like `core::parseNumber`.
 impl Qname {
-    pub fn new(s: String) -> Self {
-        Self(s)
+    /// Create a new `Qname`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Qname;
+    /// let qname = Qname::new("core::parseNumber".to_owned()).unwrap();
+    /// assert_eq!(qname.lib(), Some("core"));
+    /// assert_eq!(qname.name(), "parseNumber");
+    /// assert!(Qname::new("core::".to_owned()).is_err());
+    /// assert!(Qname::new("do".to_owned()).is_err());
+    /// ```
+    pub fn new(s: String) -> Result<Self, ParseQnameError> {
+        if Self::is_valid_qname(&s) {
+            Ok(Self(s))
+        } else {
+            Err(ParseQnameError::from_string(s))
+        }
+    }
+
+    /// Create a new `Qname` without a library, like `parseNumber`. Returns
+    /// an error if the name is an Axon keyword.
+    pub fn new_unqualified(name: TagName) -> Result<Self, ParseQnameError> {
+        Self::new(name.into_string())
+    }
+
+    /// Create a new `Qname` from a library and a name, like
+    /// `core::parseNumber`. Returns an error if either part is an Axon
+    /// keyword.
+    pub fn new_qualified(
+        lib: TagName,
+        name: TagName,
+    ) -> Result<Self, ParseQnameError> {
+        Self::new(format!("{}::{}", lib, name))
     }
 
     /// Return a string slice containing the contents of this `Qname`.
     pub fn qname(&self) -> &str {
         &self.0
     }
+
+    /// Return the library of this `Qname`, if it has one.
+    pub fn lib(&self) -> Option<&str> {
+        self.0.split_once("::").map(|(lib, _)| lib)
+    }
+
+    /// Return the name of this `Qname`, without the library.
+    pub fn name(&self) -> &str {
+        match self.0.split_once("::") {
+            Some((_, name)) => name,
+            None => &self.0,
+        }
+    }
+
+    /// Convert this `Qname` into a string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Return Axon code which calls this function with the arguments.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Qname, Value};
+    /// let qname = Qname::new("core::parseNumber".to_owned()).unwrap();
+    /// let args = vec![Value::Str("1\"kW".to_owned()), Value::Bool(false)];
+    /// assert_eq!(
+    ///     qname.to_axon_call(&args),
+    ///     "core::parseNumber(\"1\\\"kW\", false)"
+    /// );
+    /// ```
+    pub fn to_axon_call(&self, args: &[Value]) -> String {
+        let args = args.iter().cloned().map(Expr::literal).collect();
+        Expr::call(self.clone(), args).to_axon_code()
+    }
+
+    /// Return true if the string is a valid `Qname`.
+    pub(crate) fn is_valid_qname(s: &str) -> bool {
+        match s.split_once("::") {
+            Some((lib, name)) => is_ident(lib) && is_ident(name),
+            None => is_ident(s),
+        }
+    }
+}
+
+impl std::str::FromStr for Qname {
+    type Err = ParseQnameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.to_owned())
+    }
 }
 
 impl std::fmt::Display for Qname {
@@ -18,3 +107,85 @@ impl std::fmt::Display for Qname {
         write!(f, "{}", self.qname())
     }
 }
+
+impl std::convert::AsRef<str> for Qname {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::convert::TryFrom<TagName> for Qname {
+    type Error = ParseQnameError;
+
+    fn try_from(name: TagName) -> Result<Self, Self::Error> {
+        Self::new_unqualified(name)
+    }
+}
+
+/// Return true if the string is a valid part of a `Qname`.
+fn is_ident(s: &str) -> bool {
+    is_tag_name(s) && !is_axon_keyword(s)
+}
+
+/// An error indicating that a `Qname` could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse a Qname from the string {unparsable_qname}")]
+pub struct ParseQnameError {
+    unparsable_qname: String,
+}
+
+impl ParseQnameError {
+    pub(crate) fn from_string(s: String) -> Self {
+        Self {
+            unparsable_qname: s,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Qname;
+    use crate::TagName;
+
+    #[test]
+    fn parse_qname() {
+        assert!(Qname::is_valid_qname("parseNumber"));
+        assert!(Qname::is_valid_qname("core::parseNumber"));
+        assert!(Qname::is_valid_qname("my_lib2::a"));
+        assert!(!Qname::is_valid_qname(""));
+        assert!(!Qname::is_valid_qname("::parseNumber"));
+        assert!(!Qname::is_valid_qname("core::"));
+        assert!(!Qname::is_valid_qname("a::b::c"));
+        assert!(!Qname::is_valid_qname("Core::parseNumber"));
+        assert!(!Qname::is_valid_qname("core::parse-number"));
+        assert!(!Qname::is_valid_qname("core: :parseNumber"));
+        assert!(!Qname::is_valid_qname("do"));
+        assert!(!Qname::is_valid_qname("core::return"));
+        assert!(!Qname::is_valid_qname("if::now"));
+    }
+
+    #[test]
+    fn parts_work() {
+        let lib = TagName::new("core".to_owned()).unwrap();
+        let name = TagName::new("now".to_owned()).unwrap();
+        let qualified = Qname::new_qualified(lib, name.clone()).unwrap();
+        assert_eq!(qualified, "core::now".parse().unwrap());
+        assert_eq!(qualified.lib(), Some("core"));
+        assert_eq!(qualified.name(), "now");
+
+        let unqualified = Qname::new_unqualified(name).unwrap();
+        assert_eq!(unqualified.qname(), "now");
+        assert_eq!(unqualified.lib(), None);
+        assert_eq!(unqualified.name(), "now");
+        assert_ne!(qualified, unqualified);
+
+        let keyword = TagName::new("end".to_owned()).unwrap();
+        assert!(Qname::new_unqualified(keyword).is_err());
+    }
+
+    #[test]
+    fn to_axon_call_works() {
+        let qname: Qname = "readAll".parse().unwrap();
+        assert_eq!(qname.to_axon_call(&[]), "readAll()");
+    }
+}
@@ -0,0 +1,491 @@
+use crate::{Date, DateTime, Time, TimeZone, Xstr};
+use chrono::{Datelike, Days};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// The `Xstr` type name used by SkySpark for spans.
+const XSTR_TYPE: &str = "Span";
+
+/// A SkySpark Span, representing a range of time. SkySpark encodes spans
+/// as an `Xstr`, like `Span("2024-01-01,2024-01-31")` or `Span("today")`.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::{Date, Span, SpanMode, TimeZone};
+/// let span: Span = "lastMonth".parse().unwrap();
+/// assert_eq!(span, Span::Relative(SpanMode::LastMonth));
+///
+/// let tz = TimeZone::new("New_York").unwrap();
+/// let today = Date::new(2024, 3, 15).unwrap();
+/// let (start, end) = span.resolve_at(&tz, today).unwrap();
+/// assert_eq!(start.to_string(), "2024-02-01T00:00:00-05:00 New_York");
+/// assert_eq!(end.to_string(), "2024-03-01T00:00:00-05:00 New_York");
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Span {
+    /// Whole days, from the start of the first date to the end of the
+    /// last date.
+    Dates { first: Date, last: Date },
+    /// The time from the start date time (inclusive) to the end date
+    /// time (exclusive).
+    DateTimes { start: DateTime, end: DateTime },
+    /// A span which is relative to the current date.
+    Relative(SpanMode),
+}
+
+impl Span {
+    /// Create a new `Span` of whole days, from the first date to the last
+    /// date inclusive. Returns `None` if the last date is before
+    /// the first date.
+    pub fn new_dates(first: Date, last: Date) -> Option<Self> {
+        if first <= last {
+            Some(Self::Dates { first, last })
+        } else {
+            None
+        }
+    }
+
+    /// Create a new `Span` from the start date time (inclusive) to the end
+    /// date time (exclusive). Returns `None` if the end is before the start.
+    pub fn new_date_times(start: DateTime, end: DateTime) -> Option<Self> {
+        if start.date_time() <= end.date_time() {
+            Some(Self::DateTimes { start, end })
+        } else {
+            None
+        }
+    }
+
+    /// Return the start (inclusive) and end (exclusive) of this span in the
+    /// given timezone, resolving relative spans using the current date
+    /// in that timezone. See `resolve_at`.
+    pub fn resolve(
+        &self,
+        time_zone: &TimeZone,
+    ) -> Option<(DateTime, DateTime)> {
+        self.resolve_at(time_zone, time_zone.now().date())
+    }
+
+    /// Return the start (inclusive) and end (exclusive) of this span in the
+    /// given timezone, resolving relative spans as if the current date
+    /// was `today`. Spans of whole days start at midnight. Returns `None`
+    /// if the span ends after the latest date which can be represented.
+    pub fn resolve_at(
+        &self,
+        time_zone: &TimeZone,
+        today: Date,
+    ) -> Option<(DateTime, DateTime)> {
+        let (first, last) = match self {
+            Self::Dates { first, last } => (*first, *last),
+            Self::DateTimes { start, end } => {
+                return Some((
+                    start.to_time_zone(time_zone)?,
+                    end.to_time_zone(time_zone)?,
+                ))
+            }
+            Self::Relative(mode) => mode.dates(today)?,
+        };
+        let after_last = add_days(last, 1)?;
+        Some((
+            start_of_day(time_zone, first)?,
+            start_of_day(time_zone, after_last)?,
+        ))
+    }
+
+    /// Return this span as an `Xstr`, which is how SkySpark encodes spans.
+    pub fn to_xstr(&self) -> Xstr {
+        Xstr::new(XSTR_TYPE.to_owned(), self.to_string())
+            .expect("Span should be a valid Xstr type name")
+    }
+
+    /// Parse a span from an `Xstr` with the type `Span`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Date, Span, Xstr};
+    /// let xstr = Xstr::new("Span".to_owned(), "2024-01-01,2024-01-31".to_owned()).unwrap();
+    /// let span = Span::from_xstr(&xstr).unwrap();
+    /// let first = Date::new(2024, 1, 1).unwrap();
+    /// let last = Date::new(2024, 1, 31).unwrap();
+    /// assert_eq!(span, Span::new_dates(first, last).unwrap());
+    /// assert_eq!(span.to_xstr(), xstr);
+    /// ```
+    pub fn from_xstr(xstr: &Xstr) -> Result<Self, ParseSpanError> {
+        if xstr.type_name() == XSTR_TYPE {
+            xstr.value().parse()
+        } else {
+            Err(ParseSpanError::from_str(&xstr.to_string()))
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dates { first, last } if first == last => {
+                write!(f, "{}", first)
+            }
+            Self::Dates { first, last } => write!(f, "{},{}", first, last),
+            Self::DateTimes { start, end } => write!(f, "{},{}", start, end),
+            Self::Relative(mode) => write!(f, "{}", mode),
+        }
+    }
+}
+
+impl std::str::FromStr for Span {
+    type Err = ParseSpanError;
+
+    /// Parse a span from the value of a SkySpark span `Xstr`, which is
+    /// either a span mode like `today`, a date like `2024-01-01`, two dates
+    /// like `2024-01-01,2024-01-31`, or two date times separated
+    /// by a comma.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseSpanError::from_str(s);
+
+        if let Ok(mode) = s.parse() {
+            return Ok(Self::Relative(mode));
+        }
+        if let Ok(date) = s.parse() {
+            return Ok(Self::Dates {
+                first: date,
+                last: date,
+            });
+        }
+
+        let (start, end) = s.split_once(',').ok_or_else(err)?;
+        if let (Ok(first), Ok(last)) = (start.parse(), end.parse()) {
+            return Self::new_dates(first, last).ok_or_else(err);
+        }
+        match (start.parse(), end.parse()) {
+            (Ok(start), Ok(end)) => {
+                Self::new_date_times(start, end).ok_or_else(err)
+            }
+            _ => Err(err()),
+        }
+    }
+}
+
+impl std::convert::From<Span> for Xstr {
+    fn from(span: Span) -> Self {
+        span.to_xstr()
+    }
+}
+
+/// A SkySpark span mode, which describes a span relative to the
+/// current date. Weeks start on Sunday.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SpanMode {
+    /// The current day.
+    Today,
+    /// The day before the current day.
+    Yesterday,
+    /// The week containing the current day.
+    ThisWeek,
+    /// The month containing the current day.
+    ThisMonth,
+    /// The quarter containing the current day.
+    ThisQuarter,
+    /// The year containing the current day.
+    ThisYear,
+    /// The 7 days up to and including the current day.
+    PastWeek,
+    /// The 30 days up to and including the current day.
+    PastMonth,
+    /// The 365 days up to and including the current day.
+    PastYear,
+    /// The week before the current week.
+    LastWeek,
+    /// The month before the current month.
+    LastMonth,
+    /// The quarter before the current quarter.
+    LastQuarter,
+    /// The year before the current year.
+    LastYear,
+}
+
+impl SpanMode {
+    const ALL: [SpanMode; 13] = [
+        Self::Today,
+        Self::Yesterday,
+        Self::ThisWeek,
+        Self::ThisMonth,
+        Self::ThisQuarter,
+        Self::ThisYear,
+        Self::PastWeek,
+        Self::PastMonth,
+        Self::PastYear,
+        Self::LastWeek,
+        Self::LastMonth,
+        Self::LastQuarter,
+        Self::LastYear,
+    ];
+
+    /// Return the name of this mode, as used by SkySpark, like `thisWeek`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Today => "today",
+            Self::Yesterday => "yesterday",
+            Self::ThisWeek => "thisWeek",
+            Self::ThisMonth => "thisMonth",
+            Self::ThisQuarter => "thisQuarter",
+            Self::ThisYear => "thisYear",
+            Self::PastWeek => "pastWeek",
+            Self::PastMonth => "pastMonth",
+            Self::PastYear => "pastYear",
+            Self::LastWeek => "lastWeek",
+            Self::LastMonth => "lastMonth",
+            Self::LastQuarter => "lastQuarter",
+            Self::LastYear => "lastYear",
+        }
+    }
+
+    /// Return the first and last dates (inclusive) of this mode, if the
+    /// current date was `today`. Returns `None` if the dates are outside
+    /// the range of dates which can be represented.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Date, SpanMode};
+    /// let today = Date::new(2024, 5, 15).unwrap();
+    /// let (first, last) = SpanMode::LastQuarter.dates(today).unwrap();
+    /// assert_eq!(first, Date::new(2024, 1, 1).unwrap());
+    /// assert_eq!(last, Date::new(2024, 3, 31).unwrap());
+    /// ```
+    pub fn dates(&self, today: Date) -> Option<(Date, Date)> {
+        let date = today.naive_date();
+        let year = date.year();
+        let month = date.month0() as i32;
+        let quarter = month - month % 3;
+        let week =
+            add_days(today, -(date.weekday().num_days_from_sunday() as i64))?;
+        let months = |first: i32, count: i32| {
+            let after_last = month_start(year, first + count)?;
+            Some((month_start(year, first)?, add_days(after_last, -1)?))
+        };
+
+        match self {
+            Self::Today => Some((today, today)),
+            Self::Yesterday => {
+                let yesterday = add_days(today, -1)?;
+                Some((yesterday, yesterday))
+            }
+            Self::ThisWeek => Some((week, add_days(week, 6)?)),
+            Self::ThisMonth => months(month, 1),
+            Self::ThisQuarter => months(quarter, 3),
+            Self::ThisYear => months(0, 12),
+            Self::PastWeek => Some((add_days(today, -6)?, today)),
+            Self::PastMonth => Some((add_days(today, -29)?, today)),
+            Self::PastYear => Some((add_days(today, -364)?, today)),
+            Self::LastWeek => Some((add_days(week, -7)?, add_days(week, -1)?)),
+            Self::LastMonth => months(month - 1, 1),
+            Self::LastQuarter => months(quarter - 3, 3),
+            Self::LastYear => months(-12, 12),
+        }
+    }
+}
+
+impl std::fmt::Display for SpanMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for SpanMode {
+    type Err = ParseSpanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|mode| mode.name() == s)
+            .copied()
+            .ok_or_else(|| ParseSpanError::from_str(s))
+    }
+}
+
+/// Return the first day of a month, where the month is counted from
+/// January of the given year and may be negative or greater than 11.
+/// Returns `None` if the date cannot be represented.
+fn month_start(year: i32, month0: i32) -> Option<Date> {
+    let year = year.checked_add(month0.div_euclid(12))?;
+    let month = month0.rem_euclid(12) as u32 + 1;
+    Date::new(year, month, 1)
+}
+
+/// Add a number of days to a date, which may be negative. Returns `None`
+/// if the date cannot be represented.
+fn add_days(date: Date, days: i64) -> Option<Date> {
+    let naive = date.naive_date();
+    let added = if days >= 0 {
+        naive.checked_add_days(Days::new(days as u64))
+    } else {
+        naive.checked_sub_days(Days::new(days.unsigned_abs()))
+    };
+    added.and_then(|date| Date::try_from(date).ok())
+}
+
+/// Return the start of a day in a timezone. This is midnight, unless
+/// midnight was skipped because clocks went forward, in which case it is
+/// the first hour of the day which exists. Returns `None` if the date time
+/// cannot be represented.
+fn start_of_day(time_zone: &TimeZone, date: Date) -> Option<DateTime> {
+    (0..24)
+        .filter_map(|hour| Time::new(hour, 0, 0))
+        .find_map(|time| time_zone.date_time(date, time))
+}
+
+/// An error indicating that a `Span` could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse a Span from the string {unparsable_span}")]
+pub struct ParseSpanError {
+    unparsable_span: String,
+}
+
+impl ParseSpanError {
+    pub(crate) fn from_str(s: &str) -> Self {
+        Self {
+            unparsable_span: s.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Span, SpanMode};
+    use crate::{Date, TimeZone, Xstr};
+
+    fn date(y: i32, m: u32, d: u32) -> Date {
+        Date::new(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn mode_dates_work() {
+        // A Wednesday:
+        let today = date(2024, 1, 3);
+        let cases = [
+            (SpanMode::Today, date(2024, 1, 3), date(2024, 1, 3)),
+            (SpanMode::Yesterday, date(2024, 1, 2), date(2024, 1, 2)),
+            (SpanMode::ThisWeek, date(2023, 12, 31), date(2024, 1, 6)),
+            (SpanMode::ThisMonth, date(2024, 1, 1), date(2024, 1, 31)),
+            (SpanMode::ThisQuarter, date(2024, 1, 1), date(2024, 3, 31)),
+            (SpanMode::ThisYear, date(2024, 1, 1), date(2024, 12, 31)),
+            (SpanMode::PastWeek, date(2023, 12, 28), date(2024, 1, 3)),
+            (SpanMode::PastMonth, date(2023, 12, 5), date(2024, 1, 3)),
+            (SpanMode::PastYear, date(2023, 1, 4), date(2024, 1, 3)),
+            (SpanMode::LastWeek, date(2023, 12, 24), date(2023, 12, 30)),
+            (SpanMode::LastMonth, date(2023, 12, 1), date(2023, 12, 31)),
+            (SpanMode::LastQuarter, date(2023, 10, 1), date(2023, 12, 31)),
+            (SpanMode::LastYear, date(2023, 1, 1), date(2023, 12, 31)),
+        ];
+        for (mode, first, last) in cases.iter() {
+            assert_eq!(mode.dates(today), Some((*first, *last)), "{}", mode);
+        }
+        assert_eq!(
+            SpanMode::LastMonth.dates(date(2024, 3, 31)),
+            Some((date(2024, 2, 1), date(2024, 2, 29)))
+        );
+    }
+
+    #[test]
+    fn out_of_range_dates_work() {
+        let last = date(9999, 12, 31);
+        assert_eq!(SpanMode::ThisYear.dates(last), None);
+        assert_eq!(SpanMode::ThisWeek.dates(last), None);
+        assert_eq!(SpanMode::Today.dates(last), Some((last, last)));
+
+        let tz = TimeZone::new("UTC").unwrap();
+        assert!(Span::Relative(SpanMode::Today)
+            .resolve_at(&tz, last)
+            .is_none());
+        let first = date(0, 1, 1);
+        assert_eq!(SpanMode::Yesterday.dates(first), None);
+        assert!(Span::new_dates(first, first)
+            .unwrap()
+            .resolve_at(&tz, last)
+            .is_some());
+    }
+
+    #[test]
+    fn parse_works() {
+        for mode in SpanMode::ALL.iter() {
+            let span: Span = mode.name().parse().unwrap();
+            assert_eq!(span, Span::Relative(*mode));
+            assert_eq!(span.to_string(), mode.name());
+        }
+
+        let span: Span = "2024-01-01".parse().unwrap();
+        assert_eq!(
+            span,
+            Span::new_dates(date(2024, 1, 1), date(2024, 1, 1)).unwrap()
+        );
+        assert_eq!(span.to_string(), "2024-01-01");
+
+        let s = "2024-01-01,2024-02-01";
+        let span: Span = s.parse().unwrap();
+        assert_eq!(
+            span,
+            Span::new_dates(date(2024, 1, 1), date(2024, 2, 1)).unwrap()
+        );
+        assert_eq!(span.to_string(), s);
+
+        let s = "2024-01-01T08:00:00-05:00 New_York,2024-01-01T17:30:00-05:00 New_York";
+        let span: Span = s.parse().unwrap();
+        assert!(matches!(span, Span::DateTimes { .. }));
+        assert_eq!(span.to_string(), s);
+
+        assert!("".parse::<Span>().is_err());
+        assert!("nextWeek".parse::<Span>().is_err());
+        assert!("2024-02-01,2024-01-01".parse::<Span>().is_err());
+        assert!("2024-01-01,".parse::<Span>().is_err());
+        assert!("2024-01-01,2024-01-01T00:00:00Z UTC"
+            .parse::<Span>()
+            .is_err());
+    }
+
+    #[test]
+    fn resolve_works() {
+        let tz = TimeZone::new("New_York").unwrap();
+        let today = date(2024, 3, 12);
+
+        let (start, end) = Span::Relative(SpanMode::Yesterday)
+            .resolve_at(&tz, today)
+            .unwrap();
+        assert_eq!(start.to_string(), "2024-03-11T00:00:00-04:00 New_York");
+        assert_eq!(end.to_string(), "2024-03-12T00:00:00-04:00 New_York");
+
+        // Clocks go forward on 2024-03-10 in New York:
+        let (start, end) = Span::Relative(SpanMode::ThisWeek)
+            .resolve_at(&tz, today)
+            .unwrap();
+        assert_eq!(start.to_string(), "2024-03-10T00:00:00-05:00 New_York");
+        assert_eq!(end.to_string(), "2024-03-17T00:00:00-04:00 New_York");
+
+        let span: Span = "2024-01-01T08:00:00Z UTC,2024-01-01T09:00:00Z UTC"
+            .parse()
+            .unwrap();
+        let (start, end) = span.resolve_at(&tz, today).unwrap();
+        assert_eq!(start.to_string(), "2024-01-01T03:00:00-05:00 New_York");
+        assert_eq!(end.to_string(), "2024-01-01T04:00:00-05:00 New_York");
+    }
+
+    #[test]
+    fn resolve_skipped_midnight_works() {
+        // Clocks go forward at midnight on 2024-09-08 in Santiago:
+        let tz = TimeZone::new("Santiago").unwrap();
+        let span: Span = "2024-09-08".parse().unwrap();
+        let (start, end) = span.resolve_at(&tz, date(2024, 1, 1)).unwrap();
+        assert_eq!(start.to_string(), "2024-09-08T01:00:00-03:00 Santiago");
+        assert_eq!(end.to_string(), "2024-09-09T00:00:00-03:00 Santiago");
+    }
+
+    #[test]
+    fn xstr_works() {
+        let xstr =
+            Xstr::new("Span".to_owned(), "lastMonth".to_owned()).unwrap();
+        let span = Span::from_xstr(&xstr).unwrap();
+        assert_eq!(span, Span::Relative(SpanMode::LastMonth));
+        assert_eq!(Xstr::from(span), xstr);
+
+        let other =
+            Xstr::new("Other".to_owned(), "lastMonth".to_owned()).unwrap();
+        assert!(Span::from_xstr(&other).is_err());
+    }
+}
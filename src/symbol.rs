@@ -2,7 +2,7 @@ use regex::Regex;
 use thiserror::Error;
 
 /// A Haystack Symbol.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Symbol(String);
 
 impl Symbol {
@@ -1,7 +1,7 @@
 use thiserror::Error;
 
 /// A Haystack tag name.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct TagName(String);
 
 impl TagName {
@@ -20,6 +20,13 @@ impl TagName {
     }
 }
 
+/// Create a `TagName` in tests, panicking if the string is not a valid
+/// tag name.
+#[cfg(test)]
+pub(crate) fn tn(s: &str) -> TagName {
+    TagName::new(s.to_owned()).unwrap()
+}
+
 impl std::fmt::Display for TagName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -0,0 +1,137 @@
+use crate::date::matches_pattern;
+use chrono::{NaiveTime, Timelike};
+use thiserror::Error;
+
+/// A Haystack Time, representing a time of day with no date or timezone.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Time(NaiveTime);
+
+impl Time {
+    /// Create a new `Time`. Returns `None` if the hour, minute and second
+    /// do not form a valid time of day.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Time;
+    /// let my_time = Time::new(8, 30, 0).unwrap();
+    /// ```
+    pub fn new(hour: u32, minute: u32, second: u32) -> Option<Self> {
+        NaiveTime::from_hms_opt(hour, minute, second).map(Self)
+    }
+
+    /// Create a new `Time` with a fractional second component. Returns `None`
+    /// if the components do not form a valid time of day.
+    pub fn new_nano(
+        hour: u32,
+        minute: u32,
+        second: u32,
+        nanosecond: u32,
+    ) -> Option<Self> {
+        NaiveTime::from_hms_nano_opt(hour, minute, second, nanosecond).map(Self)
+    }
+
+    /// Return the hour component of this `Time`.
+    pub fn hour(&self) -> u32 {
+        self.0.hour()
+    }
+
+    /// Return the minute component of this `Time`.
+    pub fn minute(&self) -> u32 {
+        self.0.minute()
+    }
+
+    /// Return the second component of this `Time`.
+    pub fn second(&self) -> u32 {
+        self.0.second()
+    }
+
+    /// Return the fractional second component of this `Time`,
+    /// in nanoseconds.
+    pub fn nanosecond(&self) -> u32 {
+        self.0.nanosecond()
+    }
+
+    /// Return the underlying `chrono::NaiveTime`.
+    pub fn naive_time(&self) -> NaiveTime {
+        self.0
+    }
+
+    /// Return this time as an Axon time literal.
+    pub fn to_axon_code(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.format("%H:%M:%S%.f"))
+    }
+}
+
+impl std::str::FromStr for Time {
+    type Err = ParseTimeError;
+
+    /// Parse a Haystack time, like `08:30:00` or `08:30:00.123`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hms, fraction) = match s.find('.') {
+            Some(index) => (&s[..index], &s[index + 1..]),
+            None => (s, ""),
+        };
+        let is_fraction_valid = if s.contains('.') {
+            !fraction.is_empty() && fraction.bytes().all(|c| c.is_ascii_digit())
+        } else {
+            true
+        };
+        if !matches_pattern(hms, "dd:dd:dd") || !is_fraction_valid {
+            return Err(ParseTimeError::from_str(s));
+        }
+        NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+            .map(Self)
+            .map_err(|_| ParseTimeError::from_str(s))
+    }
+}
+
+impl std::convert::From<NaiveTime> for Time {
+    fn from(time: NaiveTime) -> Self {
+        Self(time)
+    }
+}
+
+/// An error indicating that a `Time` could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse a Time from the string {unparsable_time}")]
+pub struct ParseTimeError {
+    unparsable_time: String,
+}
+
+impl ParseTimeError {
+    pub(crate) fn from_str(s: &str) -> Self {
+        Self {
+            unparsable_time: s.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Time;
+
+    #[test]
+    fn parse_works() {
+        let time: Time = "08:30:05".parse().unwrap();
+        assert_eq!(time, Time::new(8, 30, 5).unwrap());
+        assert_eq!(time.to_string(), "08:30:05");
+
+        let time: Time = "23:59:59.125".parse().unwrap();
+        assert_eq!(time, Time::new_nano(23, 59, 59, 125_000_000).unwrap());
+        assert_eq!(time.to_string(), "23:59:59.125");
+    }
+
+    #[test]
+    fn parse_invalid_fails() {
+        assert!("8:30:00".parse::<Time>().is_err());
+        assert!("08:30".parse::<Time>().is_err());
+        assert!("24:00:00".parse::<Time>().is_err());
+        assert!("08:30:00.".parse::<Time>().is_err());
+    }
+}
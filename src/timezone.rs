@@ -0,0 +1,264 @@
+use crate::{Date, DateTime, Time};
+use chrono::{FixedOffset, LocalResult, Offset, TimeZone as _, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// The IANA regions whose timezones have Haystack names.
+const REGIONS: &[&str] = &[
+    "Africa",
+    "America",
+    "Antarctica",
+    "Arctic",
+    "Asia",
+    "Atlantic",
+    "Australia",
+    "Etc",
+    "Europe",
+    "Indian",
+    "Pacific",
+];
+
+/// A Haystack timezone, like `New_York`, backed by the IANA timezone
+/// database embedded in this crate.
+///
+/// A Haystack timezone name is the last part of an IANA timezone id, so
+/// `America/New_York` is `New_York` and `Etc/GMT+5` is `GMT+5`.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::TimeZone;
+/// let tz = TimeZone::new("New_York").unwrap();
+/// assert_eq!(tz.iana_name(), "America/New_York");
+/// let tz = TimeZone::from_iana_name("Europe/London").unwrap();
+/// assert_eq!(tz.name(), "London");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TimeZone {
+    name: &'static str,
+    tz: Tz,
+}
+
+impl TimeZone {
+    /// Create a new `TimeZone` from a Haystack timezone name. Returns `None`
+    /// if the name is not a known Haystack timezone.
+    pub fn new(name: &str) -> Option<Self> {
+        haystack_zones()
+            .get_key_value(name)
+            .map(|(name, tz)| Self { name, tz: *tz })
+    }
+
+    /// Create a new `TimeZone` from an IANA timezone id,
+    /// like `America/New_York`. Returns `None` if the id is not known or
+    /// has no Haystack name.
+    pub fn from_iana_name(iana_name: &str) -> Option<Self> {
+        let tz: Tz = iana_name.parse().ok()?;
+        Self::new(haystack_name(tz.name())?)
+    }
+
+    /// Return the UTC timezone.
+    pub fn utc() -> Self {
+        Self::new("UTC").expect("UTC should be a Haystack timezone")
+    }
+
+    /// Return the Haystack name of this timezone, like `New_York`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Return the IANA id of this timezone, like `America/New_York`.
+    pub fn iana_name(&self) -> &'static str {
+        self.tz.name()
+    }
+
+    /// Return the UTC offset of this timezone at the given instant,
+    /// taking daylight saving time into account.
+    pub fn offset_at<Tz2: chrono::TimeZone>(
+        &self,
+        instant: &chrono::DateTime<Tz2>,
+    ) -> FixedOffset {
+        instant.with_timezone(&self.tz).offset().fix()
+    }
+
+    /// Return the `DateTime` in this timezone for the given instant.
+    /// Returns `None` if the year in this timezone is not between 0
+    /// and 9999.
+    pub fn date_time_at<Tz2: chrono::TimeZone>(
+        &self,
+        instant: &chrono::DateTime<Tz2>,
+    ) -> Option<DateTime> {
+        let date_time = instant.with_timezone(&self.tz).fixed_offset();
+        DateTime::new(date_time, self.name.to_owned())
+    }
+
+    /// Return the `DateTime` for a local date and time in this timezone,
+    /// using the daylight saving time rules of the timezone to find the
+    /// offset. If the local time occurs twice (when clocks go back), the
+    /// earlier instant is returned. Returns `None` if the local time does
+    /// not exist (when clocks go forward).
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Date, Time, TimeZone};
+    /// let tz = TimeZone::new("New_York").unwrap();
+    /// let date = Date::new(2024, 7, 1).unwrap();
+    /// let time = Time::new(8, 0, 0).unwrap();
+    /// let date_time = tz.date_time(date, time).unwrap();
+    /// assert_eq!(date_time.to_string(), "2024-07-01T08:00:00-04:00 New_York");
+    /// ```
+    pub fn date_time(&self, date: Date, time: Time) -> Option<DateTime> {
+        let naive = date.naive_date().and_time(time.naive_time());
+        let date_time = match self.tz.from_local_datetime(&naive) {
+            LocalResult::Single(date_time) => date_time,
+            LocalResult::Ambiguous(earliest, _) => earliest,
+            LocalResult::None => return None,
+        };
+        DateTime::new(date_time.fixed_offset(), self.name.to_owned())
+    }
+
+    /// Return the current `DateTime` in this timezone.
+    pub fn now(&self) -> DateTime {
+        self.date_time_at(&Utc::now())
+            .expect("the current year should be between 0 and 9999")
+    }
+}
+
+impl std::fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl DateTime {
+    /// Return the `TimeZone` of this `DateTime`, or `None` if its
+    /// timezone name is not a known Haystack timezone.
+    pub fn tz(&self) -> Option<TimeZone> {
+        TimeZone::new(self.time_zone())
+    }
+
+    /// Return the same instant as this `DateTime`, in another timezone.
+    /// Returns `None` if the year in the other timezone is not between 0
+    /// and 9999.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{DateTime, TimeZone};
+    /// let date_time: DateTime = "2024-01-15T08:00:00-05:00 New_York".parse().unwrap();
+    /// let london = TimeZone::new("London").unwrap();
+    /// let converted = date_time.to_time_zone(&london).unwrap();
+    /// assert_eq!(converted.to_string(), "2024-01-15T13:00:00Z London");
+    /// ```
+    pub fn to_time_zone(&self, time_zone: &TimeZone) -> Option<DateTime> {
+        time_zone.date_time_at(self.date_time())
+    }
+}
+
+/// Return the Haystack name for an IANA timezone id, if it has one.
+fn haystack_name(iana_name: &str) -> Option<&str> {
+    let (region, rest) = iana_name.split_once('/')?;
+    if REGIONS.contains(&region) {
+        rest.rsplit('/').next()
+    } else {
+        None
+    }
+}
+
+/// Return the timezones keyed by their Haystack names.
+fn haystack_zones() -> &'static HashMap<&'static str, Tz> {
+    static ZONES: OnceLock<HashMap<&'static str, Tz>> = OnceLock::new();
+    ZONES.get_or_init(|| {
+        let mut zones: HashMap<&'static str, Tz> = HashMap::new();
+        for tz in TZ_VARIANTS.iter() {
+            let name = match haystack_name(tz.name()) {
+                Some(name) => name,
+                None => continue,
+            };
+            // Some names are shared by an old link and a canonical id, like
+            // `America/Indianapolis` and `America/Indiana/Indianapolis`.
+            // The canonical ids are the more specific ones.
+            let depth = |tz: &Tz| tz.name().matches('/').count();
+            match zones.get(name) {
+                Some(existing) if depth(existing) >= depth(tz) => (),
+                _ => {
+                    zones.insert(name, *tz);
+                }
+            }
+        }
+        zones
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimeZone;
+    use crate::{Date, DateTime, Time};
+
+    #[test]
+    fn names_work() {
+        let tz = TimeZone::new("New_York").unwrap();
+        assert_eq!(tz.iana_name(), "America/New_York");
+        assert_eq!(TimeZone::utc().iana_name(), "Etc/UTC");
+        assert_eq!(TimeZone::new("GMT+5").unwrap().iana_name(), "Etc/GMT+5");
+        assert_eq!(
+            TimeZone::new("Indianapolis").unwrap().iana_name(),
+            "America/Indiana/Indianapolis"
+        );
+        assert_eq!(
+            TimeZone::from_iana_name("America/Argentina/Buenos_Aires")
+                .unwrap()
+                .name(),
+            "Buenos_Aires"
+        );
+        assert!(TimeZone::new("America/New_York").is_none());
+        assert!(TimeZone::new("Narnia").is_none());
+        assert!(TimeZone::from_iana_name("Nowhere/Narnia").is_none());
+    }
+
+    #[test]
+    fn dst_offsets_work() {
+        let tz = TimeZone::new("Sydney").unwrap();
+        let time = Time::new(12, 0, 0).unwrap();
+        let summer = tz.date_time(Date::new(2024, 1, 1).unwrap(), time);
+        let winter = tz.date_time(Date::new(2024, 7, 1).unwrap(), time);
+        assert_eq!(
+            summer.unwrap().date_time().offset().local_minus_utc(),
+            11 * 3600
+        );
+        assert_eq!(
+            winter.unwrap().date_time().offset().local_minus_utc(),
+            10 * 3600
+        );
+    }
+
+    #[test]
+    fn dst_transitions_work() {
+        let tz = TimeZone::new("New_York").unwrap();
+        // Clocks went forward at 02:00 on 2024-03-10:
+        let date = Date::new(2024, 3, 10).unwrap();
+        assert!(tz.date_time(date, Time::new(2, 30, 0).unwrap()).is_none());
+        // Clocks went back at 02:00 on 2024-11-03, so 01:30 happened twice:
+        let date = Date::new(2024, 11, 3).unwrap();
+        let date_time = tz.date_time(date, Time::new(1, 30, 0).unwrap());
+        assert_eq!(
+            date_time.unwrap().to_string(),
+            "2024-11-03T01:30:00-04:00 New_York"
+        );
+    }
+
+    #[test]
+    fn convert_works() {
+        let date_time: DateTime =
+            "2024-07-01T08:00:00-04:00 New_York".parse().unwrap();
+        let tz = date_time.tz().unwrap();
+        assert_eq!(tz.name(), "New_York");
+
+        let tokyo = TimeZone::new("Tokyo").unwrap();
+        let converted = date_time.to_time_zone(&tokyo).unwrap();
+        assert_eq!(converted.to_string(), "2024-07-01T21:00:00+09:00 Tokyo");
+        assert_eq!(converted.date_time(), date_time.date_time());
+        assert_eq!(converted.to_time_zone(&tz), Some(date_time));
+
+        let last: DateTime = "9999-12-31T23:00:00Z UTC".parse().unwrap();
+        assert!(last.to_time_zone(&tokyo).is_none());
+    }
+}
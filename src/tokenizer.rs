@@ -0,0 +1,908 @@
+//! A tokenizer for the Haystack text grammar shared by Zinc and filters.
+use crate::datetime::default_time_zone_name;
+use crate::number::{is_unit_char, parse_numeric};
+use crate::{Date, DateTime, Number, Position, Ref, Symbol, Uri, Value};
+use chrono::{FixedOffset, NaiveTime, TimeZone};
+use std::io::BufRead;
+
+/// An error which occurred while tokenizing.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct TokenError {
+    pub(crate) position: Position,
+    pub(crate) message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Token {
+    /// An identifier, like `site` or `INF`.
+    Id(String),
+    /// A literal scalar value, like a number, string, ref or symbol.
+    Val(Value),
+    Comma,
+    Colon,
+    Semicolon,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    LtLt,
+    GtGt,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Eq,
+    NotEq,
+    Assign,
+    Arrow,
+    Minus,
+    Plus,
+    Star,
+    Slash,
+    Dot,
+    DotDot,
+    FatArrow,
+    DoubleColon,
+    /// The comparison operator `<=>`.
+    Cmp,
+    /// A comment, like `// note`, including its delimiters. Comments are
+    /// only read when enabled with `Tokenizer::read_comments`.
+    Comment(String),
+    Nl,
+    Eof,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Id(id) => return write!(f, "identifier '{}'", id),
+            Self::Val(value) => return write!(f, "{} literal", value.kind()),
+            Self::Comma => ",",
+            Self::Colon => ":",
+            Self::Semicolon => ";",
+            Self::LBracket => "[",
+            Self::RBracket => "]",
+            Self::LBrace => "{",
+            Self::RBrace => "}",
+            Self::LParen => "(",
+            Self::RParen => ")",
+            Self::LtLt => "<<",
+            Self::GtGt => ">>",
+            Self::Lt => "<",
+            Self::LtEq => "<=",
+            Self::Gt => ">",
+            Self::GtEq => ">=",
+            Self::Eq => "==",
+            Self::NotEq => "!=",
+            Self::Assign => "=",
+            Self::Arrow => "->",
+            Self::Minus => "-",
+            Self::Plus => "+",
+            Self::Star => "*",
+            Self::Slash => "/",
+            Self::Dot => ".",
+            Self::DotDot => "..",
+            Self::FatArrow => "=>",
+            Self::DoubleColon => "::",
+            Self::Cmp => "<=>",
+            Self::Comment(_) => return write!(f, "comment"),
+            Self::Nl => return write!(f, "newline"),
+            Self::Eof => return write!(f, "end of input"),
+        };
+        write!(f, "'{}'", s)
+    }
+}
+
+/// Reads UTF-8 encoded characters from a `BufRead`, one at a time.
+struct CharReader<R> {
+    reader: R,
+}
+
+impl<R: BufRead> CharReader<R> {
+    fn next_char(&mut self) -> Result<Option<char>, String> {
+        let first = match self.next_byte()? {
+            Some(byte) => byte,
+            None => return Ok(None),
+        };
+        let width = match first {
+            0x00..=0x7f => return Ok(Some(first as char)),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err("Invalid UTF-8 byte".to_owned()),
+        };
+        let mut bytes = [first, 0, 0, 0];
+        for byte in bytes.iter_mut().take(width).skip(1) {
+            *byte = self
+                .next_byte()?
+                .ok_or_else(|| "Unexpected end of UTF-8 sequence".to_owned())?;
+        }
+        std::str::from_utf8(&bytes[..width])
+            .map(|s| s.chars().next())
+            .map_err(|_| "Invalid UTF-8 sequence".to_owned())
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, String> {
+        let buf = self.reader.fill_buf().map_err(|err| err.to_string())?;
+        match buf.first() {
+            Some(&byte) => {
+                self.reader.consume(1);
+                Ok(Some(byte))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Splits Haystack source text into tokens.
+pub(crate) struct Tokenizer<R> {
+    reader: CharReader<R>,
+    cur: Option<char>,
+    peek: Option<char>,
+    position: Position,
+    read_error: Option<String>,
+    comments: bool,
+}
+
+impl<R: BufRead> Tokenizer<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        let mut tokenizer = Self {
+            reader: CharReader { reader },
+            cur: None,
+            peek: None,
+            position: Position { line: 1, column: 1 },
+            read_error: None,
+            comments: false,
+        };
+        tokenizer.cur = tokenizer.read_char();
+        tokenizer.peek = tokenizer.read_char();
+        tokenizer
+    }
+
+    /// Return this tokenizer, reading `//` and `/* */` comments as
+    /// comment tokens.
+    #[cfg(feature = "axon")]
+    pub(crate) fn read_comments(mut self) -> Self {
+        self.comments = true;
+        self
+    }
+
+    /// Return the position just after the last token which was read.
+    #[cfg(feature = "axon")]
+    pub(crate) fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Return the next token, and the position at which it starts.
+    pub(crate) fn next_token(
+        &mut self,
+    ) -> Result<(Token, Position), TokenError> {
+        self.skip_spaces();
+        let position = self.position;
+        let token = self.read_token()?;
+        if let Some(message) = self.read_error.take() {
+            return Err(TokenError {
+                position: self.position,
+                message,
+            });
+        }
+        Ok((token, position))
+    }
+
+    fn read_char(&mut self) -> Option<char> {
+        match self.reader.next_char() {
+            Ok(c) => c,
+            Err(message) => {
+                self.read_error = Some(message);
+                None
+            }
+        }
+    }
+
+    fn consume(&mut self) {
+        if self.cur == Some('\n') {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        self.cur = self.peek;
+        self.peek = self.read_char();
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(c) = self.cur {
+            if c == ' ' || c == '\t' || c == '\u{a0}' || c == '\r' {
+                self.consume();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, TokenError> {
+        Err(TokenError {
+            position: self.position,
+            message: message.to_owned(),
+        })
+    }
+
+    fn read_token(&mut self) -> Result<Token, TokenError> {
+        let c = match self.cur {
+            Some(c) => c,
+            None => return Ok(Token::Eof),
+        };
+        let peek = self.peek;
+
+        if c.is_ascii_alphabetic() || c == '_' {
+            return Ok(self.read_id());
+        }
+        if c.is_ascii_digit()
+            || (c == '-' && peek.is_some_and(|p| p.is_ascii_digit()))
+        {
+            return self.read_num();
+        }
+        if self.comments && c == '/' && (peek == Some('/') || peek == Some('*'))
+        {
+            return self.read_comment();
+        }
+        match c {
+            '"' => return self.read_str().map(|s| Token::Val(Value::Str(s))),
+            '`' => return self.read_uri(),
+            '@' => return self.read_ref(),
+            '^' => return self.read_symbol(),
+            _ => (),
+        }
+
+        self.consume();
+        let token = match (c, peek) {
+            ('\n', _) => Token::Nl,
+            (',', _) => Token::Comma,
+            (':', Some(':')) => self.two(Token::DoubleColon),
+            (':', _) => Token::Colon,
+            (';', _) => Token::Semicolon,
+            ('[', _) => Token::LBracket,
+            (']', _) => Token::RBracket,
+            ('{', _) => Token::LBrace,
+            ('}', _) => Token::RBrace,
+            ('(', _) => Token::LParen,
+            (')', _) => Token::RParen,
+            ('<', Some('<')) => self.two(Token::LtLt),
+            ('<', Some('=')) => {
+                self.consume();
+                if self.cur == Some('>') {
+                    self.two(Token::Cmp)
+                } else {
+                    Token::LtEq
+                }
+            }
+            ('<', _) => Token::Lt,
+            ('>', Some('>')) => self.two(Token::GtGt),
+            ('>', Some('=')) => self.two(Token::GtEq),
+            ('>', _) => Token::Gt,
+            ('=', Some('=')) => self.two(Token::Eq),
+            ('=', Some('>')) => self.two(Token::FatArrow),
+            ('=', _) => Token::Assign,
+            ('!', Some('=')) => self.two(Token::NotEq),
+            ('-', Some('>')) => self.two(Token::Arrow),
+            ('-', _) => Token::Minus,
+            ('+', _) => Token::Plus,
+            ('*', _) => Token::Star,
+            ('/', _) => Token::Slash,
+            ('.', Some('.')) => self.two(Token::DotDot),
+            ('.', _) => Token::Dot,
+            _ => {
+                return Err(TokenError {
+                    position: Position {
+                        line: self.position.line,
+                        column: self.position.column - 1,
+                    },
+                    message: format!("Unexpected character '{}'", c),
+                })
+            }
+        };
+        Ok(token)
+    }
+
+    /// Consume the second character of a two character token.
+    fn two(&mut self, token: Token) -> Token {
+        self.consume();
+        token
+    }
+
+    fn read_id(&mut self) -> Token {
+        let mut id = String::new();
+        while let Some(c) = self.cur {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                id.push(c);
+                self.consume();
+            } else {
+                break;
+            }
+        }
+        Token::Id(id)
+    }
+
+    fn read_comment(&mut self) -> Result<Token, TokenError> {
+        let mut comment = String::from("/");
+        self.consume();
+        let is_block = self.cur == Some('*');
+        loop {
+            match (self.cur, self.peek) {
+                (Some('\n'), _) | (None, _) if !is_block => break,
+                (None, _) => return self.error("Unexpected end of comment"),
+                (Some('*'), Some('/')) if is_block => {
+                    comment.push_str("*/");
+                    self.consume();
+                    self.consume();
+                    break;
+                }
+                (Some(c), _) => {
+                    comment.push(c);
+                    self.consume();
+                }
+            }
+        }
+        Ok(Token::Comment(comment.trim_end().to_owned()))
+    }
+
+    fn read_str(&mut self) -> Result<String, TokenError> {
+        self.consume(); // opening quote
+        let mut s = String::new();
+        loop {
+            match self.cur {
+                None => return self.error("Unexpected end of string"),
+                Some('"') => {
+                    self.consume();
+                    return Ok(s);
+                }
+                Some('\\') => s.push(self.read_escape()?),
+                Some(c) => {
+                    s.push(c);
+                    self.consume();
+                }
+            }
+        }
+    }
+
+    fn read_escape(&mut self) -> Result<char, TokenError> {
+        self.consume(); // backslash
+        let c = match self.cur {
+            Some(c) => c,
+            None => return self.error("Unexpected end of escape sequence"),
+        };
+        self.consume();
+        let escaped = match c {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '"' => '"',
+            '\\' => '\\',
+            '$' => '$',
+            '\'' => '\'',
+            '`' => '`',
+            'u' => {
+                let mut code = 0;
+                for _ in 0..4 {
+                    let digit = self.cur.and_then(|c| c.to_digit(16));
+                    match digit {
+                        Some(digit) => code = code * 16 + digit,
+                        None => return self.error("Invalid unicode escape"),
+                    }
+                    self.consume();
+                }
+                match std::char::from_u32(code) {
+                    Some(c) => c,
+                    None => return self.error("Invalid unicode escape"),
+                }
+            }
+            _ => return self.error(&format!("Invalid escape '\\{}'", c)),
+        };
+        Ok(escaped)
+    }
+
+    fn read_uri(&mut self) -> Result<Token, TokenError> {
+        self.consume(); // opening backtick
+        let mut s = String::new();
+        loop {
+            match (self.cur, self.peek) {
+                (None, _) => return self.error("Unexpected end of uri"),
+                (Some('`'), _) => {
+                    self.consume();
+                    return Ok(Token::Val(Value::Uri(Uri::new(s))));
+                }
+                (Some('\\'), Some('`')) | (Some('\\'), Some('\\')) => {
+                    self.consume();
+                    s.push(self.cur.unwrap());
+                    self.consume();
+                }
+                (Some('\\'), Some('u')) => s.push(self.read_escape()?),
+                (Some(c), _) => {
+                    s.push(c);
+                    self.consume();
+                }
+            }
+        }
+    }
+
+    fn read_ref(&mut self) -> Result<Token, TokenError> {
+        let mut s = String::new();
+        s.push('@');
+        self.consume();
+        while let Some(c) = self.cur {
+            // Stop before an arrow, so `@abc->dis` is read as a get in Axon:
+            if c == '-' && self.peek == Some('>') {
+                break;
+            }
+            if c.is_alphanumeric() || "_:-.~".contains(c) {
+                s.push(c);
+                self.consume();
+            } else {
+                break;
+            }
+        }
+        match Ref::new(s) {
+            Ok(hsref) => Ok(Token::Val(Value::Ref(hsref))),
+            Err(_) => self.error("Invalid ref"),
+        }
+    }
+
+    fn read_symbol(&mut self) -> Result<Token, TokenError> {
+        let mut s = String::new();
+        s.push('^');
+        self.consume();
+        while let Some(c) = self.cur {
+            if c.is_alphanumeric() || "_:-.~".contains(c) {
+                s.push(c);
+                self.consume();
+            } else {
+                break;
+            }
+        }
+        match Symbol::new(s) {
+            Ok(symbol) => Ok(Token::Val(Value::Symbol(symbol))),
+            Err(_) => self.error("Invalid symbol"),
+        }
+    }
+
+    fn read_digits(&mut self, s: &mut String) {
+        while let Some(c) = self.cur {
+            if c.is_ascii_digit() {
+                s.push(c);
+                self.consume();
+            } else if c == '_' && self.peek.is_some_and(|p| p.is_ascii_digit())
+            {
+                self.consume();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn read_num(&mut self) -> Result<Token, TokenError> {
+        let mut int_part = String::new();
+        if self.cur == Some('-') {
+            int_part.push('-');
+            self.consume();
+        }
+
+        if self.cur == Some('0') && self.peek == Some('x') {
+            self.consume();
+            self.consume();
+            return self.read_hex(int_part.starts_with('-'));
+        }
+
+        self.read_digits(&mut int_part);
+
+        let is_negative = int_part.starts_with('-');
+        let peek_is_digit = self.peek.is_some_and(|p| p.is_ascii_digit());
+        if !is_negative && peek_is_digit {
+            if int_part.len() == 4 && self.cur == Some('-') {
+                let year = int_part.parse().unwrap();
+                return self.read_date_or_date_time(year);
+            }
+            if int_part.len() == 2 && self.cur == Some(':') {
+                let time = self.read_time(int_part.parse().unwrap())?;
+                return Ok(Token::Val(Value::Time(time.into())));
+            }
+        }
+
+        let mut fraction = String::new();
+        if self.cur == Some('.') && peek_is_digit {
+            self.consume();
+            self.read_digits(&mut fraction);
+        }
+
+        let mut exponent = None;
+        if self.cur == Some('e') || self.cur == Some('E') {
+            let is_exp = match self.peek {
+                Some(p) if p.is_ascii_digit() => true,
+                Some('+') | Some('-') => true,
+                _ => false,
+            };
+            if is_exp {
+                self.consume();
+                let mut exp = String::new();
+                if self.cur == Some('-') || self.cur == Some('+') {
+                    exp.push(self.cur.unwrap());
+                    self.consume();
+                }
+                self.read_digits(&mut exp);
+                match exp.parse::<i32>() {
+                    Ok(exp) => exponent = Some(exp),
+                    Err(_) => return self.error("Invalid number exponent"),
+                }
+            }
+        }
+
+        let unit = self.read_unit();
+
+        let mut numeric = int_part;
+        if !fraction.is_empty() {
+            numeric = format!("{}.{}", numeric, fraction);
+        }
+        if let Some(exponent) = exponent {
+            numeric = format!("{}e{}", numeric, exponent);
+        }
+
+        match parse_numeric(&numeric, unit) {
+            Some(number) => Ok(Token::Val(Value::Number(number))),
+            None => self.error("Invalid number"),
+        }
+    }
+
+    fn read_hex(&mut self, is_negative: bool) -> Result<Token, TokenError> {
+        let mut s = String::new();
+        while let Some(c) = self.cur {
+            if c.is_ascii_hexdigit() {
+                s.push(c);
+                self.consume();
+            } else if c == '_' {
+                self.consume();
+            } else {
+                break;
+            }
+        }
+        match u64::from_str_radix(&s, 16) {
+            Ok(n) => {
+                let n = i128::from(n);
+                let n = if is_negative { -n } else { n };
+                // Keep large 64-bit values exact:
+                let number = if n as f64 as i128 == n {
+                    Number::new_unitless(n as f64)
+                } else {
+                    Number::new_integer(n, None)
+                };
+                Ok(Token::Val(Value::Number(number)))
+            }
+            Err(_) => self.error("Invalid hex number"),
+        }
+    }
+
+    fn read_unit(&mut self) -> Option<String> {
+        let mut unit = String::new();
+        while let Some(c) = self.cur {
+            if is_unit_char(c) {
+                unit.push(c);
+                self.consume();
+            } else {
+                break;
+            }
+        }
+        if unit.is_empty() {
+            None
+        } else {
+            Some(unit)
+        }
+    }
+
+    /// Read exactly `count` digits, returning their value.
+    fn read_fixed_digits(&mut self, count: usize) -> Result<u32, TokenError> {
+        let mut n = 0;
+        for _ in 0..count {
+            match self.cur.and_then(|c| c.to_digit(10)) {
+                Some(digit) => n = n * 10 + digit,
+                None => return self.error("Expected a digit"),
+            }
+            self.consume();
+        }
+        Ok(n)
+    }
+
+    fn expect_char(&mut self, expected: char) -> Result<(), TokenError> {
+        if self.cur == Some(expected) {
+            self.consume();
+            Ok(())
+        } else {
+            self.error(&format!("Expected '{}'", expected))
+        }
+    }
+
+    fn read_date_or_date_time(
+        &mut self,
+        year: i32,
+    ) -> Result<Token, TokenError> {
+        self.expect_char('-')?;
+        let month = self.read_fixed_digits(2)?;
+        self.expect_char('-')?;
+        let day = self.read_fixed_digits(2)?;
+        let date = match Date::new(year, month, day) {
+            Some(date) => date,
+            None => return self.error("Invalid date"),
+        };
+
+        if self.cur != Some('T') {
+            return Ok(Token::Val(Value::Date(date)));
+        }
+        self.consume();
+
+        let hour = self.read_fixed_digits(2)?;
+        let time = self.read_time(hour)?;
+
+        let offset_seconds = match self.cur {
+            Some('Z') => {
+                self.consume();
+                0
+            }
+            Some(sign) if sign == '+' || sign == '-' => {
+                self.consume();
+                let hours = self.read_fixed_digits(2)? as i32;
+                self.expect_char(':')?;
+                let minutes = self.read_fixed_digits(2)? as i32;
+                let seconds = hours * 3600 + minutes * 60;
+                if sign == '-' {
+                    -seconds
+                } else {
+                    seconds
+                }
+            }
+            _ => return self.error("Expected a timezone offset"),
+        };
+
+        let offset = match FixedOffset::east_opt(offset_seconds) {
+            Some(offset) => offset,
+            None => return self.error("Invalid timezone offset"),
+        };
+
+        let time_zone = if self.cur == Some(' ')
+            && self.peek.is_some_and(|p| p.is_ascii_uppercase())
+        {
+            self.consume();
+            self.read_time_zone_name()
+        } else {
+            match default_time_zone_name(offset_seconds) {
+                Some(time_zone) => time_zone,
+                None => {
+                    return self.error(
+                        "Expected a timezone name after a timezone offset",
+                    )
+                }
+            }
+        };
+
+        let date_time = match offset
+            .from_local_datetime(&date.naive_date().and_time(time))
+            .single()
+        {
+            Some(date_time) => date_time,
+            None => return self.error("Invalid date time"),
+        };
+        match DateTime::new(date_time, time_zone) {
+            Some(date_time) => Ok(Token::Val(Value::DateTime(date_time))),
+            None => self.error("Invalid timezone name"),
+        }
+    }
+
+    fn read_time_zone_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.cur {
+            // Stop before an arrow, like in refs:
+            if c == '-' && self.peek == Some('>') {
+                break;
+            }
+            if c.is_ascii_alphanumeric() || "_-+/".contains(c) {
+                name.push(c);
+                self.consume();
+            } else {
+                break;
+            }
+        }
+        name
+    }
+
+    /// Read the rest of a time, where the hour has already been read.
+    fn read_time(&mut self, hour: u32) -> Result<NaiveTime, TokenError> {
+        self.expect_char(':')?;
+        let minute = self.read_fixed_digits(2)?;
+        let second = if self.cur == Some(':') {
+            self.consume();
+            self.read_fixed_digits(2)?
+        } else {
+            0
+        };
+        let mut nano = 0;
+        if self.cur == Some('.')
+            && self.peek.is_some_and(|p| p.is_ascii_digit())
+        {
+            self.consume();
+            let mut scale = 100_000_000;
+            while let Some(digit) = self.cur.and_then(|c| c.to_digit(10)) {
+                nano += digit * scale;
+                scale /= 10;
+                self.consume();
+            }
+        }
+        match NaiveTime::from_hms_nano_opt(hour, minute, second, nano) {
+            Some(time) => Ok(time),
+            None => self.error("Invalid time"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Token, Tokenizer};
+    use crate::{Date, DateTime, Number, Ref, Time, Value};
+
+    fn tokens(s: &str) -> Vec<Token> {
+        let mut tokenizer = Tokenizer::new(s.as_bytes());
+        let mut tokens = Vec::new();
+        loop {
+            let (token, _) = tokenizer.next_token().unwrap();
+            if token == Token::Eof {
+                return tokens;
+            }
+            tokens.push(token);
+        }
+    }
+
+    #[test]
+    fn numbers_work() {
+        assert_eq!(
+            tokens("72.5°F -3 1_000 0x10"),
+            vec![
+                Token::Val(Value::Number(Number::new(
+                    72.5,
+                    Some("°F".to_owned())
+                ))),
+                Token::Val(Value::Number(Number::new_unitless(-3.0))),
+                Token::Val(Value::Number(Number::new_unitless(1000.0))),
+                Token::Val(Value::Number(Number::new_unitless(16.0))),
+            ]
+        );
+        assert_eq!(
+            tokens("1.2e-3kW"),
+            vec![Token::Val(Value::Number(
+                Number::new_scientific(1.2, -3, Some("kW".to_owned())).unwrap()
+            ))]
+        );
+    }
+
+    #[test]
+    fn exact_numbers_work() {
+        assert_eq!(
+            tokens("18446744073709551615 0xFFFF_FFFF_FFFF_FFFF 0.1"),
+            vec![
+                Token::Val(Value::Number(Number::new_integer(
+                    u64::MAX.into(),
+                    None
+                ))),
+                Token::Val(Value::Number(Number::new_integer(
+                    u64::MAX.into(),
+                    None
+                ))),
+                Token::Val(Value::Number(Number::new_unitless(0.1))),
+            ]
+        );
+        assert_eq!(
+            tokens("1.2345678901234567891e300kWh"),
+            vec![Token::Val(Value::Number(Number::new_decimal(
+                12345678901234567891,
+                281,
+                Some("kWh".to_owned())
+            )))]
+        );
+    }
+
+    #[test]
+    fn dates_and_times_work() {
+        let date_time: DateTime =
+            "2024-03-10T02:30:00-05:00 New_York".parse().unwrap();
+        assert_eq!(
+            tokens("2024-03-10 08:15 23:59:59.5 2024-03-10T02:30:00-05:00 New_York"),
+            vec![
+                Token::Val(Value::Date(Date::new(2024, 3, 10).unwrap())),
+                Token::Val(Value::Time(Time::new(8, 15, 0).unwrap())),
+                Token::Val(Value::Time(
+                    Time::new_nano(23, 59, 59, 500_000_000).unwrap()
+                )),
+                Token::Val(Value::DateTime(date_time)),
+            ]
+        );
+        let utc: DateTime = "2024-03-10T07:30:00Z UTC".parse().unwrap();
+        assert_eq!(
+            tokens("2024-03-10T07:30:00Z"),
+            vec![Token::Val(utc.into())]
+        );
+        let mut tokenizer = Tokenizer::new("2024-02-30".as_bytes());
+        assert!(tokenizer.next_token().is_err());
+        let mut tokenizer =
+            Tokenizer::new("2024-03-10T07:30:00Z America/New_York".as_bytes());
+        assert!(tokenizer.next_token().is_err());
+    }
+
+    #[test]
+    fn punctuation_works() {
+        assert_eq!(
+            tokens("a->b <= >> ,\n"),
+            vec![
+                Token::Id("a".to_owned()),
+                Token::Arrow,
+                Token::Id("b".to_owned()),
+                Token::LtEq,
+                Token::GtGt,
+                Token::Comma,
+                Token::Nl,
+            ]
+        );
+    }
+
+    #[cfg(feature = "axon")]
+    #[test]
+    fn axon_punctuation_works() {
+        let mut tokenizer =
+            Tokenizer::new("a::b => 1..2 <=> // note\n".as_bytes())
+                .read_comments();
+        let mut tokens = Vec::new();
+        loop {
+            match tokenizer.next_token().unwrap() {
+                (Token::Eof, _) => break,
+                (token, _) => tokens.push(token),
+            }
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Id("a".to_owned()),
+                Token::DoubleColon,
+                Token::Id("b".to_owned()),
+                Token::FatArrow,
+                Token::Val(Value::Number(Number::new_unitless(1.0))),
+                Token::DotDot,
+                Token::Val(Value::Number(Number::new_unitless(2.0))),
+                Token::Cmp,
+                Token::Comment("// note".to_owned()),
+                Token::Nl,
+            ]
+        );
+    }
+
+    #[test]
+    fn ref_before_arrow_works() {
+        let mut tokenizer = Tokenizer::new("@abc-1->dis".as_bytes());
+        let hsref = Ref::new("@abc-1".to_owned()).unwrap();
+        let (token, _) = tokenizer.next_token().unwrap();
+        assert_eq!(token, Token::Val(Value::Ref(hsref)));
+        assert_eq!(tokenizer.next_token().unwrap().0, Token::Arrow);
+
+        let source = "2024-03-10T07:30:00Z UTC->dis";
+        let mut tokenizer = Tokenizer::new(source.as_bytes());
+        let utc: DateTime = "2024-03-10T07:30:00Z UTC".parse().unwrap();
+        assert_eq!(tokenizer.next_token().unwrap().0, Token::Val(utc.into()));
+        assert_eq!(tokenizer.next_token().unwrap().0, Token::Arrow);
+    }
+
+    #[test]
+    fn positions_work() {
+        let mut tokenizer = Tokenizer::new("a\n  %".as_bytes());
+        tokenizer.next_token().unwrap();
+        tokenizer.next_token().unwrap();
+        let err = tokenizer.next_token().unwrap_err();
+        assert_eq!(err.position.line, 2);
+        assert_eq!(err.position.column, 3);
+    }
+}
@@ -0,0 +1,483 @@
+use crate::{Dict, FromZinc, Grid, ParseZincError, TagName, Value, Zinc};
+use std::io::BufRead;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The prefix of a tag value which is written as indented Zinc, which is
+/// used for grids and other values whose Zinc contains several lines.
+const ZINC_PREFIX: &str = "Zinc:";
+
+/// The indentation used for multi-line values.
+const INDENT: &str = "  ";
+
+/// An error indicating that Trio could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse Trio at line {line}, column {column}: {message}")]
+pub struct ParseTrioError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseTrioError {
+    fn new(line: usize, column: usize, message: String) -> Self {
+        Self {
+            line,
+            column,
+            message,
+        }
+    }
+
+    /// Create an error from a Zinc error, where the Zinc text started at
+    /// the given line and column of the Trio text.
+    fn from_zinc(err: ParseZincError, line: usize, column: usize) -> Self {
+        let err_column = if err.line() == 1 {
+            column + err.column() - 1
+        } else {
+            err.column()
+        };
+        Self::new(line + err.line() - 1, err_column, err.message().to_owned())
+    }
+
+    /// Return the line at which the error occurred, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Return the column at which the error occurred, starting from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Return a description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Something which can be encoded as Trio
+/// (the text format used by Project Haystack for version-controlled records).
+pub trait Trio {
+    fn to_trio(&self) -> String;
+}
+
+/// Something which can be decoded from Trio
+/// (the text format used by Project Haystack for version-controlled records).
+pub trait FromTrio: Sized {
+    fn from_trio(trio: &str) -> Result<Self, ParseTrioError>;
+}
+
+impl Trio for Dict {
+    fn to_trio(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in self.iter() {
+            let name: &str = name.as_ref();
+            match value {
+                Value::Marker => out.push_str(name),
+                Value::Str(s) if is_multi_line_str(s) => {
+                    out.push_str(name);
+                    out.push(':');
+                    for line in s.split('\n') {
+                        out.push('\n');
+                        if !line.is_empty() {
+                            out.push_str(INDENT);
+                            out.push_str(line);
+                        }
+                    }
+                }
+                Value::Grid(grid) => {
+                    write_zinc_block(&mut out, name, &grid.to_zinc())
+                }
+                value => {
+                    let zinc = value.to_zinc();
+                    // Values containing grids are written over several
+                    // lines, which must be indented:
+                    if zinc.contains('\n') {
+                        write_zinc_block(&mut out, name, &zinc);
+                    } else {
+                        out.push_str(&format!("{}: {}", name, zinc))
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Trio for [Dict] {
+    fn to_trio(&self) -> String {
+        let records: Vec<String> =
+            self.iter().map(|record| record.to_trio()).collect();
+        records.join("---\n")
+    }
+}
+
+impl FromTrio for Vec<Dict> {
+    fn from_trio(trio: &str) -> Result<Self, ParseTrioError> {
+        TrioReader::new(trio.as_bytes()).read_records()
+    }
+}
+
+/// Write a tag whose value is written as indented Zinc.
+fn write_zinc_block(out: &mut String, name: &str, zinc: &str) {
+    out.push_str(&format!("{}: {}", name, ZINC_PREFIX));
+    for line in zinc.lines() {
+        out.push('\n');
+        out.push_str(INDENT);
+        out.push_str(line);
+    }
+}
+
+/// Return true if the string can be written as an indented multi-line
+/// string and read back unchanged.
+fn is_multi_line_str(s: &str) -> bool {
+    s.contains('\n')
+        && !s.ends_with('\n')
+        && !s.contains('\r')
+        && !s.starts_with(char::is_whitespace)
+        && s.split('\n').all(|line| line.is_empty() || !is_blank(line))
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn is_indented(line: &str) -> bool {
+    line.starts_with(' ') || line.starts_with('\t')
+}
+
+/// Return the width in bytes of the spaces and tabs at the start of a line.
+fn indent_width(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+/// Reads Trio records from any `BufRead`, one record at a time.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::TrioReader;
+/// let trio = "dis: \"Site A\"\nsite\n---\ndis: \"Site B\"\nsite\n";
+/// let mut reader = TrioReader::new(trio.as_bytes());
+/// while let Some(record) = reader.read_record().unwrap() {
+///     assert!(record.has_marker("site"));
+/// }
+/// ```
+pub struct TrioReader<R> {
+    reader: R,
+    /// The next line, if it has been read but not yet consumed.
+    peek: Option<String>,
+    /// The number of the last line which was consumed.
+    line: usize,
+}
+
+impl<R: BufRead> TrioReader<R> {
+    /// Create a new `TrioReader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            peek: None,
+            line: 0,
+        }
+    }
+
+    /// Read all remaining records.
+    pub fn read_records(&mut self) -> Result<Vec<Dict>, ParseTrioError> {
+        let mut records = Vec::new();
+        while let Some(record) = self.read_record()? {
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Read the next record, returning `None` once there are no
+    /// more records.
+    pub fn read_record(&mut self) -> Result<Option<Dict>, ParseTrioError> {
+        let mut record = Dict::new();
+        while let Some(line) = self.next_line()? {
+            if line.starts_with("---") {
+                if record.is_empty() {
+                    continue;
+                } else {
+                    break;
+                }
+            }
+            if is_blank(&line) || line.starts_with("//") {
+                continue;
+            }
+            if is_indented(&line) {
+                return Err(self.error(1, "Unexpected indented line"));
+            }
+
+            let line_number = self.line;
+            let (name, value) = self.read_tag(&line)?;
+            if record.contains(name.as_ref()) {
+                let message = format!("Duplicate tag '{}'", name);
+                return Err(ParseTrioError::new(line_number, 1, message));
+            }
+            record.insert(name, value);
+        }
+
+        if record.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(record))
+        }
+    }
+
+    /// Read a tag from a line, along with any indented lines which follow
+    /// it as part of the tag's value.
+    fn read_tag(
+        &mut self,
+        line: &str,
+    ) -> Result<(TagName, Value), ParseTrioError> {
+        let line_number = self.line;
+        let (name, rest) = match line.find(':') {
+            Some(index) => (&line[..index], Some(&line[index + 1..])),
+            None => (line, None),
+        };
+
+        let name = TagName::from_str(name.trim_end())
+            .map_err(|err| self.error(1, &err.to_string()))?;
+
+        let rest = match rest {
+            Some(rest) => rest,
+            None => return Ok((name, Value::Marker)),
+        };
+
+        let text = rest.trim();
+        let column =
+            line.chars().count() - rest.trim_start().chars().count() + 1;
+        let value = if text.is_empty() {
+            let (lines, _) = self.read_indented_lines()?;
+            Value::Str(lines.join("\n"))
+        } else if text == ZINC_PREFIX {
+            let start_line = self.line + 1;
+            let (lines, indent) = self.read_indented_lines()?;
+            let zinc = lines.join("\n");
+            let value = if zinc.starts_with("ver:") {
+                Grid::from_zinc(&zinc).map(Value::from)
+            } else {
+                Value::from_zinc(&zinc)
+            };
+            value.map_err(|err| {
+                ParseTrioError::from_zinc(err, start_line, indent + 1)
+            })?
+        } else {
+            match Value::from_zinc(text) {
+                Ok(value) => value,
+                // Strings which start with a letter may be unquoted:
+                Err(_) if text.starts_with(char::is_alphabetic) => {
+                    Value::Str(text.to_owned())
+                }
+                Err(err) => {
+                    return Err(ParseTrioError::from_zinc(
+                        err,
+                        line_number,
+                        column,
+                    ))
+                }
+            }
+        };
+        Ok((name, value))
+    }
+
+    /// Read the indented lines which follow a tag, with their common
+    /// indentation removed. Also returns the width of the indentation.
+    fn read_indented_lines(
+        &mut self,
+    ) -> Result<(Vec<String>, usize), ParseTrioError> {
+        let mut lines = Vec::new();
+        while let Some(line) = self.peek_line()? {
+            if is_blank(line) || is_indented(line) {
+                lines.push(self.next_line()?.unwrap());
+            } else {
+                break;
+            }
+        }
+        while lines.last().is_some_and(|line| is_blank(line)) {
+            lines.pop();
+        }
+
+        let indent = lines
+            .iter()
+            .filter(|line| !is_blank(line))
+            .map(|line| indent_width(line))
+            .min()
+            .unwrap_or(0);
+        let lines = lines
+            .into_iter()
+            .map(|line| {
+                if is_blank(&line) {
+                    String::new()
+                } else {
+                    line[indent..].to_owned()
+                }
+            })
+            .collect();
+        Ok((lines, indent))
+    }
+
+    fn error(&self, column: usize, message: &str) -> ParseTrioError {
+        ParseTrioError::new(self.line, column, message.to_owned())
+    }
+
+    fn peek_line(&mut self) -> Result<Option<&str>, ParseTrioError> {
+        if self.peek.is_none() {
+            let mut line = String::new();
+            let bytes_read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|err| self.error(1, &err.to_string()))?;
+            if bytes_read == 0 {
+                return Ok(None);
+            }
+            while line.ends_with('\n') || line.ends_with('\r') {
+                line.pop();
+            }
+            self.peek = Some(line);
+        }
+        Ok(self.peek.as_deref())
+    }
+
+    fn next_line(&mut self) -> Result<Option<String>, ParseTrioError> {
+        self.peek_line()?;
+        let line = self.peek.take();
+        if line.is_some() {
+            self.line += 1;
+        }
+        Ok(line)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FromTrio, Trio};
+    use crate::tag::tn;
+    use crate::{Dict, Grid, Number, Ref, Symbol, Value};
+
+    #[test]
+    fn read_records_works() {
+        let trio = "// A comment
+id: @site
+dis: \"Site 1\"
+site
+area: 1000ft²
+primaryFunction: Office
+doc:
+  First line
+
+    Indented line
+---
+---
+id: @equip
+equipRef: @site
+kind: ^steam-boiler
+";
+        let records = Vec::<Dict>::from_trio(trio).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let site = &records[0];
+        assert_eq!(
+            site.get_ref("id"),
+            Some(&Ref::new("@site".to_owned()).unwrap())
+        );
+        assert_eq!(site.get_str("dis"), Some("Site 1"));
+        assert!(site.has_marker("site"));
+        assert_eq!(
+            site.get_number("area"),
+            Some(&Number::new(1000.0, Some("ft²".to_owned())))
+        );
+        assert_eq!(site.get_str("primaryFunction"), Some("Office"));
+        assert_eq!(site.get_str("doc"), Some("First line\n\n  Indented line"));
+
+        let equip = &records[1];
+        assert_eq!(
+            equip.get_symbol("kind"),
+            Some(&Symbol::new("^steam-boiler".to_owned()).unwrap())
+        );
+    }
+
+    #[test]
+    fn read_mixed_indentation_works() {
+        let records =
+            Vec::<Dict>::from_trio("dis:\n   a\n \u{3000}b\n").unwrap();
+        assert_eq!(records[0].get_str("dis"), Some("  a\n\u{3000}b"));
+    }
+
+    #[test]
+    fn read_zinc_grid_works() {
+        let trio = "dis: \"Points\"
+rows: Zinc:
+  ver:\"3.0\"
+  a,b
+  1,2
+  3,4
+site
+";
+        let records = Vec::<Dict>::from_trio(trio).unwrap();
+        let grid = records[0].get_grid("rows").unwrap();
+        assert_eq!(grid.len(), 2);
+        assert!(records[0].has_marker("site"));
+    }
+
+    #[test]
+    fn round_trip_works() {
+        let mut first = Dict::new();
+        first.insert(tn("site"), Value::Marker);
+        first.insert(tn("dis"), "Site \"1\"".into());
+        first.insert(tn("doc"), "Line 1\n\n  Line 3".into());
+        first.insert(tn("trailing"), "Line 1\n".into());
+        first.insert(tn("tags"), vec![Value::Null, true.into()].into());
+        let mut row = Dict::new();
+        row.insert(tn("a"), Number::new_unitless(1.0).into());
+        let grid = Grid::from_rows(vec![row]);
+        first.insert(tn("rows"), grid.clone().into());
+        let mut second = Dict::new();
+        second.insert(tn("equip"), Value::Marker);
+
+        second.insert(tn("grids"), vec![grid.into()].into());
+
+        let records = vec![first, second];
+        let trio = records.to_trio();
+        assert_eq!(
+            trio,
+            "site
+dis: \"Site \\\"1\\\"\"
+doc:
+  Line 1
+
+    Line 3
+trailing: \"Line 1\\n\"
+tags: [N, T]
+rows: Zinc:
+  ver:\"3.0\"
+  a
+  1
+---
+equip
+grids: Zinc:
+  [<<
+  ver:\"3.0\"
+  a
+  1
+  >>]
+"
+        );
+        assert_eq!(Vec::<Dict>::from_trio(&trio).unwrap(), records);
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        let err = Vec::<Dict>::from_trio("site\nBad: 1\n").unwrap_err();
+        assert_eq!(err.line(), 2);
+        assert_eq!(err.column(), 1);
+
+        let err = Vec::<Dict>::from_trio("site\narea: [1, }\n").unwrap_err();
+        assert_eq!(err.line(), 2);
+        assert_eq!(err.column(), 11);
+
+        let err = Vec::<Dict>::from_trio("site\nsite\n").unwrap_err();
+        assert_eq!(err.line(), 2);
+    }
+}
@@ -0,0 +1,509 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// The Project Haystack unit database, embedded in this crate.
+const UNITS: &str = include_str!("units.txt");
+
+/// The SI base units, in the order used by `Dimension`.
+const BASE_UNITS: [&str; 7] = ["kg", "m", "sec", "K", "A", "mol", "cd"];
+
+/// A unit from the Project Haystack unit database, like `kW` or `°F`.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::Unit;
+/// let unit = Unit::find("fahrenheit").unwrap();
+/// assert_eq!(unit.symbol(), "°F");
+/// assert_eq!(unit.quantity(), "temperature");
+/// assert_eq!(Unit::find("°F"), Some(unit));
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Unit {
+    ids: Vec<&'static str>,
+    quantity: &'static str,
+    dimension: Dimension,
+    scale: f64,
+    offset: f64,
+}
+
+impl Unit {
+    /// Return the unit with the given name, symbol or alias, or `None`
+    /// if it is not in the unit database.
+    pub fn find(id: &str) -> Option<&'static Unit> {
+        let db = database();
+        db.by_id.get(id).map(|&index| &db.units[index])
+    }
+
+    /// Return all units in the unit database.
+    pub fn all() -> &'static [Unit] {
+        &database().units
+    }
+
+    /// Return the names of all quantities in the unit database,
+    /// like `temperature` and `energy`.
+    pub fn quantities() -> &'static [&'static str] {
+        &database().quantities
+    }
+
+    /// Return the units which measure the given quantity.
+    pub fn quantity_units(quantity: &str) -> Vec<&'static Unit> {
+        Self::all()
+            .iter()
+            .filter(|unit| unit.quantity == quantity)
+            .collect()
+    }
+
+    /// Return the full name of this unit, like `kilowatt`.
+    pub fn name(&self) -> &'static str {
+        self.ids[0]
+    }
+
+    /// Return the symbol of this unit, like `kW`. This is the string
+    /// used as the unit of a Haystack `Number`.
+    pub fn symbol(&self) -> &'static str {
+        self.ids[self.ids.len() - 1]
+    }
+
+    /// Return the name, aliases and symbol of this unit.
+    pub fn ids(&self) -> &[&'static str] {
+        &self.ids
+    }
+
+    /// Return the name of the quantity this unit measures, like `power`.
+    pub fn quantity(&self) -> &'static str {
+        self.quantity
+    }
+
+    /// Return the factor which converts a value in this unit into
+    /// SI base units, before the offset is added.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Return the offset which is added when converting a value in this
+    /// unit into SI base units. This is zero except for units like
+    /// `°C` and `°F`.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Return true if values can be converted between this unit and
+    /// the other unit. Units are convertible if they measure the same
+    /// quantity. Currencies cannot be converted to each other, and neither
+    /// can the unrelated units in the `misc` quantity, like `pH` and `dB`.
+    pub fn is_convertible_to(&self, other: &Unit) -> bool {
+        if self.quantity == "currency" || self.quantity == "misc" {
+            self == other
+        } else {
+            self.quantity == other.quantity && self.dimension == other.dimension
+        }
+    }
+
+    /// Convert a value in this unit into the other unit.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Unit;
+    /// let fahrenheit = Unit::find("°F").unwrap();
+    /// let celsius = Unit::find("°C").unwrap();
+    /// let value = fahrenheit.convert(212.0, celsius).unwrap();
+    /// assert!((value - 100.0).abs() < 1e-9);
+    /// ```
+    pub fn convert(
+        &self,
+        value: f64,
+        to: &Unit,
+    ) -> Result<f64, ConvertUnitError> {
+        if self == to {
+            return Ok(value);
+        }
+        if !self.is_convertible_to(to) {
+            return Err(ConvertUnitError::Incompatible {
+                from: self.symbol().to_owned(),
+                to: to.symbol().to_owned(),
+            });
+        }
+        Ok((value * self.scale + self.offset - to.offset) / to.scale)
+    }
+
+    /// Return the factor and the offset which convert a value in this unit
+    /// into the other unit, as `value * factor + offset`. The units are
+    /// assumed to be convertible.
+    pub(crate) fn linear_conversion(&self, to: &Unit) -> (f64, f64) {
+        (self.scale / to.scale, (self.offset - to.offset) / to.scale)
+    }
+
+    /// Return the unit which is the product of this unit and the other unit,
+    /// like `kWh` for `kW` and `h`, along with any factor which values must
+    /// be multiplied by. A dimensionless product has no unit.
+    pub(crate) fn multiply(
+        &self,
+        other: &Unit,
+    ) -> Option<(Option<&'static Unit>, f64)> {
+        let dimension = self.dimension.combine(other.dimension, 1);
+        let symbols = [
+            format!("{}{}", self.symbol(), other.symbol()),
+            format!("{}{}", other.symbol(), self.symbol()),
+        ];
+        derived_unit(dimension, self.scale * other.scale, &symbols)
+    }
+
+    /// Return the unit which is the quotient of this unit and the other unit,
+    /// like `kW` for `kWh` and `h`, along with any factor which values must
+    /// be multiplied by. A dimensionless quotient has no unit.
+    pub(crate) fn divide(
+        &self,
+        other: &Unit,
+    ) -> Option<(Option<&'static Unit>, f64)> {
+        let dimension = self.dimension.combine(other.dimension, -1);
+        let mut symbols = vec![format!("{}/{}", self.symbol(), other.symbol())];
+        if let Some(symbol) = self.symbol().strip_suffix(other.symbol()) {
+            symbols.push(symbol.to_owned());
+        }
+        derived_unit(dimension, self.scale / other.scale, &symbols)
+    }
+
+    /// Return true if converting between this unit and the other unit only
+    /// involves multiplying by a scale factor.
+    pub(crate) fn has_same_offset(&self, other: &Unit) -> bool {
+        self.offset == other.offset
+    }
+
+    /// Return the dimension of this unit, in terms of SI base units.
+    /// Units can only be converted between each other if they have the
+    /// same dimension.
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+}
+
+/// Return the unit with the dimension and scale resulting from multiplying
+/// or dividing units. Units with offsets, like `°F`, cannot be combined.
+///
+/// Several quantities can share a dimension and scale, like `kWh`, `kVAh`
+/// and `kvarh`. In that case the unit must have one of the given symbols,
+/// which are written in terms of the units being combined.
+fn derived_unit(
+    dimension: Dimension,
+    scale: f64,
+    symbols: &[String],
+) -> Option<(Option<&'static Unit>, f64)> {
+    if dimension.is_dimensionless() {
+        return Some((None, scale));
+    }
+    let is_close = |a: f64, b: f64| ((a - b) / b).abs() < 1e-9;
+    let units: Vec<&'static Unit> = Unit::all()
+        .iter()
+        .filter(|unit| {
+            unit.dimension == dimension
+                && unit.offset == 0.0
+                && is_close(unit.scale, scale)
+        })
+        .collect();
+    let first = units.first()?;
+    let unit = if units.iter().all(|unit| unit.quantity == first.quantity) {
+        first
+    } else {
+        units
+            .iter()
+            .find(|unit| symbols.iter().any(|symbol| symbol == unit.symbol()))?
+    };
+    Some((Some(unit), 1.0))
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Return true if the string is the name, symbol or alias of a unit in the
+/// Project Haystack unit database.
+pub fn is_unit<T: AsRef<str>>(s: T) -> bool {
+    Unit::find(s.as_ref()).is_some()
+}
+
+/// The exponents of the SI base units which make up a unit,
+/// like `m2*kg1*sec-3` for power.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Dimension([i8; 7]);
+
+impl Dimension {
+    /// Return true if this dimension has no SI base units, like the
+    /// dimension of `%`.
+    pub fn is_dimensionless(&self) -> bool {
+        self.0.iter().all(|&exponent| exponent == 0)
+    }
+
+    /// Add the exponents of the other dimension, multiplied by the sign.
+    fn combine(self, other: Dimension, sign: i8) -> Self {
+        let mut exponents = self.0;
+        for (exponent, other) in exponents.iter_mut().zip(other.0.iter()) {
+            *exponent += other * sign;
+        }
+        Self(exponents)
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let mut exponents = [0; 7];
+        for part in s.split('*').map(str::trim).filter(|p| !p.is_empty()) {
+            let split = part.find(|c: char| c == '-' || c.is_ascii_digit())?;
+            let (base, exponent) = part.split_at(split);
+            let index = BASE_UNITS.iter().position(|&b| b == base)?;
+            exponents[index] = exponent.parse().ok()?;
+        }
+        Some(Self(exponents))
+    }
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = BASE_UNITS
+            .iter()
+            .zip(self.0.iter())
+            .filter(|(_, &exponent)| exponent != 0)
+            .map(|(base, exponent)| format!("{}{}", base, exponent))
+            .collect();
+        write!(f, "{}", parts.join("*"))
+    }
+}
+
+struct Database {
+    units: Vec<Unit>,
+    quantities: Vec<&'static str>,
+    by_id: HashMap<&'static str, usize>,
+}
+
+fn database() -> &'static Database {
+    static DATABASE: OnceLock<Database> = OnceLock::new();
+    DATABASE.get_or_init(|| parse_database(UNITS))
+}
+
+fn parse_database(s: &'static str) -> Database {
+    let mut units = Vec::new();
+    let mut quantities = Vec::new();
+    let mut by_id = HashMap::new();
+
+    for (index, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("--") {
+            let quantity = header.split('(').next().unwrap_or("").trim();
+            quantities.push(quantity);
+            continue;
+        }
+
+        let unit =
+            parse_unit(line, quantities.last().copied()).unwrap_or_else(|| {
+                panic!("Invalid unit database line {}: {}", index + 1, line)
+            });
+        for id in &unit.ids {
+            by_id.insert(*id, units.len());
+        }
+        units.push(unit);
+    }
+
+    Database {
+        units,
+        quantities,
+        by_id,
+    }
+}
+
+fn parse_unit(
+    line: &'static str,
+    quantity: Option<&'static str>,
+) -> Option<Unit> {
+    // Trailing fields are optional, defaulting to a dimensionless unit
+    // with a scale of 1 and an offset of 0:
+    let mut parts = line.split(';').map(str::trim);
+    let ids = parts.next()?.split(',').map(str::trim).collect();
+    let dimension = Dimension::parse(parts.next().unwrap_or(""))?;
+    let scale = match parts.next() {
+        Some(scale) => scale.parse().ok()?,
+        None => 1.0,
+    };
+    let offset = match parts.next() {
+        Some(offset) => offset.parse().ok()?,
+        None => 0.0,
+    };
+    Some(Unit {
+        ids,
+        quantity: quantity?,
+        dimension,
+        scale,
+        offset,
+    })
+}
+
+/// An error indicating that a unit is not in the Project Haystack
+/// unit database.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("The unit {unit} is not in the Haystack unit database")]
+pub struct UnknownUnitError {
+    unit: String,
+}
+
+impl UnknownUnitError {
+    pub(crate) fn new(unit: String) -> Self {
+        Self { unit }
+    }
+
+    /// Return the unknown unit.
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+}
+
+/// An error indicating that a number could not be converted into
+/// another unit.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum ConvertUnitError {
+    /// The number being converted has no unit.
+    #[error("Cannot convert a number which has no unit")]
+    MissingUnit,
+    /// One of the units is not in the Haystack unit database.
+    #[error("The unit {0} is not in the Haystack unit database")]
+    UnknownUnit(String),
+    /// The units measure different quantities.
+    #[error("Cannot convert from {from} to {to}")]
+    Incompatible { from: String, to: String },
+    /// The converted number is too large or too small to be represented.
+    #[error("The number is out of range when converted from {from} to {to}")]
+    OutOfRange { from: String, to: String },
+}
+
+impl std::convert::From<UnknownUnitError> for ConvertUnitError {
+    fn from(err: UnknownUnitError) -> Self {
+        Self::UnknownUnit(err.unit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_unit, ConvertUnitError, Dimension, Unit};
+
+    #[test]
+    fn database_parses() {
+        let units = Unit::all();
+        assert!(units.len() > 300);
+        for unit in units {
+            for id in unit.ids() {
+                // Every id should find a unit, even if an alias is shared:
+                assert!(Unit::find(id).is_some(), "{}", id);
+            }
+        }
+    }
+
+    #[test]
+    fn find_works() {
+        let kw = Unit::find("kW").unwrap();
+        assert_eq!(Unit::find("kilowatt"), Some(kw));
+        assert_eq!(kw.name(), "kilowatt");
+        assert_eq!(kw.symbol(), "kW");
+        assert_eq!(kw.quantity(), "power");
+        assert_eq!(kw.scale(), 1000.0);
+        assert_eq!(Unit::find("hr").unwrap().symbol(), "h");
+        assert_eq!(Unit::find("kvar").unwrap().quantity(), "reactive power");
+        assert_eq!(Unit::find("kVAh").unwrap().quantity(), "apparent energy");
+        assert_eq!(Unit::find("gpm").unwrap().symbol(), "gal/min");
+        assert!(Unit::find("kilowatts").is_none());
+    }
+
+    #[test]
+    fn quantities_work() {
+        assert!(Unit::quantities().contains(&"temperature"));
+        assert!(Unit::quantities().contains(&"energy"));
+        let temperatures: Vec<&str> = Unit::quantity_units("temperature")
+            .iter()
+            .map(|unit| unit.symbol())
+            .collect();
+        assert_eq!(temperatures, vec!["K", "°C", "°F", "°R"]);
+    }
+
+    #[test]
+    fn optional_fields_work() {
+        let ph = Unit::find("pH").unwrap();
+        assert_eq!(ph.name(), "pH");
+        assert_eq!(ph.symbol(), "pH");
+        assert_eq!(ph.quantity(), "misc");
+        assert!(ph.dimension().is_dimensionless());
+        assert_eq!(ph.scale(), 1.0);
+        assert!(!ph.is_convertible_to(Unit::find("dB").unwrap()));
+
+        let kelvin = Unit::find("K").unwrap();
+        assert_eq!(kelvin.scale(), 1.0);
+        assert_eq!(kelvin.offset(), 0.0);
+    }
+
+    #[test]
+    fn is_unit_works() {
+        assert!(is_unit("°F"));
+        assert!(is_unit("ft²"));
+        assert!(is_unit("%"));
+        assert!(!is_unit("furlongs"));
+        assert!(!is_unit(""));
+    }
+
+    fn convert(value: f64, from: &str, to: &str) -> f64 {
+        let from = Unit::find(from).unwrap();
+        let to = Unit::find(to).unwrap();
+        from.convert(value, to).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn convert_works() {
+        assert_close(convert(32.0, "°F", "°C"), 0.0);
+        assert_close(convert(-40.0, "°C", "°F"), -40.0);
+        assert_close(convert(0.0, "°C", "K"), 273.15);
+        assert_close(convert(9.0, "Δ°F", "Δ°C"), 5.0);
+        assert_close(convert(1.0, "kW", "W"), 1000.0);
+        assert_close(convert(1.0, "tonref", "BTU/h"), 12000.0);
+        assert_close(convert(1.0, "kWh", "BTU"), 3412.141633127942);
+        assert_close(convert(1.0, "m²", "ft²"), 10.763910416709722);
+        assert_close(convert(50.0, "%", "%"), 50.0);
+    }
+
+    #[test]
+    fn convert_incompatible_fails() {
+        let incompatible = vec![
+            ("°F", "Δ°F"),
+            ("kW", "kWh"),
+            ("kW", "kVA"),
+            ("$", "€"),
+            ("m", "m²"),
+        ];
+        for (from, to) in incompatible {
+            let from = Unit::find(from).unwrap();
+            let to = Unit::find(to).unwrap();
+            assert!(!from.is_convertible_to(to));
+            assert!(matches!(
+                from.convert(1.0, to),
+                Err(ConvertUnitError::Incompatible { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn dimension_works() {
+        let w = Unit::find("W").unwrap().dimension();
+        let btuh = Unit::find("BTU/h").unwrap().dimension();
+        assert_eq!(w, btuh);
+        assert_eq!(w, Dimension([1, 2, -3, 0, 0, 0, 0]));
+        assert_eq!(w.to_string(), "kg1*m2*sec-3");
+        assert!(!w.is_dimensionless());
+        assert!(Unit::find("%").unwrap().dimension().is_dimensionless());
+    }
+}
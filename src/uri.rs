@@ -1,5 +1,7 @@
+use crate::zinc::zinc_uri;
+
 /// A Haystack Uri.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Uri(String);
 
 impl Uri {
@@ -19,9 +21,10 @@ impl Uri {
         self.0
     }
 
-    /// Return this uri as an Axon Uri literal.
+    /// Return this uri as an Axon Uri literal, with backticks and
+    /// backslashes escaped.
     pub fn to_axon_code(&self) -> String {
-        format!("`{}`", self.0)
+        zinc_uri(&self.0)
     }
 }
 
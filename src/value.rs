@@ -0,0 +1,592 @@
+use crate::zinc::zinc_str;
+use crate::{
+    Bin, Coord, Date, DateTime, Dict, Grid, Marker, Na, Number, Ref,
+    RemoveMarker, Symbol, Time, Uri, Xstr,
+};
+use std::cmp::Ordering;
+
+/// A Haystack value, which can hold any of the Haystack kinds.
+///
+/// Values can be hashed, so they can be put in sets or used to deduplicate
+/// records. Use `Value::haystack_cmp` to sort values.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Value {
+    Null,
+    Marker,
+    RemoveMarker,
+    Na,
+    Bool(bool),
+    Number(Number),
+    Str(String),
+    Uri(Uri),
+    Ref(Ref),
+    Symbol(Symbol),
+    Date(Date),
+    Time(Time),
+    DateTime(DateTime),
+    Coord(Coord),
+    Xstr(Xstr),
+    Bin(Bin),
+    List(Vec<Value>),
+    Dict(Dict),
+    Grid(Box<Grid>),
+}
+
+/// The kind of a Haystack value. Kinds are ordered in the order that
+/// values of different kinds are sorted by `Value::haystack_cmp`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Kind {
+    Null,
+    Marker,
+    Remove,
+    Na,
+    Bool,
+    Number,
+    Str,
+    Uri,
+    Ref,
+    Symbol,
+    Date,
+    Time,
+    DateTime,
+    Coord,
+    Xstr,
+    Bin,
+    List,
+    Dict,
+    Grid,
+}
+
+impl Kind {
+    /// Return the name of this kind, as used by Project Haystack.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Null => "Null",
+            Self::Marker => "Marker",
+            Self::Remove => "Remove",
+            Self::Na => "NA",
+            Self::Bool => "Bool",
+            Self::Number => "Number",
+            Self::Str => "Str",
+            Self::Uri => "Uri",
+            Self::Ref => "Ref",
+            Self::Symbol => "Symbol",
+            Self::Date => "Date",
+            Self::Time => "Time",
+            Self::DateTime => "DateTime",
+            Self::Coord => "Coord",
+            Self::Xstr => "XStr",
+            Self::Bin => "Bin",
+            Self::List => "List",
+            Self::Dict => "Dict",
+            Self::Grid => "Grid",
+        }
+    }
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Value {
+    /// Return the kind of this value.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Self::Null => Kind::Null,
+            Self::Marker => Kind::Marker,
+            Self::RemoveMarker => Kind::Remove,
+            Self::Na => Kind::Na,
+            Self::Bool(_) => Kind::Bool,
+            Self::Number(_) => Kind::Number,
+            Self::Str(_) => Kind::Str,
+            Self::Uri(_) => Kind::Uri,
+            Self::Ref(_) => Kind::Ref,
+            Self::Symbol(_) => Kind::Symbol,
+            Self::Date(_) => Kind::Date,
+            Self::Time(_) => Kind::Time,
+            Self::DateTime(_) => Kind::DateTime,
+            Self::Coord(_) => Kind::Coord,
+            Self::Xstr(_) => Kind::Xstr,
+            Self::Bin(_) => Kind::Bin,
+            Self::List(_) => Kind::List,
+            Self::Dict(_) => Kind::Dict,
+            Self::Grid(_) => Kind::Grid,
+        }
+    }
+
+    /// Return true if this value is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Return true if this value is a marker.
+    pub fn is_marker(&self) -> bool {
+        matches!(self, Self::Marker)
+    }
+
+    /// Return true if this value is a remove marker.
+    pub fn is_remove_marker(&self) -> bool {
+        matches!(self, Self::RemoveMarker)
+    }
+
+    /// Return true if this value is NA.
+    pub fn is_na(&self) -> bool {
+        matches!(self, Self::Na)
+    }
+
+    /// If this value is a bool, return the bool.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// If this value is a number, return the number.
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Self::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+
+    /// If this value is a string, return the string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// If this value is a uri, return the uri.
+    pub fn as_uri(&self) -> Option<&Uri> {
+        match self {
+            Self::Uri(uri) => Some(uri),
+            _ => None,
+        }
+    }
+
+    /// If this value is a ref, return the ref.
+    pub fn as_ref(&self) -> Option<&Ref> {
+        match self {
+            Self::Ref(hsref) => Some(hsref),
+            _ => None,
+        }
+    }
+
+    /// If this value is a symbol, return the symbol.
+    pub fn as_symbol(&self) -> Option<&Symbol> {
+        match self {
+            Self::Symbol(symbol) => Some(symbol),
+            _ => None,
+        }
+    }
+
+    /// If this value is a date, return the date.
+    pub fn as_date(&self) -> Option<&Date> {
+        match self {
+            Self::Date(date) => Some(date),
+            _ => None,
+        }
+    }
+
+    /// If this value is a time, return the time.
+    pub fn as_time(&self) -> Option<&Time> {
+        match self {
+            Self::Time(time) => Some(time),
+            _ => None,
+        }
+    }
+
+    /// If this value is a date time, return the date time.
+    pub fn as_date_time(&self) -> Option<&DateTime> {
+        match self {
+            Self::DateTime(date_time) => Some(date_time),
+            _ => None,
+        }
+    }
+
+    /// If this value is a coord, return the coord.
+    pub fn as_coord(&self) -> Option<&Coord> {
+        match self {
+            Self::Coord(coord) => Some(coord),
+            _ => None,
+        }
+    }
+
+    /// If this value is an xstr, return the xstr.
+    pub fn as_xstr(&self) -> Option<&Xstr> {
+        match self {
+            Self::Xstr(xstr) => Some(xstr),
+            _ => None,
+        }
+    }
+
+    /// If this value is a bin, return the bin.
+    pub fn as_bin(&self) -> Option<&Bin> {
+        match self {
+            Self::Bin(bin) => Some(bin),
+            _ => None,
+        }
+    }
+
+    /// If this value is a list, return the list.
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// If this value is a dict, return the dict.
+    pub fn as_dict(&self) -> Option<&Dict> {
+        match self {
+            Self::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// If this value is a grid, return the grid.
+    pub fn as_grid(&self) -> Option<&Grid> {
+        match self {
+            Self::Grid(grid) => Some(grid),
+            _ => None,
+        }
+    }
+
+    /// Return this value as Axon code. Kinds which have no Axon literal are
+    /// created with function calls, like `marker()` or `coord(1, 2)`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::Value;
+    /// let value: Value = "say \"hi\"".into();
+    /// assert_eq!(value.to_axon_code(), "\"say \\\"hi\\\"\"");
+    /// assert_eq!(Value::Marker.to_axon_code(), "marker()");
+    /// ```
+    pub fn to_axon_code(&self) -> String {
+        match self {
+            Self::Null => "null".to_owned(),
+            Self::Marker => Marker::new().to_axon_code(),
+            Self::RemoveMarker => RemoveMarker::new().to_axon_code(),
+            Self::Na => Na::new().to_axon_code(),
+            Self::Bool(true) => "true".to_owned(),
+            Self::Bool(false) => "false".to_owned(),
+            Self::Number(number) => number.to_axon_code(),
+            Self::Str(s) => zinc_str(s),
+            Self::Uri(uri) => uri.to_axon_code(),
+            Self::Ref(hsref) => hsref.to_axon_code().to_owned(),
+            Self::Symbol(symbol) => symbol.to_axon_code().to_owned(),
+            Self::Date(date) => date.to_axon_code(),
+            Self::Time(time) => time.to_axon_code(),
+            Self::DateTime(date_time) => date_time.to_axon_code(),
+            Self::Coord(coord) => coord.to_axon_code(),
+            Self::Xstr(xstr) => xstr.to_axon_code(),
+            Self::Bin(bin) => bin.to_axon_code(),
+            Self::List(values) => {
+                let values: Vec<String> =
+                    values.iter().map(|value| value.to_axon_code()).collect();
+                format!("[{}]", values.join(", "))
+            }
+            Self::Dict(dict) => dict.to_axon_code(),
+            Self::Grid(grid) => grid.to_axon_code(),
+        }
+    }
+
+    /// Compare two values using Haystack's sort order, which is the order
+    /// SkySpark uses to sort grids. Values of different kinds are sorted
+    /// by kind, so nulls come first, followed by markers. Numbers are sorted
+    /// by unit and then by value, and refs are sorted by their display
+    /// strings, or by their ids if they have no display string. Lists,
+    /// dicts and grids are compared item by item.
+    ///
+    /// This ordering is not consistent with equality, because refs with the
+    /// same id can have different display strings, so `Value` does not
+    /// implement `Ord`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use raystack_core::{Number, Ref, Value};
+    /// let b = Ref::new_with_dis("@a".to_owned(), "B".to_owned()).unwrap();
+    /// let a = Ref::new_with_dis("@b".to_owned(), "A".to_owned()).unwrap();
+    /// let mut values: Vec<Value> = vec![
+    ///     b.into(),
+    ///     Number::new_unitless(1.0).into(),
+    ///     Value::Null,
+    ///     a.into(),
+    ///     Value::Marker,
+    /// ];
+    /// values.sort_by(|x, y| x.haystack_cmp(y));
+    /// assert!(values[0].is_null());
+    /// assert!(values[1].is_marker());
+    /// assert_eq!(values[3].as_ref().unwrap().dis(), Some("A"));
+    /// ```
+    pub fn haystack_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Number(a), Self::Number(b)) => a.cmp(b),
+            (Self::Str(a), Self::Str(b)) => a.cmp(b),
+            (Self::Uri(a), Self::Uri(b)) => a.cmp(b),
+            (Self::Ref(a), Self::Ref(b)) => {
+                ref_sort_key(a).cmp(ref_sort_key(b)).then_with(|| a.cmp(b))
+            }
+            (Self::Symbol(a), Self::Symbol(b)) => a.cmp(b),
+            (Self::Date(a), Self::Date(b)) => a.cmp(b),
+            (Self::Time(a), Self::Time(b)) => a.cmp(b),
+            (Self::DateTime(a), Self::DateTime(b)) => a.cmp(b),
+            (Self::Coord(a), Self::Coord(b)) => a.cmp(b),
+            (Self::Xstr(a), Self::Xstr(b)) => a.cmp(b),
+            (Self::Bin(a), Self::Bin(b)) => a.cmp(b),
+            (Self::List(a), Self::List(b)) => {
+                cmp_items(a, b, |a, b| a.haystack_cmp(b))
+            }
+            (Self::Dict(a), Self::Dict(b)) => cmp_dicts(a, b),
+            (Self::Grid(a), Self::Grid(b)) => {
+                cmp_items(a.rows(), b.rows(), cmp_dicts)
+            }
+            _ => self.kind().cmp(&other.kind()),
+        }
+    }
+}
+
+/// Return the string used to sort a ref, which is its display string if
+/// it has one.
+fn ref_sort_key(hsref: &Ref) -> &str {
+    hsref.dis().unwrap_or_else(|| hsref.as_ref())
+}
+
+/// Compare two dicts using Haystack's sort order, comparing their tags
+/// in tag name order.
+fn cmp_dicts(a: &Dict, b: &Dict) -> Ordering {
+    cmp_items(
+        &a.sorted_tags(),
+        &b.sorted_tags(),
+        |(a, a_val), (b, b_val)| {
+            a.cmp(b).then_with(|| a_val.haystack_cmp(b_val))
+        },
+    )
+}
+
+/// Compare two slices item by item, with a shorter slice coming before
+/// a longer slice which starts with the same items.
+fn cmp_items<T, F>(a: &[T], b: &[T], cmp: F) -> Ordering
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| cmp(a, b))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+impl std::convert::From<Marker> for Value {
+    fn from(_: Marker) -> Self {
+        Self::Marker
+    }
+}
+
+impl std::convert::From<RemoveMarker> for Value {
+    fn from(_: RemoveMarker) -> Self {
+        Self::RemoveMarker
+    }
+}
+
+impl std::convert::From<Na> for Value {
+    fn from(_: Na) -> Self {
+        Self::Na
+    }
+}
+
+impl std::convert::From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl std::convert::From<Number> for Value {
+    fn from(number: Number) -> Self {
+        Self::Number(number)
+    }
+}
+
+impl std::convert::From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl std::convert::From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Self::Str(s.to_owned())
+    }
+}
+
+impl std::convert::From<Uri> for Value {
+    fn from(uri: Uri) -> Self {
+        Self::Uri(uri)
+    }
+}
+
+impl std::convert::From<Ref> for Value {
+    fn from(hsref: Ref) -> Self {
+        Self::Ref(hsref)
+    }
+}
+
+impl std::convert::From<Symbol> for Value {
+    fn from(symbol: Symbol) -> Self {
+        Self::Symbol(symbol)
+    }
+}
+
+impl std::convert::From<Bin> for Value {
+    fn from(bin: Bin) -> Self {
+        Self::Bin(bin)
+    }
+}
+
+impl std::convert::From<Date> for Value {
+    fn from(date: Date) -> Self {
+        Self::Date(date)
+    }
+}
+
+impl std::convert::From<Time> for Value {
+    fn from(time: Time) -> Self {
+        Self::Time(time)
+    }
+}
+
+impl std::convert::From<DateTime> for Value {
+    fn from(date_time: DateTime) -> Self {
+        Self::DateTime(date_time)
+    }
+}
+
+impl std::convert::From<Coord> for Value {
+    fn from(coord: Coord) -> Self {
+        Self::Coord(coord)
+    }
+}
+
+impl std::convert::From<Xstr> for Value {
+    fn from(xstr: Xstr) -> Self {
+        Self::Xstr(xstr)
+    }
+}
+
+impl std::convert::From<Vec<Value>> for Value {
+    fn from(list: Vec<Value>) -> Self {
+        Self::List(list)
+    }
+}
+
+impl std::convert::From<Dict> for Value {
+    fn from(dict: Dict) -> Self {
+        Self::Dict(dict)
+    }
+}
+
+impl std::convert::From<Grid> for Value {
+    fn from(grid: Grid) -> Self {
+        Self::Grid(Box::new(grid))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Kind, Value};
+    use crate::tag::tn;
+    use crate::{Coord, Dict, Marker, Number, Ref};
+    use std::cmp::Ordering;
+    use std::collections::HashSet;
+
+    #[test]
+    fn from_works() {
+        let hsref = Ref::new("@abc".to_owned()).unwrap();
+        let value: Value = hsref.clone().into();
+        assert_eq!(value.as_ref(), Some(&hsref));
+        assert_eq!(value.kind(), Kind::Ref);
+
+        let value: Value = Marker::new().into();
+        assert!(value.is_marker());
+        assert_eq!(value.kind(), Kind::Marker);
+    }
+
+    #[test]
+    fn accessors_return_none_for_other_kinds() {
+        let value: Value = Number::new_unitless(1.0).into();
+        assert_eq!(value.as_number(), Some(&Number::new_unitless(1.0)));
+        assert!(value.as_ref().is_none());
+        assert!(value.as_str().is_none());
+        assert!(!value.is_null());
+    }
+
+    fn num(value: f64, unit: Option<&str>) -> Value {
+        Number::new(value, unit.map(|unit| unit.to_owned())).into()
+    }
+
+    #[test]
+    fn haystack_cmp_works() {
+        let mut values = vec![
+            num(5.0, Some("kW")),
+            Ref::new_with_dis("@x".to_owned(), "Beta".to_owned())
+                .unwrap()
+                .into(),
+            "text".into(),
+            num(10.0, None),
+            Ref::new("@Alpha".to_owned()).unwrap().into(),
+            Value::Marker,
+            num(2.0, Some("kW")),
+            Value::Null,
+            num(f64::NAN, None),
+        ];
+        values.sort_by(|a, b| a.haystack_cmp(b));
+        let kinds: Vec<Kind> = values.iter().map(Value::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Kind::Null,
+                Kind::Marker,
+                Kind::Number,
+                Kind::Number,
+                Kind::Number,
+                Kind::Number,
+                Kind::Str,
+                Kind::Ref,
+                Kind::Ref,
+            ]
+        );
+        assert_eq!(values[2], num(10.0, None));
+        assert!(values[3].as_number().unwrap().to_f64().is_nan());
+        assert_eq!(values[4], num(2.0, Some("kW")));
+        assert_eq!(values[7].as_ref().unwrap().as_ref() as &str, "@Alpha");
+        assert_eq!(values[8].as_ref().unwrap().dis(), Some("Beta"));
+
+        let short: Value = vec![num(1.0, None)].into();
+        let long: Value = vec![num(1.0, None), Value::Null].into();
+        assert_eq!(short.haystack_cmp(&long), Ordering::Less);
+    }
+
+    #[test]
+    fn hash_works() {
+        let mut a = Dict::new();
+        a.insert(tn("site"), Value::Marker);
+        a.insert(tn("area"), num(f64::NAN, Some("ft²")));
+        let mut b = Dict::new();
+        b.insert(tn("area"), num(f64::NAN, Some("ft²")));
+        b.insert(tn("site"), Value::Marker);
+
+        let mut set = HashSet::new();
+        assert!(set.insert(Value::Dict(a)));
+        assert!(!set.insert(Value::Dict(b)));
+        assert!(set.insert(Coord::new(1.0, 2.0).into()));
+        assert!(!set.insert(Coord::new(1.0, 2.0).into()));
+        let scientific = Number::new_scientific_unitless(1.5, 3).unwrap();
+        assert!(set.insert(num(1500.0, None)));
+        assert!(!set.insert(scientific.into()));
+    }
+}
@@ -1,20 +1,32 @@
+use crate::zinc::zinc_str;
+use thiserror::Error;
+
 /// A Haystack XStr.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Xstr {
     type_name: String,
     value: String,
 }
 
 impl Xstr {
-    /// Create a new `Xstr`.
+    /// Create a new `Xstr`. Returns an error if the type name does not
+    /// start with an uppercase ASCII letter, followed by ASCII letters,
+    /// digits or underscores.
     ///
     /// # Example
     /// ```rust
     /// use raystack_core::Xstr;
-    /// let my_xstr = Xstr::new("Color".to_string(), "red".to_string());
+    /// let my_xstr = Xstr::new("Color".to_string(), "red".to_string()).unwrap();
     /// ```
-    pub fn new(type_name: String, value: String) -> Self {
-        Self { type_name, value }
+    pub fn new(
+        type_name: String,
+        value: String,
+    ) -> Result<Self, ParseXstrError> {
+        if is_type_name(&type_name) {
+            Ok(Self { type_name, value })
+        } else {
+            Err(ParseXstrError { type_name })
+        }
     }
 
     pub fn type_name(&self) -> &str {
@@ -27,12 +39,46 @@ impl Xstr {
 
     /// Return this `Xstr` as Axon code.
     pub fn to_axon_code(&self) -> String {
-        format!("xstr(\"{}\", \"{}\")", self.type_name(), self.value())
+        format!(
+            "xstr({}, {})",
+            zinc_str(self.type_name()),
+            zinc_str(self.value())
+        )
     }
 }
 
+/// Return true if the string is a valid `Xstr` type name, like `Color`.
+fn is_type_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 impl std::fmt::Display for Xstr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}(\"{}\")", self.type_name(), self.value())
     }
 }
+
+/// An error indicating that an `Xstr` has an invalid type name.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("The Xstr type name {type_name} is not valid")]
+pub struct ParseXstrError {
+    type_name: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::Xstr;
+
+    #[test]
+    fn new_invalid_type_name_fails() {
+        let new = |type_name: &str| Xstr::new(type_name.to_owned(), "v".into());
+        assert!(new("Color").is_ok());
+        assert!(new("Span_2").is_ok());
+        assert!(new("").is_err());
+        assert!(new("color").is_err());
+        assert!(new("Foo:bar").is_err());
+        assert!(new("Foo\") + evil(\"").is_err());
+    }
+}
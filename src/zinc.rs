@@ -0,0 +1,403 @@
+use crate::{
+    BasicNumber, Bin, Coord, Date, DateTime, DecimalNumber, Dict, Grid,
+    IntegerNumber, Marker, Na, Number, Qname, Ref, RemoveMarker,
+    ScientificNumber, Symbol, Time, Uri, Value, Xstr,
+};
+
+/// The Zinc version written in grid headers.
+const ZINC_VERSION: &str = "3.0";
+
+/// Something which can be encoded as Zinc
+/// (the default text format used by Project Haystack).
+pub trait Zinc {
+    fn to_zinc(&self) -> String;
+}
+
+/// Return a Zinc string literal containing the given string,
+/// with special characters escaped.
+pub(crate) fn zinc_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '$' => out.push_str("\\$"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Return a Zinc uri literal containing the given string, with backticks
+/// and backslashes escaped.
+pub(crate) fn zinc_uri(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('`');
+    for c in s.chars() {
+        match c {
+            '`' => out.push_str("\\`"),
+            '\\' => out.push_str("\\\\"),
+            c => out.push(c),
+        }
+    }
+    out.push('`');
+    out
+}
+
+impl Zinc for Marker {
+    fn to_zinc(&self) -> String {
+        "M".to_owned()
+    }
+}
+
+impl Zinc for RemoveMarker {
+    fn to_zinc(&self) -> String {
+        "R".to_owned()
+    }
+}
+
+impl Zinc for Na {
+    fn to_zinc(&self) -> String {
+        "NA".to_owned()
+    }
+}
+
+impl Zinc for bool {
+    fn to_zinc(&self) -> String {
+        if *self {
+            "T".to_owned()
+        } else {
+            "F".to_owned()
+        }
+    }
+}
+
+impl Zinc for BasicNumber {
+    fn to_zinc(&self) -> String {
+        let value = self.value();
+        // Zinc does not allow units on NaN, INF or -INF.
+        if value.is_nan() {
+            "NaN".to_owned()
+        } else if value.is_infinite() && value.is_sign_positive() {
+            "INF".to_owned()
+        } else if value.is_infinite() && value.is_sign_negative() {
+            "-INF".to_owned()
+        } else {
+            format!("{}{}", value, self.unit().unwrap_or(""))
+        }
+    }
+}
+
+impl Zinc for ScientificNumber {
+    fn to_zinc(&self) -> String {
+        format!(
+            "{}e{}{}",
+            self.significand(),
+            self.exponent(),
+            self.unit().unwrap_or("")
+        )
+    }
+}
+
+impl Zinc for IntegerNumber {
+    fn to_zinc(&self) -> String {
+        self.to_axon_code()
+    }
+}
+
+impl Zinc for DecimalNumber {
+    fn to_zinc(&self) -> String {
+        self.to_axon_code()
+    }
+}
+
+impl Zinc for Number {
+    fn to_zinc(&self) -> String {
+        match self {
+            Self::Basic(num) => num.to_zinc(),
+            Self::Scientific(ex) => ex.to_zinc(),
+            Self::Integer(int) => int.to_zinc(),
+            Self::Decimal(dec) => dec.to_zinc(),
+        }
+    }
+}
+
+impl Zinc for str {
+    fn to_zinc(&self) -> String {
+        zinc_str(self)
+    }
+}
+
+impl Zinc for String {
+    fn to_zinc(&self) -> String {
+        zinc_str(self)
+    }
+}
+
+impl Zinc for Uri {
+    fn to_zinc(&self) -> String {
+        zinc_uri(self.as_ref())
+    }
+}
+
+impl Zinc for Ref {
+    fn to_zinc(&self) -> String {
+        match self.dis() {
+            Some(dis) => format!("{} {}", self.to_axon_code(), zinc_str(dis)),
+            None => self.to_axon_code().to_owned(),
+        }
+    }
+}
+
+impl Zinc for Symbol {
+    fn to_zinc(&self) -> String {
+        self.to_axon_code().to_owned()
+    }
+}
+
+/// Zinc has no kind for qualified names, so a `Qname` is encoded as a
+/// string.
+impl Zinc for Qname {
+    fn to_zinc(&self) -> String {
+        zinc_str(self.qname())
+    }
+}
+
+impl Zinc for Date {
+    fn to_zinc(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Zinc for Time {
+    fn to_zinc(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Zinc for DateTime {
+    fn to_zinc(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Zinc for Bin {
+    fn to_zinc(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Zinc for Coord {
+    fn to_zinc(&self) -> String {
+        format!("C({},{})", self.lat(), self.lng())
+    }
+}
+
+impl Zinc for Xstr {
+    fn to_zinc(&self) -> String {
+        format!("{}({})", self.type_name(), zinc_str(self.value()))
+    }
+}
+
+impl Zinc for [Value] {
+    fn to_zinc(&self) -> String {
+        let values: Vec<String> = self.iter().map(|v| v.to_zinc()).collect();
+        format!("[{}]", values.join(", "))
+    }
+}
+
+impl Zinc for Dict {
+    fn to_zinc(&self) -> String {
+        format!("{{{}}}", tags_to_zinc(self))
+    }
+}
+
+impl Zinc for Grid {
+    fn to_zinc(&self) -> String {
+        let mut out = format!("ver:{}", zinc_str(ZINC_VERSION));
+        if !self.meta().is_empty() {
+            out.push(' ');
+            out.push_str(&tags_to_zinc(self.meta()));
+        }
+        out.push('\n');
+
+        if self.columns().is_empty() {
+            // A grid must have at least one column.
+            out.push_str("empty\n");
+            return out;
+        }
+
+        let columns: Vec<String> = self
+            .columns()
+            .iter()
+            .map(|col| {
+                let name: &str = col.name().as_ref();
+                if col.meta().is_empty() {
+                    name.to_owned()
+                } else {
+                    format!("{} {}", name, tags_to_zinc(col.meta()))
+                }
+            })
+            .collect();
+        out.push_str(&columns.join(","));
+        out.push('\n');
+
+        for row in self.rows() {
+            let cells: Vec<String> = self
+                .columns()
+                .iter()
+                .map(|col| match row.get(col.name().as_ref()) {
+                    Some(Value::Null) | None => String::new(),
+                    Some(value) => value.to_zinc(),
+                })
+                .collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+impl Zinc for Value {
+    fn to_zinc(&self) -> String {
+        match self {
+            Self::Null => "N".to_owned(),
+            Self::Marker => Marker.to_zinc(),
+            Self::RemoveMarker => RemoveMarker.to_zinc(),
+            Self::Na => Na.to_zinc(),
+            Self::Bool(b) => b.to_zinc(),
+            Self::Number(number) => number.to_zinc(),
+            Self::Str(s) => s.to_zinc(),
+            Self::Uri(uri) => uri.to_zinc(),
+            Self::Ref(hsref) => hsref.to_zinc(),
+            Self::Symbol(symbol) => symbol.to_zinc(),
+            Self::Date(date) => date.to_zinc(),
+            Self::Time(time) => time.to_zinc(),
+            Self::DateTime(date_time) => date_time.to_zinc(),
+            Self::Coord(coord) => coord.to_zinc(),
+            Self::Xstr(xstr) => xstr.to_zinc(),
+            Self::Bin(bin) => bin.to_zinc(),
+            Self::List(list) => list.to_zinc(),
+            Self::Dict(dict) => dict.to_zinc(),
+            Self::Grid(grid) => format!("<<\n{}>>", grid.to_zinc()),
+        }
+    }
+}
+
+/// Return the tags of a dict as space-separated Zinc tags, where markers
+/// are written as just the tag name.
+fn tags_to_zinc(dict: &Dict) -> String {
+    let tags: Vec<String> = dict
+        .iter()
+        .map(|(name, value)| {
+            let name: &str = name.as_ref();
+            if value.is_marker() {
+                name.to_owned()
+            } else {
+                format!("{}:{}", name, value.to_zinc())
+            }
+        })
+        .collect();
+    tags.join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::Zinc;
+    use crate::tag::tn;
+    use crate::{
+        Coord, DateTime, Dict, Grid, Number, Qname, Ref, Symbol, Time, Uri,
+        Value, Xstr,
+    };
+
+    #[test]
+    fn number_to_zinc_works() {
+        assert_eq!(
+            Number::new(72.5, Some("°F".to_owned())).to_zinc(),
+            "72.5°F"
+        );
+        assert_eq!(Number::new_unitless(-3.0).to_zinc(), "-3");
+        assert_eq!(
+            Number::new(f64::INFINITY, Some("kW".to_owned())).to_zinc(),
+            "INF"
+        );
+        assert_eq!(Number::new_unitless(f64::NEG_INFINITY).to_zinc(), "-INF");
+        assert_eq!(Number::new_unitless(f64::NAN).to_zinc(), "NaN");
+        assert_eq!(
+            Number::new_scientific(1.2, -3, Some("kW".to_owned()))
+                .unwrap()
+                .to_zinc(),
+            "1.2e-3kW"
+        );
+    }
+
+    #[test]
+    fn scalars_to_zinc_works() {
+        let hsref = Ref::new("@p:demo:r:1".to_owned()).unwrap();
+        assert_eq!(hsref.to_zinc(), "@p:demo:r:1");
+        let hsref =
+            Ref::new_with_dis("@site".to_owned(), "Site \"1\"".to_owned())
+                .unwrap();
+        assert_eq!(hsref.to_zinc(), "@site \"Site \\\"1\\\"\"");
+        let sym = Symbol::new("^steam-boiler".to_owned()).unwrap();
+        assert_eq!(sym.to_zinc(), "^steam-boiler");
+        let qname = Qname::new("core::now".to_owned()).unwrap();
+        assert_eq!(qname.to_zinc(), "\"core::now\"");
+        assert_eq!(Coord::new(37.55, -77.45).to_zinc(), "C(37.55,-77.45)");
+        let date_time: DateTime =
+            "2024-03-01T08:00:00-05:00 New_York".parse().unwrap();
+        assert_eq!(date_time.to_zinc(), "2024-03-01T08:00:00-05:00 New_York");
+        assert_eq!(Time::new(8, 0, 0).unwrap().to_zinc(), "08:00:00");
+        let xstr = Xstr::new("Span".to_owned(), "today".to_owned()).unwrap();
+        assert_eq!(xstr.to_zinc(), "Span(\"today\")");
+        let uri = Uri::new("http://a.com/`x`\\y".to_owned());
+        assert_eq!(uri.to_zinc(), "`http://a.com/\\`x\\`\\\\y`");
+        assert_eq!("a\"b$c\n".to_zinc(), "\"a\\\"b\\$c\\n\"");
+    }
+
+    #[test]
+    fn collections_to_zinc_works() {
+        let mut dict = Dict::new();
+        dict.insert(tn("site"), Value::Marker);
+        dict.insert(tn("dis"), "Site".into());
+        dict.insert(tn("tags"), vec![Value::Null, true.into()].into());
+        assert_eq!(dict.to_zinc(), "{site dis:\"Site\" tags:[N, T]}");
+    }
+
+    #[test]
+    fn grid_to_zinc_works() {
+        let mut row1 = Dict::new();
+        row1.insert(tn("dis"), "A".into());
+        row1.insert(
+            tn("area"),
+            Number::new(10.0, Some("ft²".to_owned())).into(),
+        );
+        let mut row2 = Dict::new();
+        row2.insert(tn("dis"), "B".into());
+        let mut grid = Grid::from_rows(vec![row1, row2]);
+        grid.meta_mut().insert(tn("hisStart"), Value::Marker);
+        grid.column_meta_mut("dis")
+            .unwrap()
+            .insert(tn("dis"), "Name".into());
+
+        assert_eq!(
+            grid.to_zinc(),
+            "ver:\"3.0\" hisStart\ndis dis:\"Name\",area\n\"A\",10ft²\n\"B\",\n"
+        );
+        assert_eq!(Grid::empty().to_zinc(), "ver:\"3.0\"\nempty\n");
+
+        let nested: Value = Grid::empty().into();
+        assert_eq!(nested.to_zinc(), "<<\nver:\"3.0\"\nempty\n>>");
+    }
+}
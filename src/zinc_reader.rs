@@ -0,0 +1,636 @@
+use crate::tokenizer::{Token, TokenError, Tokenizer};
+use crate::{
+    Bin, Column, Coord, Dict, Grid, Number, Position, Qname, TagName, Value,
+    Xstr,
+};
+use std::io::BufRead;
+use thiserror::Error;
+
+/// An error indicating that Zinc could not be parsed.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Could not parse Zinc at line {line}, column {column}: {message}")]
+pub struct ParseZincError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseZincError {
+    fn new(position: Position, message: String) -> Self {
+        Self {
+            line: position.line,
+            column: position.column,
+            message,
+        }
+    }
+
+    /// Return the line at which the error occurred, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Return the column at which the error occurred, starting from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Return a description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::convert::From<TokenError> for ParseZincError {
+    fn from(err: TokenError) -> Self {
+        Self::new(err.position, err.message)
+    }
+}
+
+/// Something which can be decoded from Zinc
+/// (the default text format used by Project Haystack).
+pub trait FromZinc: Sized {
+    fn from_zinc(zinc: &str) -> Result<Self, ParseZincError>;
+}
+
+impl FromZinc for Grid {
+    fn from_zinc(zinc: &str) -> Result<Self, ParseZincError> {
+        ZincReader::new(zinc.as_bytes()).read_grid()
+    }
+}
+
+impl FromZinc for Value {
+    fn from_zinc(zinc: &str) -> Result<Self, ParseZincError> {
+        let mut reader = ZincReader::new(zinc.as_bytes());
+        let value = reader.read_value()?;
+        reader.skip_newlines()?;
+        reader.expect(Token::Eof)?;
+        Ok(value)
+    }
+}
+
+impl FromZinc for Qname {
+    fn from_zinc(zinc: &str) -> Result<Self, ParseZincError> {
+        let start = Position { line: 1, column: 1 };
+        match Value::from_zinc(zinc)? {
+            Value::Str(s) => s
+                .parse::<Qname>()
+                .map_err(|err| ParseZincError::new(start, err.to_string())),
+            _ => Err(ParseZincError::new(
+                start,
+                "Qname Zinc value must be a string".to_owned(),
+            )),
+        }
+    }
+}
+
+/// Reads Zinc from any `BufRead`, allowing large grids to be read one row
+/// at a time.
+///
+/// # Example
+/// ```rust
+/// use raystack_core::ZincReader;
+/// let zinc = "ver:\"3.0\"\nid,dis\n@a,\"Site A\"\n@b,\"Site B\"\n";
+/// let mut reader = ZincReader::new(zinc.as_bytes());
+/// let header = reader.read_header().unwrap();
+/// assert_eq!(header.columns().len(), 2);
+/// while let Some(row) = reader.read_row().unwrap() {
+///     assert!(row.get_ref("id").is_some());
+/// }
+/// ```
+pub struct ZincReader<R> {
+    tokenizer: Tokenizer<R>,
+    cur: Token,
+    cur_position: Position,
+    peek: Token,
+    peek_position: Position,
+    started: bool,
+    /// The columns of the grid currently being read, once its header has
+    /// been read.
+    columns: Option<Vec<TagName>>,
+}
+
+impl<R: BufRead> ZincReader<R> {
+    /// Create a new `ZincReader`.
+    pub fn new(reader: R) -> Self {
+        let start = Position { line: 1, column: 1 };
+        Self {
+            tokenizer: Tokenizer::new(reader),
+            cur: Token::Eof,
+            cur_position: start,
+            peek: Token::Eof,
+            peek_position: start,
+            started: false,
+            columns: None,
+        }
+    }
+
+    /// Read an entire grid.
+    pub fn read_grid(&mut self) -> Result<Grid, ParseZincError> {
+        let header = self.read_header()?;
+        let (meta, columns) = (header.meta().clone(), header.columns());
+        let mut rows = Vec::new();
+        while let Some(row) = self.read_row()? {
+            rows.push(row);
+        }
+        Ok(Grid::new(meta, columns.to_vec(), rows))
+    }
+
+    /// Read the header of a grid, returning a grid with metadata and columns
+    /// but no rows. The rows can then be read using `read_row`.
+    pub fn read_header(&mut self) -> Result<Grid, ParseZincError> {
+        self.start()?;
+        self.skip_newlines()?;
+        let grid = self.read_grid_header()?;
+        self.columns =
+            Some(grid.column_names().cloned().collect::<Vec<TagName>>());
+        Ok(grid)
+    }
+
+    /// Read the next row of the grid whose header was read by `read_header`,
+    /// returning `None` once there are no more rows.
+    pub fn read_row(&mut self) -> Result<Option<Dict>, ParseZincError> {
+        let columns = match self.columns.take() {
+            Some(columns) => columns,
+            None => {
+                return Err(ParseZincError::new(
+                    self.cur_position,
+                    "The grid header has not been read".to_owned(),
+                ))
+            }
+        };
+        let row = self.read_grid_row(&columns)?;
+        if row.is_some() {
+            self.columns = Some(columns);
+        } else {
+            self.expect(Token::Eof)?;
+        }
+        Ok(row)
+    }
+
+    /// Read a single Zinc value.
+    pub fn read_value(&mut self) -> Result<Value, ParseZincError> {
+        self.start()?;
+        self.parse_value()
+    }
+
+    /// Read the first two tokens, if they have not already been read.
+    fn start(&mut self) -> Result<(), ParseZincError> {
+        if !self.started {
+            self.started = true;
+            self.advance()?;
+            self.advance()?;
+        }
+        Ok(())
+    }
+
+    fn advance(&mut self) -> Result<(), ParseZincError> {
+        let (token, position) = self.tokenizer.next_token()?;
+        self.cur = std::mem::replace(&mut self.peek, token);
+        self.cur_position =
+            std::mem::replace(&mut self.peek_position, position);
+        Ok(())
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseZincError> {
+        Err(ParseZincError::new(self.cur_position, message))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseZincError> {
+        if self.cur == expected {
+            self.advance()
+        } else {
+            self.error(format!("Expected {} but found {}", expected, self.cur))
+        }
+    }
+
+    fn skip_newlines(&mut self) -> Result<(), ParseZincError> {
+        while self.cur == Token::Nl {
+            self.advance()?;
+        }
+        Ok(())
+    }
+
+    fn read_tag_name(&mut self) -> Result<TagName, ParseZincError> {
+        match &self.cur {
+            Token::Id(id) => match TagName::new(id.clone()) {
+                Some(name) => {
+                    self.advance()?;
+                    Ok(name)
+                }
+                None => self.error(format!("Invalid tag name '{}'", id)),
+            },
+            token => {
+                self.error(format!("Expected a tag name but found {}", token))
+            }
+        }
+    }
+
+    fn read_grid_header(&mut self) -> Result<Grid, ParseZincError> {
+        match &self.cur {
+            Token::Id(id) if id == "ver" => self.advance()?,
+            _ => return self.error("Expected 'ver' in grid header".to_owned()),
+        }
+        self.expect(Token::Colon)?;
+        match &self.cur {
+            Token::Val(Value::Str(ver)) if ver == "3.0" || ver == "2.0" => {
+                self.advance()?
+            }
+            token => {
+                return self
+                    .error(format!("Unsupported Zinc version {}", token))
+            }
+        }
+        let meta = self.read_meta()?;
+        self.expect(Token::Nl)?;
+
+        let mut columns = Vec::new();
+        loop {
+            let name = self.read_tag_name()?;
+            let meta = self.read_meta()?;
+            columns.push(Column::new(name, meta));
+            if self.cur == Token::Comma {
+                self.advance()?;
+            } else {
+                break;
+            }
+        }
+        self.end_of_line()?;
+
+        let is_empty = columns.len() == 1 && {
+            let name: &str = columns[0].name().as_ref();
+            name == "empty"
+        };
+        if is_empty {
+            columns.clear();
+        }
+
+        Ok(Grid::new(meta, columns, Vec::new()))
+    }
+
+    /// Read the end of a line in a grid, which may also be the end of the
+    /// input or the end of a nested grid.
+    fn end_of_line(&mut self) -> Result<(), ParseZincError> {
+        match self.cur {
+            Token::Nl => self.advance(),
+            Token::Eof | Token::GtGt => Ok(()),
+            _ => self.error(format!("Expected newline but found {}", self.cur)),
+        }
+    }
+
+    fn is_end_of_grid(&self) -> bool {
+        matches!(self.cur, Token::Nl | Token::Eof | Token::GtGt)
+    }
+
+    fn read_grid_row(
+        &mut self,
+        columns: &[TagName],
+    ) -> Result<Option<Dict>, ParseZincError> {
+        if self.is_end_of_grid() {
+            if self.cur == Token::Nl {
+                self.skip_newlines()?;
+            }
+            return Ok(None);
+        }
+
+        let mut row = Dict::new();
+        for (index, name) in columns.iter().enumerate() {
+            if !matches!(self.cur, Token::Comma | Token::Nl | Token::Eof) {
+                let value = self.parse_value()?;
+                if !value.is_null() {
+                    row.insert(name.clone(), value);
+                }
+            }
+            if index + 1 < columns.len() {
+                self.expect(Token::Comma)?;
+            }
+        }
+        self.end_of_line()?;
+        Ok(Some(row))
+    }
+
+    /// Read tags separated by whitespace, like those in grid or
+    /// column metadata.
+    fn read_meta(&mut self) -> Result<Dict, ParseZincError> {
+        let mut meta = Dict::new();
+        while let Token::Id(_) = self.cur {
+            let name = self.read_tag_name()?;
+            let value = if self.cur == Token::Colon {
+                self.advance()?;
+                self.parse_value()?
+            } else {
+                Value::Marker
+            };
+            meta.insert(name, value);
+        }
+        Ok(meta)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ParseZincError> {
+        match &self.cur {
+            Token::Val(value) => {
+                let mut value = value.clone();
+                self.advance()?;
+                if let (Value::Ref(hsref), Token::Val(Value::Str(dis))) =
+                    (&mut value, &self.cur)
+                {
+                    // A ref may be followed by its display string.
+                    hsref.set_dis(Some(dis.clone()));
+                    self.advance()?;
+                }
+                Ok(value)
+            }
+            Token::Id(id) => {
+                let id = id.clone();
+                self.parse_id_value(id)
+            }
+            Token::Minus => {
+                self.advance()?;
+                match &self.cur {
+                    Token::Id(id) if id == "INF" => {
+                        self.advance()?;
+                        Ok(Number::new_unitless(f64::NEG_INFINITY).into())
+                    }
+                    token => self.error(format!("Unexpected {}", token)),
+                }
+            }
+            Token::LBracket => self.parse_list(),
+            Token::LBrace => self.parse_dict(),
+            Token::LtLt => self.parse_nested_grid(),
+            token => self.error(format!("Unexpected {}", token)),
+        }
+    }
+
+    fn parse_id_value(&mut self, id: String) -> Result<Value, ParseZincError> {
+        let is_call = self.peek == Token::LParen;
+        let value = match id.as_ref() {
+            "N" => Value::Null,
+            "M" => Value::Marker,
+            "R" => Value::RemoveMarker,
+            "NA" => Value::Na,
+            "T" => Value::Bool(true),
+            "F" => Value::Bool(false),
+            "NaN" => Number::new_unitless(f64::NAN).into(),
+            "INF" => Number::new_unitless(f64::INFINITY).into(),
+            "C" if is_call => return self.parse_coord(),
+            _ if is_call
+                && id.starts_with(|c: char| c.is_ascii_uppercase()) =>
+            {
+                return self.parse_xstr(id)
+            }
+            _ => return self.error(format!("Unexpected identifier '{}'", id)),
+        };
+        self.advance()?;
+        Ok(value)
+    }
+
+    fn parse_coord(&mut self) -> Result<Value, ParseZincError> {
+        self.advance()?;
+        self.expect(Token::LParen)?;
+        let lat = self.parse_coord_part()?;
+        self.expect(Token::Comma)?;
+        let lng = self.parse_coord_part()?;
+        self.expect(Token::RParen)?;
+        Ok(Coord::new(lat, lng).into())
+    }
+
+    fn parse_coord_part(&mut self) -> Result<f64, ParseZincError> {
+        match &self.cur {
+            Token::Val(Value::Number(Number::Basic(num)))
+                if num.unit().is_none() =>
+            {
+                let value = num.value();
+                self.advance()?;
+                Ok(value)
+            }
+            token => self
+                .error(format!("Expected a coord number but found {}", token)),
+        }
+    }
+
+    fn parse_xstr(
+        &mut self,
+        type_name: String,
+    ) -> Result<Value, ParseZincError> {
+        self.advance()?;
+        self.expect(Token::LParen)?;
+        let value = match &self.cur {
+            Token::Val(Value::Str(s)) => s.clone(),
+            token => {
+                return self
+                    .error(format!("Expected a string but found {}", token))
+            }
+        };
+        self.advance()?;
+        self.expect(Token::RParen)?;
+        if type_name == "Bin" {
+            match Bin::new(value) {
+                Ok(bin) => Ok(bin.into()),
+                Err(err) => self.error(err.to_string()),
+            }
+        } else {
+            match Xstr::new(type_name, value) {
+                Ok(xstr) => Ok(xstr.into()),
+                Err(err) => self.error(err.to_string()),
+            }
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<Value, ParseZincError> {
+        self.advance()?;
+        let mut list = Vec::new();
+        loop {
+            self.skip_newlines()?;
+            if self.cur == Token::RBracket {
+                break;
+            }
+            list.push(self.parse_value()?);
+            self.skip_newlines()?;
+            if self.cur == Token::Comma {
+                self.advance()?;
+            } else {
+                break;
+            }
+        }
+        self.expect(Token::RBracket)?;
+        Ok(Value::List(list))
+    }
+
+    fn parse_dict(&mut self) -> Result<Value, ParseZincError> {
+        self.advance()?;
+        let mut dict = Dict::new();
+        loop {
+            self.skip_newlines()?;
+            if self.cur == Token::RBrace {
+                break;
+            }
+            let name = self.read_tag_name()?;
+            let value = if self.cur == Token::Colon {
+                self.advance()?;
+                self.parse_value()?
+            } else {
+                Value::Marker
+            };
+            dict.insert(name, value);
+            if self.cur == Token::Comma {
+                self.advance()?;
+            }
+        }
+        self.expect(Token::RBrace)?;
+        Ok(Value::Dict(dict))
+    }
+
+    fn parse_nested_grid(&mut self) -> Result<Value, ParseZincError> {
+        self.advance()?;
+        self.skip_newlines()?;
+        let grid = self.read_grid_header()?;
+        let columns: Vec<TagName> = grid.column_names().cloned().collect();
+        let mut rows = Vec::new();
+        while self.cur != Token::GtGt {
+            if self.cur == Token::Nl {
+                self.advance()?;
+                continue;
+            }
+            if self.cur == Token::Eof {
+                return self
+                    .error("Expected '>>' to end nested grid".to_owned());
+            }
+            if let Some(row) = self.read_grid_row(&columns)? {
+                rows.push(row);
+            }
+        }
+        self.advance()?;
+        let grid =
+            Grid::new(grid.meta().clone(), grid.columns().to_vec(), rows);
+        Ok(grid.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FromZinc, ZincReader};
+    use crate::{Bin, Coord, Grid, Number, Qname, Ref, Value, Zinc};
+
+    #[test]
+    fn read_grid_works() {
+        let zinc = "ver:\"3.0\" database:\"test\" dis:\"Site Energy Summary\"
+siteName dis:\"Sites\", val dis:\"Value\" unit:\"kW\"
+\"Site 1\", 356.214kW
+\"Site 2\", 463.028kW
+";
+        let grid = Grid::from_zinc(zinc).unwrap();
+        assert_eq!(grid.meta().get_str("database"), Some("test"));
+        assert_eq!(grid.columns().len(), 2);
+        assert_eq!(
+            grid.column("val").unwrap().meta().get_str("unit"),
+            Some("kW")
+        );
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.row(1).unwrap().get_str("siteName"), Some("Site 2"));
+        assert_eq!(
+            grid.row(0).unwrap().get_number("val"),
+            Some(&Number::new(356.214, Some("kW".to_owned())))
+        );
+    }
+
+    #[test]
+    fn read_empty_cells_and_special_values() {
+        let zinc = "ver:\"3.0\"\na,b,c\nN,,M\n-INF,NA,C(12.5,-77.25)\n";
+        let grid = Grid::from_zinc(zinc).unwrap();
+        let row0 = grid.row(0).unwrap();
+        assert!(!row0.contains("a"));
+        assert!(!row0.contains("b"));
+        assert!(row0.has_marker("c"));
+        let row1 = grid.row(1).unwrap();
+        assert_eq!(
+            row1.get_number("a"),
+            Some(&Number::new_unitless(f64::NEG_INFINITY))
+        );
+        assert_eq!(row1.get("b"), Some(&Value::Na));
+        assert_eq!(row1.get_coord("c"), Some(&Coord::new(12.5, -77.25)));
+    }
+
+    #[test]
+    fn read_nested_collections() {
+        let zinc = "ver:\"3.0\"
+type,val
+\"list\",[1, 2, @a \"Site\"]
+\"dict\",{dis:\"Dict!\" foo}
+\"grid\",<<
+ver:\"3.0\"
+a,b
+1,2
+3,4
+>>
+\"after\",T
+";
+        let grid = Grid::from_zinc(zinc).unwrap();
+        assert_eq!(grid.len(), 4);
+        let list = grid.row(0).unwrap().get_list("val").unwrap();
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[2], Value::Ref(Ref::new("@a".to_owned()).unwrap()));
+        assert_eq!(list[2].as_ref().unwrap().dis(), Some("Site"));
+        let dict = grid.row(1).unwrap().get_dict("val").unwrap();
+        assert!(dict.has_marker("foo"));
+        let nested = grid.row(2).unwrap().get_grid("val").unwrap();
+        assert_eq!(nested.len(), 2);
+        assert_eq!(grid.row(3).unwrap().get_bool("val"), Some(true));
+    }
+
+    #[test]
+    fn round_trip_works() {
+        let zinc = "ver:\"3.0\" hisStart\ndis dis:\"Name\",area\n\"A\",10ft²\n\"B\",\n";
+        let grid = Grid::from_zinc(zinc).unwrap();
+        assert_eq!(grid.to_zinc(), zinc);
+    }
+
+    #[test]
+    fn exact_numbers_round_trip() {
+        let zinc = "ver:\"3.0\"\ncounter,big\n18446744073709551615Wh,1.2345678901234567891e300\n";
+        let grid = Grid::from_zinc(zinc).unwrap();
+        let row = grid.row(0).unwrap();
+        assert_eq!(
+            row.get_number("counter"),
+            Some(&Number::new_integer(u64::MAX.into(), Some("Wh".to_owned())))
+        );
+        assert_eq!(grid.to_zinc(), zinc);
+    }
+
+    #[test]
+    fn bins_work() {
+        let value = Value::from_zinc("Bin(\"text/plain; charset=utf-8\")");
+        let bin = Bin::new("text/plain; charset=utf-8".to_owned()).unwrap();
+        assert_eq!(value.unwrap(), bin.clone().into());
+        assert_eq!(bin.to_zinc(), "Bin(\"text/plain; charset=utf-8\")");
+        assert!(Value::from_zinc("Bin(\"plain\")").is_err());
+    }
+
+    #[test]
+    fn read_rows_one_at_a_time() {
+        let zinc = "ver:\"3.0\"\nempty\n";
+        let mut reader = ZincReader::new(zinc.as_bytes());
+        let header = reader.read_header().unwrap();
+        assert!(header.columns().is_empty());
+        assert!(reader.read_row().unwrap().is_none());
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        let zinc = "ver:\"3.0\"\na,b\n1,2\n3,}\n";
+        let err = Grid::from_zinc(zinc).unwrap_err();
+        assert_eq!(err.line(), 4);
+        assert_eq!(err.column(), 3);
+
+        let err = Value::from_zinc("[1, 2").unwrap_err();
+        assert_eq!(err.line(), 1);
+        assert_eq!(err.column(), 6);
+    }
+
+    #[test]
+    fn read_qname_works() {
+        let qname = Qname::from_zinc("\"core::now\"").unwrap();
+        assert_eq!(qname.lib(), Some("core"));
+        assert_eq!(Qname::from_zinc(&qname.to_zinc()).unwrap(), qname);
+        assert!(Qname::from_zinc("\"core::\"").is_err());
+        assert!(Qname::from_zinc("^core").is_err());
+    }
+}